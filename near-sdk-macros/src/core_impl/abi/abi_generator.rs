@@ -77,7 +77,8 @@ impl ImplItemMethodInfo {
     /// If args are serialized with Borsh it will not include `#[derive(borsh::BorshSchema)]`.
     pub fn abi_struct(&self) -> TokenStream2 {
         let function_name_str = self.attr_signature_info.ident.to_string();
-        let function_doc = match parse_rustdoc(&self.attr_signature_info.non_bindgen_attrs) {
+        let rustdoc = parse_rustdoc(&self.attr_signature_info.non_bindgen_attrs);
+        let function_doc = match join_doc(rustdoc, access_doc(&self.attr_signature_info)) {
             Some(doc) => quote! { Some(#doc.to_string()) },
             None => quote! { None },
         };
@@ -88,6 +89,8 @@ impl ImplItemMethodInfo {
         );
         let AttrSigInfo { is_payable, is_private, is_handles_result, .. } =
             self.attr_signature_info;
+        // A method requiring a deposit accepts it, even without `#[payable]`.
+        let is_payable = is_payable || self.attr_signature_info.deposit.is_some();
 
         let mut params = Vec::<TokenStream2>::new();
         let mut callbacks = Vec::<TokenStream2>::new();
@@ -117,8 +120,8 @@ impl ImplItemMethodInfo {
                     callbacks.push(generate_abi_type(typ, &arg.serializer_ty));
                 }
                 BindgenArgType::CallbackResultArg => {
-                    let typ = if let Some(ok_type) = utils::extract_ok_type(typ) {
-                        ok_type
+                    let ok_types = if let Some(ok_types) = utils::extract_ok_types(typ) {
+                        ok_types
                     } else {
                         return syn::Error::new_spanned(
                             &arg.ty,
@@ -127,7 +130,9 @@ impl ImplItemMethodInfo {
                         )
                         .into_compile_error();
                     };
-                    callbacks.push(generate_abi_type(typ, &arg.serializer_ty));
+                    for typ in ok_types {
+                        callbacks.push(generate_abi_type(typ, &arg.serializer_ty));
+                    }
                 }
                 BindgenArgType::CallbackArgVec => {
                     if callback_vec.is_none() {
@@ -277,3 +282,37 @@ pub fn parse_rustdoc(attrs: &[Attribute]) -> Option<String> {
         Some(doc)
     }
 }
+
+/// Describes the checks of `#[only]`, `#[deposit]`, `#[when_not_paused]` and `#[non_reentrant]`,
+/// which have no field in the ABI.
+fn access_doc(attr_sig_info: &AttrSigInfo) -> Option<String> {
+    let only =
+        attr_sig_info.only.as_ref().map(|only| format!(" Only callable by {}.", only.describe()));
+    let deposit = attr_sig_info
+        .deposit
+        .as_ref()
+        .map(|deposit| format!(" Requires an attached deposit of {}.", deposit.describe()));
+    let paused = if attr_sig_info.is_when_not_paused {
+        Some(" Panics while the contract is paused.".to_string())
+    } else {
+        None
+    };
+    let non_reentrant = attr_sig_info
+        .non_reentrant
+        .as_ref()
+        .map(|non_reentrant| format!(" Not reentrant, holds the guard `{}`.", non_reentrant.guard));
+    let lines =
+        only.into_iter().chain(deposit).chain(paused).chain(non_reentrant).collect::<Vec<_>>();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+fn join_doc(doc: Option<String>, access_doc: Option<String>) -> Option<String> {
+    match (doc, access_doc) {
+        (Some(doc), Some(access_doc)) => Some(format!("{}\n\n{}", doc, access_doc)),
+        (doc, access_doc) => doc.or(access_doc),
+    }
+}
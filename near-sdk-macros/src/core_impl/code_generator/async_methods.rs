@@ -0,0 +1,517 @@
+use crate::core_impl::utils;
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote};
+use std::collections::HashSet;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{
+    parse_quote, Attribute, Block, Error, Expr, FnArg, Ident, ImplItem, ImplItemMethod, ItemImpl,
+    Local, Pat, PatIdent, PatType, Stmt, Token, Type,
+};
+
+/// Splits each `async fn` of the contract impl at its `.await` points. The method runs until the
+/// first `.await` and returns the awaited promise chained with a generated `#[private]` callback,
+/// which receives the local variables still in use and the awaited result, and runs until the
+/// next `.await`, and so on. The callbacks are added to the impl, so they are exported and get
+/// `ext` wrappers like the methods written by hand.
+pub(crate) fn split_async_methods(item: &mut ItemImpl) -> syn::Result<()> {
+    let mut items = Vec::with_capacity(item.items.len());
+    for subitem in std::mem::take(&mut item.items) {
+        match subitem {
+            ImplItem::Method(method) if method.sig.asyncness.is_some() => {
+                if let Some((_, path, _)) = &item.trait_ {
+                    return Err(Error::new(
+                        path.span(),
+                        "Async methods are only supported in an impl section without a trait.",
+                    ));
+                }
+                items.extend(split_method(method)?.into_iter().map(ImplItem::Method));
+            }
+            subitem => items.push(subitem),
+        }
+    }
+    item.items = items;
+    Ok(())
+}
+
+/// A variable which can be passed to a callback if it has a type.
+struct Binding {
+    ident: Ident,
+    mutability: Option<Token![mut]>,
+    ty: Option<Type>,
+}
+
+/// The statements of an async method up to an `.await`, or up to the end of the method.
+struct Segment {
+    stmts: Vec<Stmt>,
+    /// The awaited promise ending the segment.
+    await_point: Option<AwaitPoint>,
+}
+
+/// A promise awaited by an async method.
+struct AwaitPoint {
+    promise: Expr,
+    /// The variable the result is bound to.
+    result: Option<Binding>,
+    /// Whether the result is discarded without a `Result` type, in which case the callback
+    /// requires the promise to have succeeded.
+    check_success: bool,
+}
+
+fn split_method(mut method: ImplItemMethod) -> syn::Result<Vec<ImplItemMethod>> {
+    let sig = &method.sig;
+    match sig.receiver() {
+        Some(FnArg::Receiver(receiver))
+            if receiver.reference.is_some() && receiver.mutability.is_some() => {}
+        receiver => {
+            return Err(Error::new(
+                receiver.map_or(sig.ident.span(), |receiver| receiver.span()),
+                "Async methods must take `&mut self`, since view calls can't create promises.",
+            ))
+        }
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(Error::new(
+            sig.generics.span(),
+            "Async methods are not allowed to have type parameters.",
+        ));
+    }
+    let receiver = sig.receiver().cloned();
+    let mut scope = vec![];
+    for arg in &sig.inputs {
+        if let FnArg::Typed(pat_type) = arg {
+            if !pat_type.attrs.is_empty() {
+                return Err(Error::new(
+                    pat_type.span(),
+                    "Arguments of async methods are not allowed to have attributes.",
+                ));
+            }
+            scope.extend(pat_bindings(&pat_type.pat, Some(&pat_type.ty)));
+        }
+    }
+
+    let segments = split_stmts(std::mem::take(&mut method.block.stmts))?;
+    let mut errors = BodyCheck::default();
+    for (i, segment) in segments.iter().enumerate() {
+        errors.allow_early_return = i == segments.len() - 1;
+        for stmt in &segment.stmts {
+            errors.visit_stmt(stmt);
+        }
+        if let Some(await_point) = &segment.await_point {
+            errors.visit_expr(&await_point.promise);
+        }
+    }
+    if let Some(error) = errors.errors.into_iter().reduce(|mut acc, e| {
+        acc.combine(e);
+        acc
+    }) {
+        return Err(error);
+    }
+
+    method.sig.asyncness = None;
+    if segments.len() == 1 {
+        method.block.stmts = segments.into_iter().next().unwrap().stmts;
+        return Ok(vec![method]);
+    }
+    let name = method.sig.ident.clone();
+    let output = method.sig.output.clone();
+    // The attributes handling the final result move to the last callback, a reentrancy guard is
+    // held across all of them.
+    let (result_attrs, attrs): (Vec<Attribute>, Vec<Attribute>) =
+        std::mem::take(&mut method.attrs).into_iter().partition(|attr| {
+            attr.path.is_ident("handle_result") || attr.path.is_ident("result_serializer")
+        });
+    method.attrs = attrs;
+    let guard_attrs: Vec<Attribute> =
+        method.attrs.iter().filter(|attr| attr.path.is_ident("non_reentrant")).cloned().collect();
+    // A `mut` variable is declared again by each callback receiving it, whether or not the
+    // callback mutates it.
+    method.attrs.push(parse_quote!(#[allow(unused_mut)]));
+
+    let mut methods = vec![];
+    let mut callback_inputs: Vec<TokenStream2> = vec![];
+    let mut callback_check = quote!();
+    for (i, segment) in segments.iter().enumerate() {
+        let Segment { stmts, await_point } = segment;
+        for stmt in stmts {
+            if let Stmt::Local(local) = stmt {
+                scope.extend(pat_bindings(&local.pat, None));
+            }
+        }
+        let (output, tail, next_inputs) = match await_point {
+            Some(AwaitPoint { promise: base, result, .. }) => {
+                let used = used_idents(&segments[i + 1..]);
+                let result_ident = result.as_ref().map(|binding| &binding.ident);
+                let mut live: Vec<&Binding> = vec![];
+                for binding in scope.iter().rev() {
+                    if !used.contains(&binding.ident.to_string())
+                        || Some(&binding.ident) == result_ident
+                        || live.iter().any(|b| b.ident == binding.ident)
+                    {
+                        continue;
+                    }
+                    if binding.ty.is_none() {
+                        return Err(Error::new(
+                            binding.ident.span(),
+                            format!(
+                                "Variable `{}` is used after `.await`, so it needs a type annotation \
+                                 to be passed to the callback.",
+                                binding.ident
+                            ),
+                        ));
+                    }
+                    live.push(binding);
+                }
+                live.reverse();
+                let args = live.iter().map(|binding| &binding.ident);
+                let next = callback_name(&name, i + 1);
+                let tail = quote! {
+                    #base.then(Self::ext(near_sdk::env::current_account_id()).#next(#(#args),*))
+                };
+                let mut next_inputs: Vec<TokenStream2> =
+                    receiver.iter().map(|r| quote!(#r)).collect();
+                next_inputs.extend(live.iter().map(|binding| {
+                    let Binding { ident, mutability, ty } = binding;
+                    quote!(#mutability #ident: #ty)
+                }));
+                if let Some(Binding { ident, mutability, ty }) = result {
+                    let attr = if utils::type_is(ty.as_ref().unwrap(), "Result") {
+                        quote!(#[callback_result])
+                    } else {
+                        quote!(#[callback_unwrap])
+                    };
+                    next_inputs.push(quote!(#attr #mutability #ident: #ty));
+                }
+                (parse_quote!(-> near_sdk::Promise), tail, next_inputs)
+            }
+            None => (output.clone(), quote!(), vec![]),
+        };
+        if i == 0 {
+            method.sig.output = output;
+            let block: Block = parse_quote!({ #(#stmts)* #tail });
+            method.block.stmts = block.stmts;
+            methods.push(method.clone());
+        } else {
+            let callback = callback_name(&name, i);
+            let doc = format!(" Resumes `{}` after its `.await` number {}.", name, i);
+            let result_attrs = if await_point.is_none() { &result_attrs[..] } else { &[] };
+            methods.push(parse_quote! {
+                #[doc = #doc]
+                #[private]
+                #[allow(unused_mut)]
+                #(#guard_attrs)*
+                #(#result_attrs)*
+                pub fn #callback(#(#callback_inputs),*) #output {
+                    #callback_check
+                    #(#stmts)*
+                    #tail
+                }
+            });
+        }
+        if let Some(AwaitPoint { result: Some(result), .. }) = await_point {
+            scope.push(Binding {
+                ident: result.ident.clone(),
+                mutability: result.mutability,
+                ty: result.ty.clone(),
+            });
+        }
+        callback_check = match await_point {
+            Some(AwaitPoint { check_success: true, .. }) => {
+                let error = format!(
+                    "The promise awaited by `{}` at `.await` number {} failed",
+                    name,
+                    i + 1
+                );
+                quote!(near_sdk::require!(near_sdk::is_promise_success(), #error);)
+            }
+            _ => quote!(),
+        };
+        callback_inputs = next_inputs;
+    }
+    Ok(methods)
+}
+
+fn callback_name(method: &Ident, index: usize) -> Ident {
+    format_ident!("__{}_resume_{}", method, index)
+}
+
+/// Splits the statements at the top-level `.await` expressions.
+fn split_stmts(stmts: Vec<Stmt>) -> syn::Result<Vec<Segment>> {
+    let mut segments = vec![];
+    let mut current = vec![];
+    for stmt in stmts {
+        let await_point = match &stmt {
+            Stmt::Semi(Expr::Await(expr), _) => {
+                Some(AwaitPoint { promise: *expr.base.clone(), result: None, check_success: true })
+            }
+            Stmt::Local(Local { pat, init: Some((_, init)), .. }) => match init.as_ref() {
+                Expr::Await(expr) => {
+                    let result = await_binding(pat)?;
+                    // A discarded `Result` lets the method ignore failures.
+                    let check_success = result.is_none()
+                        && !matches!(pat, Pat::Type(PatType { ty, .. }) if utils::type_is(ty, "Result"));
+                    Some(AwaitPoint { promise: *expr.base.clone(), result, check_success })
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        match await_point {
+            Some(await_point) => segments.push(Segment {
+                stmts: std::mem::take(&mut current),
+                await_point: Some(await_point),
+            }),
+            None => current.push(stmt),
+        }
+    }
+    segments.push(Segment { stmts: current, await_point: None });
+    Ok(segments)
+}
+
+/// The variable which the result of `.await` is bound to, which is passed to the callback.
+fn await_binding(pat: &Pat) -> syn::Result<Option<Binding>> {
+    match pat {
+        Pat::Wild(_) => Ok(None),
+        Pat::Type(PatType { pat, ty, .. }) => match pat.as_ref() {
+            Pat::Wild(_) => Ok(None),
+            Pat::Ident(PatIdent { by_ref: None, subpat: None, mutability, ident, .. }) => {
+                Ok(Some(Binding {
+                    ident: ident.clone(),
+                    mutability: *mutability,
+                    ty: Some((**ty).clone()),
+                }))
+            }
+            _ => Err(Error::new(
+                pat.span(),
+                "The result of `.await` can only be bound to a single variable.",
+            )),
+        },
+        _ => Err(Error::new(
+            pat.span(),
+            "The result of `.await` needs a type annotation to be passed to the callback.",
+        )),
+    }
+}
+
+/// The variables bound by a pattern. Only a single variable with a type annotation can be passed
+/// to a callback.
+fn pat_bindings(pat: &Pat, ty: Option<&Type>) -> Vec<Binding> {
+    let (pat, ty) = match pat {
+        Pat::Type(PatType { pat, ty, .. }) => (pat.as_ref(), Some(ty.as_ref())),
+        pat => (pat, ty),
+    };
+    if let Pat::Ident(PatIdent { by_ref: None, subpat: None, mutability, ident, .. }) = pat {
+        return vec![Binding { ident: ident.clone(), mutability: *mutability, ty: ty.cloned() }];
+    }
+    #[derive(Default)]
+    struct Bindings(Vec<Binding>);
+    impl<'ast> Visit<'ast> for Bindings {
+        fn visit_pat_ident(&mut self, i: &'ast PatIdent) {
+            self.0.push(Binding { ident: i.ident.clone(), mutability: None, ty: None });
+            visit::visit_pat_ident(self, i);
+        }
+    }
+    let mut bindings = Bindings::default();
+    bindings.visit_pat(pat);
+    bindings.0
+}
+
+/// The names of the variables which may be used by the segments.
+fn used_idents(segments: &[Segment]) -> HashSet<String> {
+    #[derive(Default)]
+    struct Used(HashSet<String>);
+    impl Used {
+        fn visit_tokens(&mut self, tokens: TokenStream2) {
+            for token in tokens {
+                match token {
+                    TokenTree::Ident(ident) => {
+                        self.0.insert(ident.to_string());
+                    }
+                    TokenTree::Group(group) => self.visit_tokens(group.stream()),
+                    _ => {}
+                }
+            }
+        }
+    }
+    impl<'ast> Visit<'ast> for Used {
+        fn visit_path(&mut self, i: &'ast syn::Path) {
+            if let Some(ident) = i.get_ident() {
+                self.0.insert(ident.to_string());
+            }
+            visit::visit_path(self, i);
+        }
+        fn visit_macro(&mut self, i: &'ast syn::Macro) {
+            self.visit_tokens(i.tokens.clone());
+        }
+    }
+    let mut used = Used::default();
+    for segment in segments {
+        for stmt in &segment.stmts {
+            used.visit_stmt(stmt);
+        }
+        if let Some(await_point) = &segment.await_point {
+            used.visit_expr(&await_point.promise);
+        }
+    }
+    used.0
+}
+
+/// Rejects the `.await` expressions which are not statements of the method, and the early returns
+/// before the last `.await`, where the method returns a promise.
+#[derive(Default)]
+struct BodyCheck {
+    allow_early_return: bool,
+    errors: Vec<Error>,
+}
+
+impl BodyCheck {
+    fn early_return(&mut self, span: proc_macro2::Span) {
+        if !self.allow_early_return {
+            self.errors.push(Error::new(
+                span,
+                "Async methods can't return early before the last `.await`.",
+            ));
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for BodyCheck {
+    fn visit_expr_await(&mut self, i: &'ast syn::ExprAwait) {
+        self.errors.push(Error::new(
+            i.span(),
+            "`.await` is only supported in `let` and expression statements of an async method, \
+             such as `let x: T = promise.await;`.",
+        ));
+        visit::visit_expr_await(self, i);
+    }
+    fn visit_expr_return(&mut self, i: &'ast syn::ExprReturn) {
+        self.early_return(i.span());
+        visit::visit_expr_return(self, i);
+    }
+    fn visit_expr_try(&mut self, i: &'ast syn::ExprTry) {
+        self.early_return(i.question_token.span());
+        visit::visit_expr_try(self, i);
+    }
+    // Closures, async blocks and items have their own control flow.
+    fn visit_expr_closure(&mut self, _: &'ast syn::ExprClosure) {}
+    fn visit_expr_async(&mut self, _: &'ast syn::ExprAsync) {}
+    fn visit_item(&mut self, _: &'ast syn::Item) {}
+}
+
+// Rustfmt removes comas.
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::split_async_methods;
+    use quote::quote;
+    use syn::{parse_quote, ItemImpl};
+
+    #[test]
+    fn split_at_await() {
+        let mut item: ItemImpl = parse_quote! {
+            impl Contract {
+                /// Refreshes the balance.
+                #[non_reentrant]
+                #[handle_result]
+                pub async fn refresh(&mut self, token: AccountId, mut retries: u8) -> Result<U128, String> {
+                    let account_id: AccountId = env::predecessor_account_id();
+                    let unused = 1;
+                    let balance: U128 = ext_token::ext(token.clone()).ft_balance_of(account_id.clone()).await;
+                    retries += 1;
+                    ext_token::ext(token).ft_touch().await;
+                    let checked: Result<bool, PromiseError> = ext_token::ext(account_id).check(balance).await;
+                    self.balances.insert(&retries, &balance.0);
+                    checked.map(|_| balance).map_err(|_| "check failed".to_string())
+                }
+            }
+        };
+        split_async_methods(&mut item).unwrap();
+        let expected: ItemImpl = parse_quote! {
+            impl Contract {
+                /// Refreshes the balance.
+                #[non_reentrant]
+                #[allow(unused_mut)]
+                pub fn refresh(&mut self, token: AccountId, mut retries: u8) -> near_sdk::Promise {
+                    let account_id: AccountId = env::predecessor_account_id();
+                    let unused = 1;
+                    ext_token::ext(token.clone()).ft_balance_of(account_id.clone())
+                        .then(Self::ext(near_sdk::env::current_account_id()).__refresh_resume_1(token, retries, account_id))
+                }
+                #[doc = " Resumes `refresh` after its `.await` number 1."]
+                #[private]
+                #[allow(unused_mut)]
+                #[non_reentrant]
+                pub fn __refresh_resume_1(&mut self, token: AccountId, mut retries: u8, account_id: AccountId, #[callback_unwrap] balance: U128) -> near_sdk::Promise {
+                    retries += 1;
+                    ext_token::ext(token)
+                        .ft_touch()
+                        .then(Self::ext(near_sdk::env::current_account_id()).__refresh_resume_2(retries, account_id, balance))
+                }
+                #[doc = " Resumes `refresh` after its `.await` number 2."]
+                #[private]
+                #[allow(unused_mut)]
+                #[non_reentrant]
+                pub fn __refresh_resume_2(&mut self, mut retries: u8, account_id: AccountId, balance: U128) -> near_sdk::Promise {
+                    near_sdk::require!(near_sdk::is_promise_success(), "The promise awaited by `refresh` at `.await` number 2 failed");
+                    ext_token::ext(account_id)
+                        .check(balance)
+                        .then(Self::ext(near_sdk::env::current_account_id()).__refresh_resume_3(retries, balance))
+                }
+                #[doc = " Resumes `refresh` after its `.await` number 3."]
+                #[private]
+                #[allow(unused_mut)]
+                #[non_reentrant]
+                #[handle_result]
+                pub fn __refresh_resume_3(&mut self, mut retries: u8, balance: U128, #[callback_result] checked: Result<bool, PromiseError>) -> Result<U128, String> {
+                    self.balances.insert(&retries, &balance.0);
+                    checked.map(|_| balance).map_err(|_| "check failed".to_string())
+                }
+            }
+        };
+        assert_eq!(quote!(#expected).to_string(), quote!(#item).to_string());
+    }
+
+    #[test]
+    fn unsupported_await() {
+        let mut item: ItemImpl = parse_quote! {
+            impl Contract {
+                pub async fn method(&mut self) -> u64 {
+                    if self.enabled {
+                        return 0;
+                    }
+                    let value: u64 = ext_counter::ext(self.counter.clone()).get().await;
+                    value + ext_counter::ext(self.counter.clone()).get().await
+                }
+            }
+        };
+        let errors: Vec<String> =
+            split_async_methods(&mut item).unwrap_err().into_iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            errors,
+            [
+                "Async methods can't return early before the last `.await`.",
+                "`.await` is only supported in `let` and expression statements of an async method, \
+                 such as `let x: T = promise.await;`.",
+            ]
+        );
+    }
+
+    #[test]
+    fn untyped_local() {
+        let mut item: ItemImpl = parse_quote! {
+            impl Contract {
+                pub async fn method(&mut self) {
+                    let account_id = env::predecessor_account_id();
+                    ext_counter::ext(self.counter.clone()).increment().await;
+                    self.last = account_id;
+                }
+            }
+        };
+        let error = split_async_methods(&mut item).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Variable `account_id` is used after `.await`, so it needs a type annotation to be \
+             passed to the callback."
+        );
+    }
+}
@@ -176,6 +176,9 @@ impl AttrSigInfo {
 
     /// Create code that deserializes arguments that were decorated with `#[callback*]`
     pub fn callback_deserialization(&self) -> TokenStream2 {
+        // Index of the next promise result to read, since a `#[callback_result]` tuple reads one
+        // result per element.
+        let mut idx = 0u64;
         self.args
             .iter()
             .filter(|arg| {
@@ -184,9 +187,7 @@ impl AttrSigInfo {
                     BindgenArgType::CallbackArg | BindgenArgType::CallbackResultArg
                 )
             })
-            .enumerate()
-            .fold(TokenStream2::new(), |acc, (idx, arg)| {
-                let idx = idx as u64;
+            .fold(TokenStream2::new(), |acc, arg| {
                 let ArgInfo { mutability, ident, ty, bindgen_ty, serializer_ty, .. } = arg;
                 match &bindgen_ty {
                     BindgenArgType::CallbackArg => {
@@ -197,6 +198,7 @@ impl AttrSigInfo {
                                 _ => near_sdk::env::panic_str(#error_msg)
                             };
                         };
+                        idx += 1;
                         let invocation = deserialize_data(serializer_ty);
                         quote! {
                             #acc
@@ -205,48 +207,32 @@ impl AttrSigInfo {
                         }
                     }
                     BindgenArgType::CallbackResultArg => {
-                        let ok_type = if let Some(ok_type) = utils::extract_ok_type(ty) {
-                            ok_type
+                        let ok_types = if let Some(ok_types) = utils::extract_ok_types(ty) {
+                            ok_types
                         } else {
-                            return syn::Error::new_spanned(ty, "Function parameters marked with \
-                                #[callback_result] should have type Result<T, PromiseError>").into_compile_error()
-                        };
-                        let deserialize = deserialize_data(serializer_ty);
-                        let deserialization_branch = match ok_type {
-                            // The unit type in this context is a bit special because functions
-                            // without an explicit return type do not serialize their response.
-                            // But when someone tries to refer to their callback result with
-                            // `#[callback_result]` they specify the callback type as
-                            // `Result<(), PromiseError>` which cannot be correctly deserialized from
-                            // an empty byte array.
-                            //
-                            // So instead of going through serde, we consider deserialization to be
-                            // successful if the byte array is empty or try the normal
-                            // deserialization otherwise.
-                            syn::Type::Tuple(type_tuple) if type_tuple.elems.is_empty() =>
-                                quote! {
-                                    near_sdk::PromiseResult::Successful(data) if data.is_empty() =>
-                                        Ok(()),
-                                    near_sdk::PromiseResult::Successful(data) => Ok(#deserialize)
-                                },
-                            _ =>
-                                quote! {
-                                    near_sdk::PromiseResult::Successful(data) => Ok(#deserialize)
-                                }
+                            return syn::Error::new_spanned(
+                                ty,
+                                "Function parameters marked with \
+                                #[callback_result] should have type Result<T, PromiseError>",
+                            )
+                            .into_compile_error();
                         };
-                        let result = quote! {
-                            match near_sdk::env::promise_result(#idx) {
-                                #deserialization_branch,
-                                near_sdk::PromiseResult::NotReady => Err(near_sdk::PromiseError::NotReady),
-                                near_sdk::PromiseResult::Failed => Err(near_sdk::PromiseError::Failed),
-                            }
+                        let results = ok_types.iter().map(|ok_type| {
+                            let result = callback_result(idx, ok_type, serializer_ty);
+                            idx += 1;
+                            result
+                        });
+                        let result = if let syn::Type::Tuple(_) = ty {
+                            quote! { ( #(#results,)* ) }
+                        } else {
+                            quote! { #(#results)* }
                         };
                         quote! {
                             #acc
                             let #mutability #ident: #ty = #result;
                         }
                     }
-                    _ => unreachable!()
+                    _ => unreachable!(),
                 }
             })
     }
@@ -275,6 +261,38 @@ impl AttrSigInfo {
     }
 }
 
+/// Create expression that reads the promise result at `idx` as `Result<ok_type, PromiseError>`.
+fn callback_result(idx: u64, ok_type: &syn::Type, serializer_ty: &SerializerType) -> TokenStream2 {
+    let deserialize = deserialize_data(serializer_ty);
+    let deserialization_branch = match ok_type {
+        // The unit type in this context is a bit special because functions
+        // without an explicit return type do not serialize their response.
+        // But when someone tries to refer to their callback result with
+        // `#[callback_result]` they specify the callback type as
+        // `Result<(), PromiseError>` which cannot be correctly deserialized from
+        // an empty byte array.
+        //
+        // So instead of going through serde, we consider deserialization to be
+        // successful if the byte array is empty or try the normal
+        // deserialization otherwise.
+        syn::Type::Tuple(type_tuple) if type_tuple.elems.is_empty() => quote! {
+            near_sdk::PromiseResult::Successful(data) if data.is_empty() =>
+                Ok(()),
+            near_sdk::PromiseResult::Successful(data) => Ok(#deserialize)
+        },
+        _ => quote! {
+            near_sdk::PromiseResult::Successful(data) => Ok(#deserialize)
+        },
+    };
+    quote! {
+        match near_sdk::env::promise_result(#idx) {
+            #deserialization_branch,
+            near_sdk::PromiseResult::NotReady => Err(near_sdk::PromiseError::NotReady),
+            near_sdk::PromiseResult::Failed => Err(near_sdk::PromiseError::Failed),
+        }
+    }
+}
+
 pub fn deserialize_data(ty: &SerializerType) -> TokenStream2 {
     match ty {
         SerializerType::JSON => quote! {
@@ -1,4 +1,4 @@
-use crate::core_impl::{serializer, AttrSigInfo};
+use crate::core_impl::{serializer, AttrSigInfo, SerializerType};
 use proc_macro2::{Ident, TokenStream as TokenStream2};
 use quote::{format_ident, quote, ToTokens};
 use syn::{Generics, Signature};
@@ -24,8 +24,9 @@ pub(crate) fn generate_ext_structs(
     };
     if let Some(generics) = generic_details {
         // If ext generation is on struct, make ext function associated with struct not module
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
         ext_code = quote! {
-            impl #generics #ident #generics {
+            impl #impl_generics #ident #ty_generics #where_clause {
                 #ext_code
             }
         };
@@ -78,8 +79,15 @@ pub(crate) fn generate_ext_function_wrappers<'a>(
 
 fn generate_ext_function(attr_signature_info: &AttrSigInfo) -> TokenStream2 {
     let pat_type_list = attr_signature_info.pat_type_list();
-    let serialize =
+    let mut serialize =
         serializer::generate_serializer(attr_signature_info, &attr_signature_info.input_serializer);
+    // Callbacks continue to hold the guard of the call which scheduled them with its token.
+    if let (Some(non_reentrant), SerializerType::JSON) =
+        (&attr_signature_info.non_reentrant, &attr_signature_info.input_serializer)
+    {
+        let guard = &non_reentrant.guard;
+        serialize = quote! { near_sdk::access::attach_guard_token(#guard, #serialize) };
+    }
 
     let AttrSigInfo { non_bindgen_attrs, ident, original_sig, .. } = attr_signature_info;
     let ident_str = ident.to_string();
@@ -224,6 +232,31 @@ mod tests {
         assert_eq!(expected.to_string(), actual.to_string());
     }
 
+    #[test]
+    fn ext_non_reentrant() {
+        let impl_type: Type = parse_quote! { Hello };
+        let mut method: ImplItemMethod = parse_quote! {
+            #[private]
+            #[non_reentrant(guard = "withdraw")]
+            pub fn on_withdraw(&mut self) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = generate_ext_function(&method_info.attr_signature_info);
+        let expected = quote!(
+            pub fn on_withdraw(self,) -> near_sdk::Promise {
+                let __args = near_sdk::access::attach_guard_token("withdraw", vec![]);
+                near_sdk::Promise::new(self.account_id).function_call_weight(
+                    "on_withdraw".to_string(),
+                    __args,
+                    self.deposit,
+                    self.static_gas,
+                    self.gas_weight,
+                )
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
     #[test]
     fn ext_basic_borsh() {
         let impl_type: Type = syn::parse_str("Hello").unwrap();
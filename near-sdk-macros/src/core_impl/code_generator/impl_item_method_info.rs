@@ -1,21 +1,30 @@
 use crate::core_impl::info_extractor::{
-    AttrSigInfo, ImplItemMethodInfo, MethodType, SerializerType,
+    AttrSigInfo, DepositAttr, ImplItemMethodInfo, MethodType, SerializerType,
 };
 use crate::core_impl::utils;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use syn::spanned::Spanned;
-use syn::ReturnType;
+use syn::{ReturnType, Type};
 
 impl ImplItemMethodInfo {
     /// Generate wrapper method for the given method of the contract.
     pub fn method_wrapper(&self) -> TokenStream2 {
-        let ImplItemMethodInfo { attr_signature_info, struct_type, .. } = self;
+        let ImplItemMethodInfo { attr_signature_info, struct_type, panic_with_method_name, .. } =
+            self;
         // Args provided by `env::input()`.
         let has_input_args = attr_signature_info.input_args().next().is_some();
 
-        let panic_hook = quote! {
-            near_sdk::env::setup_panic_hook();
+        let panic_hook = if *panic_with_method_name {
+            let method_name = attr_signature_info.ident.to_string();
+            quote! {
+                near_sdk::env::setup_panic_hook();
+                near_sdk::env::set_panic_method_name(#method_name);
+            }
+        } else {
+            quote! {
+                near_sdk::env::setup_panic_hook();
+            }
         };
         let arg_struct;
         let arg_parsing;
@@ -55,10 +64,28 @@ impl ImplItemMethodInfo {
             method_type,
             is_payable,
             is_private,
+            only,
+            deposit,
+            is_when_not_paused,
+            non_reentrant,
             is_handles_result,
             ..
         } = attr_signature_info;
-        let deposit_check = if *is_payable || matches!(method_type, &MethodType::View) {
+        let deposit_check = if let Some(deposit) = deposit {
+            let error =
+                format!("Method {} requires an attached deposit of {}", ident, deposit.describe());
+            let condition = match deposit {
+                DepositAttr::Exact(amount) => {
+                    quote! { near_sdk::env::attached_deposit() != #amount }
+                }
+                DepositAttr::Min(amount) => quote! { near_sdk::env::attached_deposit() < #amount },
+            };
+            quote! {
+                if #condition {
+                    near_sdk::env::panic_str(#error);
+                }
+            }
+        } else if *is_payable || matches!(method_type, &MethodType::View) {
             // No check if the method is payable or a view method
             quote! {}
         } else {
@@ -80,6 +107,45 @@ impl ImplItemMethodInfo {
         } else {
             quote! {}
         };
+        let pause_check = if *is_when_not_paused {
+            let error = format!("Method {} is paused", ident);
+            quote! {
+                if near_sdk::access::is_paused() {
+                    near_sdk::env::panic_str(#error);
+                }
+            }
+        } else {
+            quote! {}
+        };
+        let guard_enter = if let Some(non_reentrant) = non_reentrant {
+            let guard = &non_reentrant.guard;
+            let error = format!("Method {} is not reentrant, the guard `{}` is held", ident, guard);
+            quote! {
+                near_sdk::access::enter_guard(#guard, #error);
+            }
+        } else {
+            quote! {}
+        };
+        // The guard is kept while the promise returned by the method is pending.
+        let guard_exit = |return_type: Option<&Type>| match non_reentrant {
+            Some(non_reentrant) => {
+                let guard = &non_reentrant.guard;
+                match return_type {
+                    Some(ty) if utils::type_is(ty, "Promise") => quote! {},
+                    Some(ty) if utils::type_is(ty, "PromiseOrValue") => quote! {
+                        if let near_sdk::PromiseOrValue::Value(_) = &result {
+                            near_sdk::access::exit_guard(#guard);
+                        }
+                    },
+                    _ => quote! {
+                        near_sdk::access::exit_guard(#guard);
+                    },
+                }
+            }
+            None => quote! {},
+        };
+        // The checks of `#[only]` need the contract state, which is then read before the arguments.
+        let mut access_check = TokenStream2::new();
         let body = if matches!(method_type, &MethodType::Init) {
             match init_method_wrapper(self, true) {
                 Ok(wrapper) => wrapper,
@@ -116,12 +182,40 @@ impl ImplItemMethodInfo {
                 };
                 contract_ser = TokenStream2::new();
             }
-            match returns {
-                ReturnType::Default => quote! {
+            let contract_deser = if let Some(only) = only {
+                let error = format!("Method {} can only be called by {}", ident, only.describe());
+                let owner = if only.owner {
+                    Some(quote! { near_sdk::access::Owner::owner(&contract) == predecessor })
+                } else {
+                    None
+                };
+                let roles = only.roles.iter().map(|role| {
+                    quote! { near_sdk::access::Roles::has_role(&contract, &predecessor, #role) }
+                });
+                let conditions = owner.into_iter().chain(roles);
+                access_check = quote! {
                     #contract_deser
-                    #method_invocation;
-                    #contract_ser
-                },
+                    {
+                        let predecessor = near_sdk::env::predecessor_account_id();
+                        if !(#(#conditions)||*) {
+                            near_sdk::env::panic_str(#error);
+                        }
+                    }
+                };
+                TokenStream2::new()
+            } else {
+                contract_deser
+            };
+            match returns {
+                ReturnType::Default => {
+                    let guard_exit = guard_exit(None);
+                    quote! {
+                        #contract_deser
+                        #method_invocation;
+                        #guard_exit
+                        #contract_ser
+                    }
+                }
                 ReturnType::Type(_, return_type)
                     if utils::type_is_result(return_type) && *is_handles_result =>
                 {
@@ -133,11 +227,13 @@ impl ImplItemMethodInfo {
                             let result = near_sdk::borsh::BorshSerialize::try_to_vec(&result).expect("Failed to serialize the return value using Borsh.");
                         },
                     };
+                    let guard_exit = guard_exit(utils::extract_ok_type(return_type));
                     quote! {
                         #contract_deser
                         let result = #method_invocation;
                         match result {
                             Ok(result) => {
+                                #guard_exit
                                 #value_ser
                                 near_sdk::env::value_return(&result);
                                 #contract_ser
@@ -164,7 +260,8 @@ impl ImplItemMethodInfo {
                     )
                     .to_compile_error();
                 }
-                ReturnType::Type(_, _) => {
+                ReturnType::Type(_, return_type) => {
+                    let guard_exit = guard_exit(Some(return_type));
                     let value_ser = match result_serializer {
                         SerializerType::JSON => quote! {
                             let result = near_sdk::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
@@ -176,6 +273,7 @@ impl ImplItemMethodInfo {
                     quote! {
                         #contract_deser
                         let result = #method_invocation;
+                        #guard_exit
                         #value_ser
                         near_sdk::env::value_return(&result);
                         #contract_ser
@@ -197,6 +295,9 @@ impl ImplItemMethodInfo {
                 #panic_hook
                 #is_private_check
                 #deposit_check
+                #pause_check
+                #access_check
+                #guard_enter
                 #arg_struct
                 #arg_parsing
                 #callback_deser
@@ -2,7 +2,7 @@ use crate::core_impl::ext::generate_ext_function_wrappers;
 use crate::ItemImplInfo;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::ToTokens;
-use syn::{spanned::Spanned, Ident};
+use syn::{spanned::Spanned, Ident, Type};
 
 impl ItemImplInfo {
     /// Generate the code that wraps
@@ -17,7 +17,19 @@ impl ItemImplInfo {
     }
 
     pub fn generate_ext_wrapper_code(&self) -> TokenStream2 {
-        match syn::parse::<Ident>(self.ty.to_token_stream().into()) {
+        // The `Ext` struct of an instantiated generic contract, such as `Vault::<Usdc>`, is named
+        // after the struct alone.
+        let ident = match &self.ty {
+            Type::Path(type_path) if type_path.qself.is_none() => {
+                type_path.path.segments.last().filter(|segment| !segment.arguments.is_empty())
+            }
+            _ => None,
+        };
+        let ident = match ident {
+            Some(segment) => Ok(segment.ident.clone()),
+            None => syn::parse::<Ident>(self.ty.to_token_stream().into()),
+        };
+        match ident {
             Ok(n) => generate_ext_function_wrappers(
                 &n,
                 self.methods
@@ -56,6 +68,26 @@ mod tests {
         assert_eq!(expected.to_string(), actual.to_string());
     }
 
+    #[test]
+    fn panic_with_method_name() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = syn::parse_str("pub fn method(&self) { }").unwrap();
+        let mut method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        method_info.panic_with_method_name = true;
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                near_sdk::env::set_panic_method_name("method");
+                let contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                contract.method();
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
     #[test]
     fn no_args_no_return_no_mut() {
         let impl_type: Type = syn::parse_str("Hello").unwrap();
@@ -420,6 +452,48 @@ mod tests {
         assert_eq!(expected.to_string(), actual.to_string());
     }
 
+    #[test]
+    fn callback_args_results_tuple() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[private] pub fn method(&self, #[callback_result] x: (Result<u64, PromiseError>, Result<(), PromiseError>), #[callback_unwrap] y: String) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::current_account_id() != near_sdk::env::predecessor_account_id() {
+                    near_sdk::env::panic_str("Method method is private");
+                }
+                let x: (Result<u64, PromiseError>, Result<(), PromiseError>) = (
+                    match near_sdk::env::promise_result(0u64) {
+                        near_sdk::PromiseResult::Successful(data) => Ok(near_sdk::serde_json::from_slice(&data).expect("Failed to deserialize callback using JSON")),
+                        near_sdk::PromiseResult::NotReady => Err(near_sdk::PromiseError::NotReady),
+                        near_sdk::PromiseResult::Failed => Err(near_sdk::PromiseError::Failed),
+                    },
+                    match near_sdk::env::promise_result(1u64) {
+                        near_sdk::PromiseResult::Successful(data) if data.is_empty() => Ok(()),
+                        near_sdk::PromiseResult::Successful(data) => Ok(near_sdk::serde_json::from_slice(&data).expect("Failed to deserialize callback using JSON")),
+                        near_sdk::PromiseResult::NotReady => Err(near_sdk::PromiseError::NotReady),
+                        near_sdk::PromiseResult::Failed => Err(near_sdk::PromiseError::Failed),
+                    },
+                );
+                let data: Vec<u8> = match near_sdk::env::promise_result(2u64) {
+                    near_sdk::PromiseResult::Successful(x) => x,
+                    _ => near_sdk::env::panic_str("Callback computation 2 was not successful")
+                };
+                let y: String = near_sdk::serde_json::from_slice(&data).expect("Failed to deserialize callback using JSON");
+                let contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                contract.method(x, y, );
+            }
+        );
+
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
     #[test]
     fn callback_args_vec() {
         let impl_type: Type = syn::parse_str("Hello").unwrap();
@@ -703,6 +777,228 @@ mod tests {
         assert_eq!(expected.to_string(), actual.to_string());
     }
 
+    #[test]
+    fn only_owner() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[only(owner)]
+            pub fn method(&mut self, k: u64) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method method doesn't accept deposit");
+                }
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                {
+                    let predecessor = near_sdk::env::predecessor_account_id();
+                    if !(near_sdk::access::Owner::owner(&contract) == predecessor) {
+                        near_sdk::env::panic_str("Method method can only be called by the owner");
+                    }
+                }
+                #[derive(near_sdk :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk::serde")]
+                struct Input {
+                    k: u64,
+                }
+                let Input { k, }: Input = near_sdk::serde_json::from_slice(
+                    &near_sdk::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                contract.method(k, );
+                near_sdk::env::state_write(&contract);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn only_roles_and_deposit() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[only(owner, role = "minter")]
+            #[only(role = "admin")]
+            #[deposit(exact = 1)]
+            pub fn method(&mut self) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() != 1u128 {
+                    near_sdk::env::panic_str("Method method requires an attached deposit of exactly 1 yoctoNEAR");
+                }
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                {
+                    let predecessor = near_sdk::env::predecessor_account_id();
+                    if !(near_sdk::access::Owner::owner(&contract) == predecessor
+                        || near_sdk::access::Roles::has_role(&contract, &predecessor, "minter")
+                        || near_sdk::access::Roles::has_role(&contract, &predecessor, "admin"))
+                    {
+                        near_sdk::env::panic_str("Method method can only be called by the owner or an account with the role `minter` or an account with the role `admin`");
+                    }
+                }
+                contract.method();
+                near_sdk::env::state_write(&contract);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn deposit_min() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[deposit(min = 1000)]
+            pub fn method(&mut self) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() < 1000u128 {
+                    near_sdk::env::panic_str("Method method requires an attached deposit of at least 1000 yoctoNEAR");
+                }
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                contract.method();
+                near_sdk::env::state_write(&contract);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn only_without_self() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[only(owner)]
+            pub fn method() { }
+        };
+        let err = ImplItemMethodInfo::new(&mut method, impl_type).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Method with `#[only]` must take `self` to read the owner and roles"
+        );
+    }
+
+    #[test]
+    fn when_not_paused_non_reentrant_promise() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[when_not_paused]
+            #[non_reentrant]
+            pub fn method(&mut self) -> Promise { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method method doesn't accept deposit");
+                }
+                if near_sdk::access::is_paused() {
+                    near_sdk::env::panic_str("Method method is paused");
+                }
+                near_sdk::access::enter_guard("default", "Method method is not reentrant, the guard `default` is held");
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                let result = contract.method();
+                let result =
+                    near_sdk::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
+                near_sdk::env::value_return(&result);
+                near_sdk::env::state_write(&contract);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn non_reentrant_callback() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[private]
+            #[non_reentrant(guard = "withdraw")]
+            pub fn method(&mut self) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::current_account_id() != near_sdk::env::predecessor_account_id() {
+                    near_sdk::env::panic_str("Method method is private");
+                }
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method method doesn't accept deposit");
+                }
+                near_sdk::access::enter_guard("withdraw", "Method method is not reentrant, the guard `withdraw` is held");
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                contract.method();
+                near_sdk::access::exit_guard("withdraw");
+                near_sdk::env::state_write(&contract);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn non_reentrant_promise_or_value() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[non_reentrant]
+            pub fn method(&mut self) -> PromiseOrValue<u64> { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method method doesn't accept deposit");
+                }
+                near_sdk::access::enter_guard("default", "Method method is not reentrant, the guard `default` is held");
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                let result = contract.method();
+                if let near_sdk::PromiseOrValue::Value(_) = &result {
+                    near_sdk::access::exit_guard("default");
+                }
+                let result =
+                    near_sdk::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
+                near_sdk::env::value_return(&result);
+                near_sdk::env::state_write(&contract);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn non_reentrant_view() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[non_reentrant]
+            pub fn method(&self) { }
+        };
+        let err = ImplItemMethodInfo::new(&mut method, impl_type).err().unwrap();
+        assert_eq!(err.to_string(), "Non-reentrant method must be mutable (not view or init)");
+    }
+
     #[test]
     fn handle_result_json() {
         let impl_type: Type = syn::parse_str("Hello").unwrap();
@@ -853,4 +1149,69 @@ mod tests {
         );
         assert_eq!(expected.to_string(), actual.to_string());
     }
+
+    #[test]
+    fn instantiate_generic_impl() {
+        let mut item: syn::ItemImpl = parse_quote! {
+            impl<T, const N: usize> Vault<T, N> where T: Token {
+                pub fn get(&self, key: T::Key, index: u8) -> [T; N] { }
+            }
+        };
+        let instance: Type = parse_quote!(Vault<Usdc, 4>);
+        let info = crate::ItemImplInfo::new_instance(&mut item, &instance).unwrap();
+        let actual = info.methods[0].method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn get() {
+                near_sdk::env::setup_panic_hook();
+                #[derive(near_sdk :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk::serde")]
+                struct Input {
+                    key: <Usdc as Token>::Key,
+                    index: u8,
+                }
+                let Input { key, index, }: Input = near_sdk::serde_json::from_slice(
+                    &near_sdk::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                let contract: Vault::<Usdc, 4> = near_sdk::env::state_read().unwrap_or_default();
+                let result = contract.get(key, index, );
+                let result =
+                    near_sdk::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
+                near_sdk::env::value_return(&result);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+        assert_eq!(quote!(#item).to_string(), quote!(
+            impl<T, const N: usize> Vault<T, N> where T: Token {
+                pub fn get(&self, key: T::Key, index: u8) -> [T; N] { }
+            }
+        ).to_string());
+    }
+
+    #[test]
+    fn instantiate_several_bounds() {
+        let instance: Type = parse_quote!(Vault<Usdc>);
+        let mut item: syn::ItemImpl = parse_quote! {
+            impl<T: Clone + Token> Vault<T> {
+                pub fn get(&self, key: T::Key) { }
+            }
+        };
+        let error = crate::ItemImplInfo::new_instance(&mut item, &instance).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "`T::Key` is ambiguous, since `T` has several trait bounds. Name the trait of the \
+             associated type, as in `<T as Trait>::Key`."
+        );
+
+        let mut item: syn::ItemImpl = parse_quote! {
+            impl<T: Clone + Token> Vault<T> {
+                pub fn get(&self, key: <T as Token>::Key) { }
+            }
+        };
+        let info = crate::ItemImplInfo::new_instance(&mut item, &instance).unwrap();
+        let arg_ty = &info.methods[0].attr_signature_info.args[0].ty;
+        assert_eq!(quote!(#arg_ty).to_string(), quote!(<Usdc as Token>::Key).to_string());
+    }
 }
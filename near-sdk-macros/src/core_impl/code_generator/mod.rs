@@ -13,3 +13,7 @@ pub use item_impl_info::*;
 pub(crate) mod ext;
 
 pub(crate) mod serializer;
+
+pub(crate) mod pausable;
+
+pub(crate) mod async_methods;
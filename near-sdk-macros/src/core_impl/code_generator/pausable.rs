@@ -0,0 +1,65 @@
+use syn::spanned::Spanned;
+use syn::{parse_quote, Error, ImplItem, ItemImpl};
+
+/// Adds the `pause`, `unpause` and `is_paused` methods of `#[near_bindgen(pausable)]` to the
+/// contract impl, so they are exported like the methods written by hand.
+pub(crate) fn add_pausable_methods(item: &mut ItemImpl) -> syn::Result<()> {
+    if let Some((_, path, _)) = &item.trait_ {
+        return Err(Error::new(
+            path.span(),
+            "`pausable` can only be used on an impl section without a trait.",
+        ));
+    }
+    item.items.push(parse_quote! {
+        /// Pauses the methods marked with `#[when_not_paused]`.
+        #[only(owner)]
+        pub fn pause(&mut self) {
+            near_sdk::access::set_paused(true);
+        }
+    });
+    item.items.push(parse_quote! {
+        /// Unpauses the methods marked with `#[when_not_paused]`.
+        #[only(owner)]
+        pub fn unpause(&mut self) {
+            near_sdk::access::set_paused(false);
+        }
+    });
+    item.items.push(parse_quote! {
+        /// Returns `true` if the methods marked with `#[when_not_paused]` are paused.
+        pub fn is_paused(&self) -> bool {
+            near_sdk::access::is_paused()
+        }
+    });
+    Ok(())
+}
+
+/// Adds the `release_guard` method to a contract impl with `#[non_reentrant]` methods, so that the
+/// owner can release a guard which a callback left held by panicking.
+pub(crate) fn add_release_guard_method(item: &mut ItemImpl) -> syn::Result<()> {
+    let guarded = item.items.iter().find_map(|subitem| match subitem {
+        ImplItem::Method(method) => {
+            method.attrs.iter().find(|attr| attr.path.is_ident("non_reentrant"))
+        }
+        _ => None,
+    });
+    let guarded = match guarded {
+        Some(attr) => attr,
+        None => return Ok(()),
+    };
+    if item.trait_.is_some() {
+        return Err(Error::new(
+            guarded.span(),
+            "Non-reentrant methods can only be used in an impl section without a trait, which \
+             gets the `release_guard` method.",
+        ));
+    }
+    item.items.push(parse_quote! {
+        /// Releases the `#[non_reentrant]` guard named `guard`, which a callback left held by
+        /// panicking.
+        #[only(owner)]
+        pub fn release_guard(&mut self, guard: String) {
+            near_sdk::access::release_guard(&guard);
+        }
+    });
+    Ok(())
+}
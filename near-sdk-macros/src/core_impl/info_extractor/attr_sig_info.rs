@@ -1,4 +1,7 @@
-use super::{ArgInfo, BindgenArgType, InitAttr, MethodType, SerializerAttr, SerializerType};
+use super::{
+    ArgInfo, BindgenArgType, DepositAttr, InitAttr, MethodType, NonReentrantAttr, OnlyAttr,
+    SerializerAttr, SerializerType,
+};
 use proc_macro2::Span;
 use quote::ToTokens;
 use syn::spanned::Spanned;
@@ -18,6 +21,14 @@ pub struct AttrSigInfo {
     pub is_payable: bool,
     /// Whether method can accept calls from self (current account)
     pub is_private: bool,
+    /// The accounts allowed to call the method, from `#[only(...)]`.
+    pub only: Option<OnlyAttr>,
+    /// The deposit the method requires, from `#[deposit(...)]`.
+    pub deposit: Option<DepositAttr>,
+    /// Whether method panics while the contract is paused.
+    pub is_when_not_paused: bool,
+    /// The guard held by the method, from `#[non_reentrant]`.
+    pub non_reentrant: Option<NonReentrantAttr>,
     /// Whether method returns Result type where only Ok type is serialized
     pub is_handles_result: bool,
     /// The serializer that we use for `env::input()`.
@@ -63,11 +74,17 @@ impl AttrSigInfo {
         let mut method_type = MethodType::Regular;
         let mut is_payable = false;
         let mut is_private = false;
+        let mut only: Option<OnlyAttr> = None;
+        let mut deposit = None;
+        let mut is_when_not_paused = false;
+        let mut non_reentrant = None;
         let mut is_handles_result = false;
         // By the default we serialize the result with JSON.
         let mut result_serializer = SerializerType::JSON;
 
         let mut payable_attr = None;
+        let mut only_attr = None;
+        let mut non_reentrant_attr = None;
         for attr in original_attrs.iter() {
             let attr_str = attr.path.to_token_stream().to_string();
             match attr_str.as_str() {
@@ -86,6 +103,27 @@ impl AttrSigInfo {
                 "private" => {
                     is_private = true;
                 }
+                "only" => {
+                    only_attr = Some(attr);
+                    let attr_only: OnlyAttr = syn::parse2(attr.tokens.clone())?;
+                    let only = only.get_or_insert_with(OnlyAttr::default);
+                    only.owner |= attr_only.owner;
+                    only.roles.extend(attr_only.roles);
+                }
+                "deposit" => {
+                    if deposit.is_some() {
+                        return Err(Error::new(attr.span(), "Duplicate `#[deposit]` attribute."));
+                    }
+                    payable_attr = Some(attr);
+                    deposit = Some(syn::parse2::<DepositAttr>(attr.tokens.clone())?);
+                }
+                "when_not_paused" => {
+                    is_when_not_paused = true;
+                }
+                "non_reentrant" => {
+                    non_reentrant_attr = Some(attr);
+                    non_reentrant = Some(syn::parse2::<NonReentrantAttr>(attr.tokens.clone())?);
+                }
                 "result_serializer" => {
                     let serializer: SerializerAttr = syn::parse2(attr.tokens.clone())?;
                     result_serializer = serializer.serializer_type;
@@ -131,6 +169,33 @@ impl AttrSigInfo {
             }
         }
 
+        if let Some(non_reentrant_attr) = non_reentrant_attr {
+            if !matches!(method_type, MethodType::Regular) {
+                return Err(Error::new(
+                    non_reentrant_attr.span(),
+                    "Non-reentrant method must be mutable (not view or init)",
+                ));
+            }
+        }
+
+        if let Some(only_attr) = only_attr {
+            if receiver.is_none() {
+                return Err(Error::new(
+                    only_attr.span(),
+                    "Method with `#[only]` must take `self` to read the owner and roles",
+                ));
+            }
+            if matches!(method_type, MethodType::View) {
+                return Err(Error::new(
+                    only_attr.span(),
+                    "Method with `#[only]` must be mutable (not view), since view calls have no \
+                    predecessor",
+                ));
+            }
+        }
+
+        // The attributes are replaced below, so only the span is kept for the serializer check.
+        let non_reentrant_span = non_reentrant_attr.map(|attr| attr.span());
         *original_attrs = non_bindgen_attrs.clone();
         let returns = original_sig.output.clone();
 
@@ -142,6 +207,10 @@ impl AttrSigInfo {
             method_type,
             is_payable,
             is_private,
+            only,
+            deposit,
+            is_when_not_paused,
+            non_reentrant,
             is_handles_result,
             result_serializer,
             receiver,
@@ -160,6 +229,15 @@ impl AttrSigInfo {
                     "Input arguments should be all of the same serialization type.",
                 ));
             };
+        if let Some(non_reentrant_span) = non_reentrant_span {
+            if input_serializer == SerializerType::Borsh {
+                return Err(Error::new(
+                    non_reentrant_span,
+                    "Non-reentrant method must take JSON arguments, which carry the token of the \
+                    guard to its callbacks",
+                ));
+            }
+        }
         result.input_serializer = input_serializer;
         Ok(result)
     }
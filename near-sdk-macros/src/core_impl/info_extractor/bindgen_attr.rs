@@ -0,0 +1,70 @@
+use syn::parse::{Parse, ParseStream};
+use syn::{Error, Ident, Token, Type};
+
+/// Arguments of the `#[near_bindgen(...)]` attribute.
+#[derive(Default)]
+pub struct BindgenAttr {
+    /// Whether panic messages of the exported methods are prefixed with the method name.
+    pub panic_with_method_name: bool,
+    /// Whether the `pause`, `unpause` and `is_paused` methods are added to the impl section.
+    pub pausable: bool,
+    /// The concrete type which the methods of a generic impl section are exported for.
+    pub instantiate: Option<Type>,
+}
+
+impl Parse for BindgenAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attr = Self::default();
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+            if ident == "panic_with_method_name" {
+                attr.panic_with_method_name = true;
+            } else if ident == "pausable" {
+                attr.pausable = true;
+            } else if ident == "instantiate" {
+                let content;
+                syn::parenthesized!(content in input);
+                let types = content.parse_terminated::<Type, Token![,]>(Type::parse)?;
+                if types.len() != 1 {
+                    return Err(Error::new(
+                        ident.span(),
+                        "`instantiate` takes exactly one type, since the exported methods \
+                         of a contract must have unique names.",
+                    ));
+                }
+                attr.instantiate = types.into_iter().next();
+            } else if ident == "init" {
+                // The legacy `init => new` form has no effect, but contracts still use it.
+                input.parse::<Token![=>]>()?;
+                input.parse::<Ident>()?;
+            } else {
+                return Err(Error::new(ident.span(), "unknown near_bindgen argument"));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(attr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BindgenAttr;
+    use quote::quote;
+
+    #[test]
+    fn parse_arguments() {
+        let attr: BindgenAttr =
+            syn::parse2(quote!(pausable, panic_with_method_name, instantiate(Vault<Usdc>)))
+                .unwrap();
+        assert!(attr.pausable && attr.panic_with_method_name && attr.instantiate.is_some());
+        syn::parse2::<BindgenAttr>(quote!(init => new)).unwrap();
+    }
+
+    #[test]
+    fn unknown_argument() {
+        let err = syn::parse2::<BindgenAttr>(quote!(panic_with_methd_name)).err().unwrap();
+        assert_eq!(err.to_string(), "unknown near_bindgen argument");
+    }
+}
@@ -0,0 +1,37 @@
+use proc_macro2::Ident;
+use syn::parse::{Parse, ParseStream};
+use syn::{parenthesized, Error, LitInt, Token};
+
+/// The deposit required by a method marked with `#[deposit(...)]`, in yoctoNEAR.
+pub enum DepositAttr {
+    Exact(u128),
+    Min(u128),
+}
+
+impl Parse for DepositAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        parenthesized!(content in input);
+        let ident: Ident = content.parse()?;
+        content.parse::<Token![=]>()?;
+        let amount = content.parse::<LitInt>()?.base10_parse::<u128>()?;
+        if !content.is_empty() {
+            return Err(Error::new(content.span(), "Expected a single `exact` or `min` amount."));
+        }
+        match ident.to_string().as_str() {
+            "exact" => Ok(Self::Exact(amount)),
+            "min" => Ok(Self::Min(amount)),
+            _ => Err(Error::new(ident.span(), "Expected `exact = ...` or `min = ...`.")),
+        }
+    }
+}
+
+impl DepositAttr {
+    /// Describes the required deposit, e.g. "exactly 1 yoctoNEAR".
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Exact(amount) => format!("exactly {} yoctoNEAR", amount),
+            Self::Min(amount) => format!("at least {} yoctoNEAR", amount),
+        }
+    }
+}
@@ -9,6 +9,8 @@ pub struct ImplItemMethodInfo {
     pub is_public: bool,
     /// The type of the contract struct.
     pub struct_type: Type,
+    /// Whether panic messages are prefixed with the method name.
+    pub panic_with_method_name: bool,
 }
 
 impl ImplItemMethodInfo {
@@ -17,6 +19,6 @@ impl ImplItemMethodInfo {
         let ImplItemMethod { attrs, sig, .. } = original;
         let attr_signature_info = AttrSigInfo::new(attrs, sig)?;
         let is_public = matches!(original.vis, Visibility::Public(_));
-        Ok(Self { attr_signature_info, is_public, struct_type })
+        Ok(Self { attr_signature_info, is_public, struct_type, panic_with_method_name: false })
     }
 }
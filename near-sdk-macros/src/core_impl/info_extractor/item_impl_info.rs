@@ -1,6 +1,14 @@
+use crate::core_impl::utils;
 use crate::ImplItemMethodInfo;
+use std::collections::HashMap;
+use syn::fold::{self, Fold};
+use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
-use syn::{Error, ImplItem, ItemImpl, Type};
+use syn::{
+    Error, Expr, GenericArgument, GenericParam, Generics, Ident, ImplItem, ItemImpl, Path,
+    PathArguments, PredicateType, QSelf, ReturnType, TraitBoundModifier, Type, TypeParamBound,
+    TypePath, WherePredicate,
+};
 
 /// Information extracted from `impl` section.
 pub struct ItemImplInfo {
@@ -17,7 +25,8 @@ impl ItemImplInfo {
         if !original.generics.params.is_empty() {
             return Err(Error::new(
                 original.generics.params.span(),
-                "Impl type parameters are not supported for smart contracts.",
+                "Impl type parameters are not supported for smart contracts, unless the impl \
+                 is instantiated with `#[near_bindgen(instantiate(...))]`.",
             ));
         }
         let is_trait_impl = original.trait_.is_some();
@@ -30,6 +39,258 @@ impl ItemImplInfo {
                 methods.push(method_info);
             }
         }
+        check_guard_release(&methods)?;
         Ok(Self { is_trait_impl, ty, methods })
     }
+
+    /// Extract info from a generic `impl` section for the concrete type `instance`, such as
+    /// `Vault<Usdc>` for `impl<T: Token> Vault<T>`. The methods are exported with the type
+    /// parameters replaced by the arguments of `instance`.
+    pub fn new_instance(original: &mut ItemImpl, instance: &Type) -> syn::Result<Self> {
+        let params = instance_params(&original.generics, &original.self_ty, instance)?;
+        let bounds = trait_bounds(&original.generics);
+        let mut folder = InstanceFolder { params, bounds, errors: vec![] };
+        let mut concrete = folder.fold_item_impl(original.clone());
+        if let Some(error) = folder.errors.into_iter().reduce(|mut acc, e| {
+            acc.combine(e);
+            acc
+        }) {
+            return Err(error);
+        }
+        concrete.generics = Generics::default();
+        // The type is also used in expressions, such as `Vault::<Usdc>::new(...)`.
+        let mut ty = instance.clone();
+        if let Type::Path(type_path) = &mut ty {
+            for segment in &mut type_path.path.segments {
+                if let PathArguments::AngleBracketed(args) = &mut segment.arguments {
+                    args.colon2_token = Some(Default::default());
+                }
+            }
+        }
+        *concrete.self_ty = ty;
+        let info = Self::new(&mut concrete)?;
+
+        // Remove the bindgen attributes from the generic methods which are kept in the code.
+        for subitem in &mut original.items {
+            if let ImplItem::Method(m) = subitem {
+                ImplItemMethodInfo::new(m, info.ty.clone())?;
+            }
+        }
+        Ok(info)
+    }
+}
+
+/// Checks that the guard of each `#[non_reentrant]` method returning a promise can be released by
+/// a callback, a `#[private]` method of the impl holding the same guard.
+fn check_guard_release(methods: &[ImplItemMethodInfo]) -> syn::Result<()> {
+    for method in methods {
+        let info = &method.attr_signature_info;
+        let guard = match &info.non_reentrant {
+            Some(non_reentrant) => &non_reentrant.guard,
+            None => continue,
+        };
+        let returns_promise = match &info.returns {
+            ReturnType::Type(_, ty) => {
+                let ty =
+                    if info.is_handles_result { utils::extract_ok_type(ty) } else { Some(&**ty) };
+                ty.map_or(false, |ty| {
+                    utils::type_is(ty, "Promise") || utils::type_is(ty, "PromiseOrValue")
+                })
+            }
+            ReturnType::Default => false,
+        };
+        let has_callback = methods.iter().map(|m| &m.attr_signature_info).any(|callback| {
+            callback.is_private
+                && callback.non_reentrant.as_ref().map_or(false, |n| &n.guard == guard)
+        });
+        if returns_promise && !has_callback {
+            return Err(Error::new(
+                info.ident.span(),
+                format!(
+                    "Method {} returns a promise while holding the guard `{}`, which is only \
+                     released by a `#[private]` callback marked with `#[non_reentrant]` for the \
+                     same guard. Add one to this impl section.",
+                    info.ident, guard
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Maps the type and const parameters of the impl to the arguments of `instance` in the same
+/// position of the self type.
+fn instance_params(
+    generics: &Generics,
+    self_ty: &Type,
+    instance: &Type,
+) -> syn::Result<HashMap<Ident, GenericArgument>> {
+    let mismatch = || {
+        Error::new(
+            instance.span(),
+            "The instantiated type must be the type of the impl section with its type parameters \
+             replaced.",
+        )
+    };
+    let (self_segment, instance_segment) = match (self_ty, instance) {
+        (Type::Path(self_path), Type::Path(instance_path)) => {
+            match (self_path.path.segments.last(), instance_path.path.segments.last()) {
+                (Some(a), Some(b)) if a.ident == b.ident => (a, b),
+                _ => return Err(mismatch()),
+            }
+        }
+        _ => return Err(mismatch()),
+    };
+    let (self_args, instance_args) = match (&self_segment.arguments, &instance_segment.arguments) {
+        (PathArguments::AngleBracketed(a), PathArguments::AngleBracketed(b))
+            if a.args.len() == b.args.len() =>
+        {
+            (&a.args, &b.args)
+        }
+        _ => return Err(mismatch()),
+    };
+    let mut params = HashMap::new();
+    for (self_arg, instance_arg) in self_args.iter().zip(instance_args) {
+        if let GenericArgument::Type(Type::Path(type_path)) = self_arg {
+            if let Some(ident) = type_path.path.get_ident() {
+                params.insert(ident.clone(), instance_arg.clone());
+            }
+        }
+    }
+    for param in &generics.params {
+        let ident = match param {
+            GenericParam::Type(param) => &param.ident,
+            GenericParam::Const(param) => &param.ident,
+            GenericParam::Lifetime(_) => continue,
+        };
+        if !params.contains_key(ident) {
+            return Err(Error::new(
+                ident.span(),
+                format!(
+                    "Type parameter `{}` must be an argument of the contract type to be \
+                     instantiated.",
+                    ident
+                ),
+            ));
+        }
+    }
+    params.retain(|ident, _| {
+        generics.params.iter().any(|param| match param {
+            GenericParam::Type(param) => &param.ident == ident,
+            GenericParam::Const(param) => &param.ident == ident,
+            GenericParam::Lifetime(_) => false,
+        })
+    });
+    Ok(params)
+}
+
+/// Finds the trait bounds of each type parameter, in its declaration and in the where clause.
+fn trait_bounds(generics: &Generics) -> HashMap<Ident, Vec<Path>> {
+    let mut bounds = HashMap::new();
+    let declared = generics.type_params().map(|param| (param.ident.clone(), &param.bounds));
+    let predicates = generics.where_clause.iter().flat_map(|clause| &clause.predicates);
+    let constrained = predicates.filter_map(|predicate| match predicate {
+        WherePredicate::Type(PredicateType { bounded_ty: Type::Path(ty), bounds, .. }) => {
+            ty.path.get_ident().map(|ident| (ident.clone(), bounds))
+        }
+        _ => None,
+    });
+    for (ident, param_bounds) in declared.chain(constrained) {
+        for bound in param_bounds {
+            match bound {
+                TypeParamBound::Trait(bound) if bound.modifier == TraitBoundModifier::None => {
+                    let paths: &mut Vec<Path> = bounds.entry(ident.clone()).or_default();
+                    if !paths.contains(&bound.path) {
+                        paths.push(bound.path.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    bounds
+}
+
+/// Replaces the type and const parameters of the impl with their instantiated arguments.
+struct InstanceFolder {
+    params: HashMap<Ident, GenericArgument>,
+    /// The trait bounds of each type parameter. Its associated types belong to the bound if
+    /// there is only one.
+    bounds: HashMap<Ident, Vec<Path>>,
+    /// The associated types which can't be resolved.
+    errors: Vec<Error>,
+}
+
+impl Fold for InstanceFolder {
+    fn fold_type(&mut self, ty: Type) -> Type {
+        if let Type::Path(type_path) = &ty {
+            let mut segments = type_path.path.segments.iter();
+            let param = match segments.next() {
+                Some(first)
+                    if type_path.qself.is_none() && type_path.path.leading_colon.is_none() =>
+                {
+                    self.params.get(&first.ident).map(|arg| (&first.ident, arg))
+                }
+                _ => None,
+            };
+            if let Some((ident, GenericArgument::Type(arg))) = param {
+                let arg = self.fold_type(arg.clone());
+                if segments.len() == 0 {
+                    return arg;
+                }
+                // An associated type such as `T::Balance` becomes `<Usdc as Token>::Balance`,
+                // resolved through the trait bound of `T`.
+                let bounds = self.bounds.get(ident).map_or(&[][..], Vec::as_slice);
+                if bounds.len() > 1 {
+                    let assoc: Vec<String> = segments.map(|s| s.ident.to_string()).collect();
+                    let error = Error::new(
+                        type_path.span(),
+                        format!(
+                            "`{ident}::{assoc}` is ambiguous, since `{ident}` has several trait \
+                             bounds. Name the trait of the associated type, as in \
+                             `<{ident} as Trait>::{assoc}`.",
+                            ident = ident,
+                            assoc = assoc.join("::"),
+                        ),
+                    );
+                    self.errors.push(error);
+                    return ty;
+                }
+                let mut path = bounds.first().cloned().unwrap_or_else(|| Path {
+                    leading_colon: Some(Default::default()),
+                    segments: Punctuated::new(),
+                });
+                let position = path.segments.len();
+                path.segments.extend(segments.cloned());
+                return Type::Path(TypePath {
+                    qself: Some(QSelf {
+                        lt_token: Default::default(),
+                        ty: Box::new(arg),
+                        position,
+                        as_token: (position > 0).then(Default::default),
+                        gt_token: Default::default(),
+                    }),
+                    path,
+                });
+            }
+        }
+        fold::fold_type(self, ty)
+    }
+
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        if let Expr::Path(expr_path) = &expr {
+            match expr_path.path.get_ident().and_then(|ident| self.params.get(ident)) {
+                Some(GenericArgument::Const(arg)) => return arg.clone(),
+                Some(GenericArgument::Type(Type::Path(arg))) => {
+                    return Expr::Path(syn::ExprPath {
+                        attrs: vec![],
+                        qself: arg.qself.clone(),
+                        path: arg.path.clone(),
+                    })
+                }
+                _ => {}
+            }
+        }
+        fold::fold_expr(self, expr)
+    }
 }
@@ -21,6 +21,18 @@ mod item_impl_info;
 mod init_attr;
 pub use init_attr::InitAttr;
 
+mod only_attr;
+pub use only_attr::OnlyAttr;
+
+mod deposit_attr;
+pub use deposit_attr::DepositAttr;
+
+mod non_reentrant_attr;
+pub use non_reentrant_attr::NonReentrantAttr;
+
+mod bindgen_attr;
+pub use bindgen_attr::BindgenAttr;
+
 pub use item_impl_info::ItemImplInfo;
 
 /// Type of serialization we use.
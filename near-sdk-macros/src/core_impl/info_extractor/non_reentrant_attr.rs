@@ -0,0 +1,27 @@
+use proc_macro2::Ident;
+use syn::parse::{Parse, ParseStream};
+use syn::token::Paren;
+use syn::{parenthesized, Error, LitStr, Token};
+
+/// The guard of a method marked with `#[non_reentrant]` or `#[non_reentrant(guard = "...")]`.
+pub struct NonReentrantAttr {
+    pub guard: String,
+}
+
+impl Parse for NonReentrantAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let guard = if input.peek(Paren) {
+            let content;
+            parenthesized!(content in input);
+            let ident: Ident = content.parse()?;
+            if ident != "guard" {
+                return Err(Error::new(ident.span(), "Expected `guard = \"...\"`."));
+            }
+            content.parse::<Token![=]>()?;
+            content.parse::<LitStr>()?.value()
+        } else {
+            "default".to_string()
+        };
+        Ok(Self { guard })
+    }
+}
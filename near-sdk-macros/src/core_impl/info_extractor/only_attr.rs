@@ -0,0 +1,60 @@
+use proc_macro2::Ident;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, Error, LitStr, Token};
+
+/// Accounts allowed to call a method marked with `#[only(...)]`.
+#[derive(Default)]
+pub struct OnlyAttr {
+    /// Whether the owner of the contract is allowed.
+    pub owner: bool,
+    /// The roles whose accounts are allowed.
+    pub roles: Vec<String>,
+}
+
+enum OnlyItem {
+    Owner,
+    Role(LitStr),
+}
+
+impl Parse for OnlyItem {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident: Ident = input.parse()?;
+        match ident.to_string().as_str() {
+            "owner" => Ok(Self::Owner),
+            "role" => {
+                input.parse::<Token![=]>()?;
+                Ok(Self::Role(input.parse()?))
+            }
+            _ => Err(Error::new(ident.span(), "Expected `owner` or `role = \"...\"`.")),
+        }
+    }
+}
+
+impl Parse for OnlyAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        parenthesized!(content in input);
+        let items = Punctuated::<OnlyItem, Token![,]>::parse_terminated(&content)?;
+        if items.is_empty() {
+            return Err(Error::new(content.span(), "Expected `owner` or `role = \"...\"`."));
+        }
+        let mut attr = Self::default();
+        for item in items {
+            match item {
+                OnlyItem::Owner => attr.owner = true,
+                OnlyItem::Role(role) => attr.roles.push(role.value()),
+            }
+        }
+        Ok(attr)
+    }
+}
+
+impl OnlyAttr {
+    /// Describes the allowed accounts, e.g. "the owner or an account with the role `minter`".
+    pub fn describe(&self) -> String {
+        let owner = if self.owner { Some("the owner".to_string()) } else { None };
+        let roles = self.roles.iter().map(|role| format!("an account with the role `{}`", role));
+        owner.into_iter().chain(roles).collect::<Vec<_>>().join(" or ")
+    }
+}
@@ -17,6 +17,17 @@ pub(crate) fn type_is_result(ty: &Type) -> bool {
     }
 }
 
+/// Checks whether the last segment of the type path is `name`, so that both `Promise` and
+/// `near_sdk::Promise` match "Promise". Type aliases do not match.
+pub(crate) fn type_is(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::Path(type_path) if type_path.qself.is_none() => {
+            type_path.path.segments.last().map_or(false, |segment| segment.ident == name)
+        }
+        _ => false,
+    }
+}
+
 /// Extracts the Ok type from a `Result` type.
 ///
 /// For example, given `Result<String, u8>` type it will return `String` type.
@@ -40,6 +51,19 @@ pub(crate) fn extract_ok_type(ty: &Type) -> Option<&Type> {
     }
 }
 
+/// Extracts the Ok types of a `#[callback_result]` parameter type, which is either a single
+/// `Result` or a tuple of `Result`s reading consecutive promise results.
+///
+/// For example, given `(Result<String, E>, Result<u8, E>)` type it will return `String` and `u8`.
+pub(crate) fn extract_ok_types(ty: &Type) -> Option<Vec<&Type>> {
+    match ty {
+        Type::Tuple(type_tuple) if !type_tuple.elems.is_empty() => {
+            type_tuple.elems.iter().map(extract_ok_type).collect()
+        }
+        _ => Some(vec![extract_ok_type(ty)?]),
+    }
+}
+
 /// Checks whether the given path is literally "Vec".
 /// Note that it won't match a fully qualified name `std::vec::Vec` or a type alias like
 /// `type MyVec = Vec<String>`.
@@ -28,6 +28,39 @@ use syn::{parse_quote, File, ItemEnum, ItemImpl, ItemStruct, ItemTrait, WhereCla
 /// done through `serde` serialized as JSON, but this can be overwritten using
 /// `#[result_serializer(borsh)]`.
 ///
+/// When used on an impl section as `#[near_bindgen(panic_with_method_name)]`, panic messages of
+/// the exported methods will be prefixed with the name of the method, see
+/// `near_sdk::env::set_panic_method_name`.
+///
+/// `#[near_bindgen(pausable)]` adds the `pause`, `unpause` and `is_paused` methods to the impl
+/// section, and methods marked with `#[when_not_paused]` panic while the contract is paused.
+/// Methods marked with `#[non_reentrant]` hold a guard until they return, or until their
+/// `#[private]` callback returns if they return a promise. Their impl section gets the owner-only
+/// `release_guard` method, so the contract has to implement `near_sdk::access::Owner` and keep its
+/// `#[non_reentrant]` methods in a single impl section, see `near_sdk::access`.
+///
+/// A generic impl section is exported for the concrete type given with
+/// `#[near_bindgen(instantiate(Vault<Usdc>))]`, as in `impl<T: Token> Vault<T>`. The type
+/// parameters of the methods' signatures are replaced with the arguments of that type, and an
+/// associated type such as `T::Balance` is resolved through the trait bound of `T`. If `T` has
+/// several trait bounds, the trait has to be named, as in `<T as Token>::Balance`. Only one type
+/// can be instantiated, since the exported methods of a contract must have unique names.
+///
+/// An `async` method taking `&mut self` can `.await` the promises of cross-contract calls in
+/// statements like `let balance: U128 = ext_token::ext(token).ft_balance_of(account_id).await;` or
+/// `ext_token::ext(token).ft_transfer(receiver_id, amount).await;`. The method is split at each
+/// `.await` into `#[private]` callbacks named `__<method>_resume_<n>`, and returns the awaited
+/// promise chained with the next callback. The variables used after an `.await` are passed to the
+/// callback as arguments, so they need type annotations. A result whose type is a `Result` is read
+/// with `#[callback_result]`, any other result with `#[callback_unwrap]`, so only a `Result` lets
+/// the method continue after a failed call. The callback after an `.await` statement, or a result
+/// discarded with `let _: T`, panics if the call failed unless `T` is a `Result`. The method can't
+/// return early before its last `.await`.
+///
+/// Change methods can restrict their callers with `#[only(owner)]` or `#[only(role = "...")]`, and
+/// require an attached deposit with `#[deposit(exact = ...)]` or `#[deposit(min = ...)]`, see
+/// `near_sdk::access`.
+///
 /// # Examples
 ///
 /// ```ignore
@@ -44,7 +77,7 @@ use syn::{parse_quote, File, ItemEnum, ItemImpl, ItemStruct, ItemTrait, WhereCla
 /// }
 /// ```
 #[proc_macro_attribute]
-pub fn near_bindgen(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn near_bindgen(attr: TokenStream, item: TokenStream) -> TokenStream {
     if let Ok(input) = syn::parse::<ItemStruct>(item.clone()) {
         let ext_gen = generate_ext_structs(&input.ident, Some(&input.generics));
         #[cfg(feature = "__abi-embed")]
@@ -68,12 +101,36 @@ pub fn near_bindgen(_attr: TokenStream, item: TokenStream) -> TokenStream {
             #abi_embedded
         })
     } else if let Ok(mut input) = syn::parse::<ItemImpl>(item) {
-        let item_impl_info = match ItemImplInfo::new(&mut input) {
+        let bindgen_attr = match syn::parse::<BindgenAttr>(attr) {
             Ok(x) => x,
             Err(err) => {
                 return err.to_compile_error().into();
             }
         };
+        if bindgen_attr.pausable {
+            if let Err(err) = pausable::add_pausable_methods(&mut input) {
+                return err.to_compile_error().into();
+            }
+        }
+        if let Err(err) = pausable::add_release_guard_method(&mut input) {
+            return err.to_compile_error().into();
+        }
+        if let Err(err) = async_methods::split_async_methods(&mut input) {
+            return err.to_compile_error().into();
+        }
+        let item_impl_info = match &bindgen_attr.instantiate {
+            Some(instance) => ItemImplInfo::new_instance(&mut input, instance),
+            None => ItemImplInfo::new(&mut input),
+        };
+        let mut item_impl_info = match item_impl_info {
+            Ok(x) => x,
+            Err(err) => {
+                return err.to_compile_error().into();
+            }
+        };
+        for method in &mut item_impl_info.methods {
+            method.panic_with_method_name = bindgen_attr.panic_with_method_name;
+        }
 
         #[cfg(not(feature = "__abi-generate"))]
         let abi_generated = quote! {};
@@ -241,8 +298,9 @@ pub fn metadata(item: TokenStream) -> TokenStream {
 pub fn derive_no_default(item: TokenStream) -> TokenStream {
     if let Ok(input) = syn::parse::<ItemStruct>(item) {
         let name = &input.ident;
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
         TokenStream::from(quote! {
-            impl Default for #name {
+            impl #impl_generics Default for #name #ty_generics #where_clause {
                 fn default() -> Self {
                     near_sdk::env::panic_str("The contract is not initialized");
                 }
@@ -0,0 +1,42 @@
+//! Methods restricted to the owner, roles and attached deposits.
+
+use near_sdk::access::{Owner, Roles};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::store::{LookupMap, LookupSet};
+use near_sdk::{near_bindgen, AccountId, PanicOnDefault};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Contract {
+    owner: AccountId,
+    minters: LookupSet<AccountId>,
+    balances: LookupMap<AccountId, u128>,
+}
+
+impl Owner for Contract {
+    fn owner(&self) -> AccountId {
+        self.owner.clone()
+    }
+}
+
+impl Roles for Contract {
+    fn has_role(&self, account_id: &AccountId, role: &str) -> bool {
+        role == "minter" && self.minters.contains(account_id)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    #[only(owner)]
+    pub fn add_minter(&mut self, account_id: AccountId) {
+        self.minters.insert(account_id);
+    }
+
+    #[only(owner, role = "minter")]
+    #[deposit(exact = 1)]
+    pub fn mint(&mut self, account_id: AccountId, amount: u128) {
+        *self.balances.entry(account_id).or_default() += amount;
+    }
+}
+
+fn main() {}
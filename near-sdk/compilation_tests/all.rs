@@ -20,4 +20,18 @@ fn compilation_tests() {
     t.pass("compilation_tests/borsh_storage_key_generics.rs");
     t.pass("compilation_tests/function_error.rs");
     t.pass("compilation_tests/enum_near_bindgen.rs");
+    t.pass("compilation_tests/panic_with_method_name.rs");
+    t.pass("compilation_tests/access_control.rs");
+    t.compile_fail("compilation_tests/only_without_self.rs");
+    t.compile_fail("compilation_tests/only_view.rs");
+    t.pass("compilation_tests/pausable.rs");
+    t.compile_fail("compilation_tests/pausable_trait_impl.rs");
+    t.compile_fail("compilation_tests/non_reentrant_without_callback.rs");
+    t.pass("compilation_tests/non_reentrant.rs");
+    t.compile_fail("compilation_tests/non_reentrant_borsh.rs");
+    t.pass("compilation_tests/async_method.rs");
+    t.compile_fail("compilation_tests/async_view.rs");
+    t.compile_fail("compilation_tests/async_nested_await.rs");
+    t.pass("compilation_tests/instantiate.rs");
+    t.compile_fail("compilation_tests/instantiate_mismatch.rs");
 }
@@ -0,0 +1,60 @@
+//! Async methods awaiting cross-contract calls.
+
+use near_sdk::access::Owner;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{env, ext_contract, near_bindgen, AccountId, PanicOnDefault, PromiseError};
+
+#[ext_contract(ext_token)]
+trait Token {
+    fn ft_balance_of(&self, account_id: AccountId) -> U128;
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128);
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Contract {
+    owner: AccountId,
+    token: AccountId,
+    total: u128,
+}
+
+impl Owner for Contract {
+    fn owner(&self) -> AccountId {
+        self.owner.clone()
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    #[non_reentrant]
+    pub async fn sweep(&mut self, receiver_id: AccountId) -> U128 {
+        let token: AccountId = self.token.clone();
+        let balance: U128 =
+            ext_token::ext(token.clone()).ft_balance_of(env::current_account_id()).await;
+        let transferred: Result<(), PromiseError> =
+            ext_token::ext(token).ft_transfer(receiver_id, balance).await;
+        if transferred.is_ok() {
+            self.total += balance.0;
+        }
+        U128(self.total)
+    }
+
+    #[handle_result]
+    pub async fn check(&mut self, account_id: AccountId) -> Result<U128, &'static str> {
+        let mut checks: u32 = 0;
+        let balance: U128 = ext_token::ext(self.token.clone()).ft_balance_of(account_id).await;
+        checks += 1;
+        if checks > 0 && balance.0 > 0 {
+            Ok(balance)
+        } else {
+            Err("Empty balance")
+        }
+    }
+
+    pub async fn no_await(&mut self) -> u128 {
+        self.total
+    }
+}
+
+fn main() {}
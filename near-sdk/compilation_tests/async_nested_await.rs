@@ -0,0 +1,24 @@
+//! `.await` is only supported in the statements of an async method.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{ext_contract, near_bindgen, AccountId, PanicOnDefault};
+
+#[ext_contract(ext_counter)]
+trait Counter {
+    fn get(&self) -> u64;
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Contract {
+    counter: AccountId,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub async fn double(&mut self) -> u64 {
+        2 * ext_counter::ext(self.counter.clone()).get().await
+    }
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+//! Async view methods can't create the promises they await
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{ext_contract, near_bindgen, AccountId, PanicOnDefault};
+
+#[ext_contract(ext_token)]
+trait Token {
+    fn ft_balance_of(&self, account_id: AccountId) -> U128;
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Contract {
+    token: AccountId,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub async fn balance(&self, account_id: AccountId) -> U128 {
+        ext_token::ext(self.token.clone()).ft_balance_of(account_id).await
+    }
+}
+
+fn main() {}
@@ -0,0 +1,59 @@
+//! Generic contract exported for a concrete type.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{env, near_bindgen, AccountId, PanicOnDefault};
+use std::marker::PhantomData;
+
+pub trait Token {
+    type Balance;
+
+    fn contract() -> AccountId;
+    fn to_yocto(balance: Self::Balance) -> u128;
+}
+
+pub struct Usdc;
+
+impl Token for Usdc {
+    type Balance = u64;
+
+    fn contract() -> AccountId {
+        "usdc.near".parse().unwrap()
+    }
+
+    fn to_yocto(balance: u64) -> u128 {
+        balance as u128
+    }
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Vault<T, const N: usize> {
+    deposits: Vec<u128>,
+    token: PhantomData<T>,
+}
+
+#[near_bindgen(instantiate(Vault<Usdc, 4>))]
+impl<T: Token, const N: usize> Vault<T, N> {
+    #[init]
+    pub fn new() -> Self {
+        Self { deposits: Vec::with_capacity(N), token: PhantomData }
+    }
+
+    #[private]
+    pub fn deposit(&mut self, #[serializer(borsh)] amount: T::Balance) {
+        if self.deposits.len() == N {
+            env::panic_str("Vault is full");
+        }
+        self.deposits.push(T::to_yocto(amount));
+    }
+
+    pub fn token(&self) -> AccountId {
+        T::contract()
+    }
+
+    pub fn slots(&self) -> [u8; N] {
+        [0; N]
+    }
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+//! The instantiated type must be the type of the generic impl section.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::near_bindgen;
+use std::marker::PhantomData;
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+pub struct Incrementer<T> {
+    value: u32,
+    data: PhantomData<T>,
+}
+
+#[near_bindgen(instantiate(Counter<u8>))]
+impl<T> Incrementer<T> {
+    pub fn inc(&mut self, by: u32) {
+        self.value += by;
+    }
+}
+
+fn main() {}
@@ -0,0 +1,35 @@
+//! Guarded methods get the `release_guard` method without `pausable`
+
+use near_sdk::access::Owner;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{env, near_bindgen, AccountId, PanicOnDefault, Promise};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Contract {
+    owner: AccountId,
+}
+
+impl Owner for Contract {
+    fn owner(&self) -> AccountId {
+        self.owner.clone()
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    #[non_reentrant]
+    pub fn withdraw(&mut self, amount: u128) -> Promise {
+        Promise::new(env::predecessor_account_id())
+            .transfer(amount)
+            .then(Self::ext(env::current_account_id()).on_withdraw())
+    }
+
+    #[private]
+    #[non_reentrant]
+    pub fn on_withdraw(&mut self) {}
+}
+
+fn main() {
+    let _ = Contract::release_guard;
+}
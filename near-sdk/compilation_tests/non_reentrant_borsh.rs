@@ -0,0 +1,28 @@
+//! The token of a guard is passed to callbacks in their JSON arguments
+
+use near_sdk::access::Owner;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{near_bindgen, AccountId, PanicOnDefault};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Contract {
+    owner: AccountId,
+}
+
+impl Owner for Contract {
+    fn owner(&self) -> AccountId {
+        self.owner.clone()
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    #[private]
+    #[non_reentrant]
+    pub fn on_withdraw(&mut self, #[serializer(borsh)] amount: u128) {
+        let _ = amount;
+    }
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+//! A guarded method returning a promise needs a callback releasing the guard.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{env, near_bindgen, Promise};
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+pub struct Contract {}
+
+#[near_bindgen]
+impl Contract {
+    #[non_reentrant(guard = "withdraw")]
+    pub fn withdraw(&mut self, amount: u128) -> Promise {
+        Promise::new(env::predecessor_account_id()).transfer(amount)
+    }
+
+    #[private]
+    #[non_reentrant]
+    pub fn on_withdraw(&mut self) {}
+}
+
+fn main() {}
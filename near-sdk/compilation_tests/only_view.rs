@@ -0,0 +1,27 @@
+//! `#[only]` reads the predecessor, which view calls don't have
+
+use near_sdk::access::Owner;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{near_bindgen, AccountId, PanicOnDefault};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+struct Test {
+    owner: AccountId,
+}
+
+impl Owner for Test {
+    fn owner(&self) -> AccountId {
+        self.owner.clone()
+    }
+}
+
+#[near_bindgen]
+impl Test {
+    #[only(owner)]
+    pub fn secret(&self) -> u64 {
+        42
+    }
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+//! `#[only]` needs the contract state to read the owner
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::near_bindgen;
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Test {}
+
+#[near_bindgen]
+impl Test {
+    #[only(owner)]
+    pub fn reset() {}
+}
+
+fn main() {}
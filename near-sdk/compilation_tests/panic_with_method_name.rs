@@ -0,0 +1,24 @@
+//! Method names can be added to panic messages.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::{near_bindgen, require};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+struct Contract {
+    value: u32,
+}
+
+#[near_bindgen(panic_with_method_name)]
+impl Contract {
+    pub fn set(&mut self, value: u32) {
+        require!(value > 0, 1, "value must be positive");
+        self.value = value;
+    }
+
+    pub fn get(&self) -> u32 {
+        self.value
+    }
+}
+
+fn main() {}
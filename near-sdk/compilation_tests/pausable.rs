@@ -0,0 +1,43 @@
+//! Pausable contract with a reentrancy guard spanning a callback.
+
+use near_sdk::access::Owner;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::store::LookupMap;
+use near_sdk::{env, near_bindgen, AccountId, PanicOnDefault, Promise};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Contract {
+    owner: AccountId,
+    balances: LookupMap<AccountId, u128>,
+}
+
+impl Owner for Contract {
+    fn owner(&self) -> AccountId {
+        self.owner.clone()
+    }
+}
+
+#[near_bindgen(pausable)]
+impl Contract {
+    #[when_not_paused]
+    #[non_reentrant]
+    pub fn withdraw(&mut self, amount: u128) -> Promise {
+        let account_id = env::predecessor_account_id();
+        let balance = self.balances.get_mut(&account_id).expect("No balance");
+        *balance = balance.checked_sub(amount).expect("Not enough balance");
+        Promise::new(account_id.clone())
+            .transfer(amount)
+            .then(Self::ext(env::current_account_id()).on_withdraw(account_id, amount))
+    }
+
+    #[private]
+    #[non_reentrant]
+    pub fn on_withdraw(&mut self, account_id: AccountId, amount: u128) {
+        if !near_sdk::is_promise_success() {
+            *self.balances.entry(account_id).or_default() += amount;
+        }
+    }
+}
+
+fn main() {}
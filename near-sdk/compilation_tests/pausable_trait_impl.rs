@@ -0,0 +1,24 @@
+//! The pause methods can't be added to a trait implementation
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::near_bindgen;
+
+pub trait Counter {
+    fn increment(&mut self);
+}
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Test {
+    value: u64,
+}
+
+#[near_bindgen(pausable)]
+impl Counter for Test {
+    #[when_not_paused]
+    fn increment(&mut self) {
+        self.value += 1;
+    }
+}
+
+fn main() {}
@@ -0,0 +1,380 @@
+//! Access control for the methods exported with [`near_bindgen`](crate::near_bindgen).
+//!
+//! Methods can restrict their callers and the attached deposit with the following attributes,
+//! checked before the arguments are deserialized:
+//!
+//! - `#[only(owner)]` allows only the account returned by [`Owner::owner`].
+//! - `#[only(role = "minter")]` allows only the accounts for which [`Roles::has_role`] returns
+//!   `true`. Listing several accounts, as in `#[only(owner, role = "minter")]`, allows any of
+//!   them.
+//! - `#[deposit(exact = 1)]` and `#[deposit(min = 1000)]` require an attached deposit, in
+//!   yoctoNEAR, and make the method payable.
+//!
+//! The checks of `#[only]` read the contract state, so the method has to take `self`. The checks
+//! are listed in the documentation of the method in the ABI.
+//!
+//! # Pausing
+//!
+//! `#[near_bindgen(pausable)]` on the impl block of a contract implementing [`Owner`] adds the
+//! `pause` and `unpause` methods, only callable by the owner, and the `is_paused` view method.
+//! Methods marked with `#[when_not_paused]` panic while the contract is paused. The flag is kept
+//! in storage outside of the contract state, see [`is_paused`] and [`set_paused`].
+//!
+//! # Reentrancy
+//!
+//! A method marked with `#[non_reentrant]` holds a guard for the duration of the call, and
+//! panics if the guard is already held. If the method returns a [`Promise`](crate::Promise), the
+//! guard stays held until a callback of the contract marked with `#[non_reentrant]` for the same
+//! guard returns without a promise. Methods share the default guard unless they name one, as in
+//! `#[non_reentrant(guard = "withdraw")]`.
+//!
+//! Taking the guard stores a new token in it, and the calls built with the `ext` function of the
+//! contract to its `#[non_reentrant]` methods pass that token as an argument while the guard is
+//! held. Only a callback with the token of the guard is let through and releases it, so the
+//! callbacks of other calls can't. The token is passed in the JSON arguments, so
+//! `#[non_reentrant]` methods can't take `#[serializer(borsh)]` arguments.
+//!
+//! A `#[non_reentrant]` method returning a promise needs a `#[private]` `#[non_reentrant]`
+//! callback for the same guard in the same impl section, otherwise the guard would never be
+//! released. A panic reverts the changes to the guard along with the rest of the call, so a
+//! callback which panics, including by running out of gas, leaves the guard held. The owner can
+//! then release it with the `release_guard` method, which is added to the impl section with the
+//! `#[non_reentrant]` methods. The contract therefore has to implement [`Owner`], and all of its
+//! `#[non_reentrant]` methods have to be in that impl section, without a trait.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::access::{Owner, Roles};
+//! use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+//! use near_sdk::store::{LookupMap, LookupSet};
+//! use near_sdk::{near_bindgen, AccountId, PanicOnDefault};
+//!
+//! #[near_bindgen]
+//! #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+//! pub struct Contract {
+//!     owner: AccountId,
+//!     minters: LookupSet<AccountId>,
+//!     balances: LookupMap<AccountId, u128>,
+//! }
+//!
+//! impl Owner for Contract {
+//!     fn owner(&self) -> AccountId {
+//!         self.owner.clone()
+//!     }
+//! }
+//!
+//! impl Roles for Contract {
+//!     fn has_role(&self, account_id: &AccountId, role: &str) -> bool {
+//!         role == "minter" && self.minters.contains(account_id)
+//!     }
+//! }
+//!
+//! #[near_bindgen]
+//! impl Contract {
+//!     #[only(owner)]
+//!     pub fn add_minter(&mut self, account_id: AccountId) {
+//!         self.minters.insert(account_id);
+//!     }
+//!
+//!     #[only(owner, role = "minter")]
+//!     #[deposit(exact = 1)]
+//!     pub fn mint(&mut self, account_id: AccountId, amount: u128) {
+//!         *self.balances.entry(account_id).or_default() += amount;
+//!     }
+//! }
+//! ```
+//!
+//! A token whose transfers can be paused by the owner, and whose withdrawals wait for the result
+//! of the transfer before accepting another one:
+//!
+//! ```
+//! use near_sdk::access::Owner;
+//! use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+//! use near_sdk::store::LookupMap;
+//! use near_sdk::{env, near_bindgen, AccountId, PanicOnDefault, Promise};
+//!
+//! #[near_bindgen]
+//! #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+//! pub struct Contract {
+//!     owner: AccountId,
+//!     balances: LookupMap<AccountId, u128>,
+//! }
+//!
+//! impl Owner for Contract {
+//!     fn owner(&self) -> AccountId {
+//!         self.owner.clone()
+//!     }
+//! }
+//!
+//! #[near_bindgen(pausable)]
+//! impl Contract {
+//!     #[when_not_paused]
+//!     #[non_reentrant]
+//!     pub fn withdraw(&mut self, amount: u128) -> Promise {
+//!         let account_id = env::predecessor_account_id();
+//!         let balance = self.balances.get_mut(&account_id).expect("No balance");
+//!         *balance = balance.checked_sub(amount).expect("Not enough balance");
+//!         Promise::new(account_id.clone())
+//!             .transfer(amount)
+//!             .then(Self::ext(env::current_account_id()).on_withdraw(account_id, amount))
+//!     }
+//!
+//!     #[private]
+//!     #[non_reentrant]
+//!     pub fn on_withdraw(&mut self, account_id: AccountId, amount: u128) {
+//!         if !near_sdk::is_promise_success() {
+//!             *self.balances.entry(account_id).or_default() += amount;
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::cell::RefCell;
+
+use serde::Deserialize;
+
+use crate::{env, AccountId};
+
+const PAUSED_KEY: &[u8] = b"__PAUSED__";
+const GUARD_KEY_PREFIX: &[u8] = b"__GUARD__:";
+const GUARD_NONCE_KEY: &[u8] = b"__GUARD_NONCE__";
+
+thread_local! {
+    /// The guards held by the current call, with their tokens.
+    // `const` initializers are not supported by the minimum supported Rust version.
+    #[allow(unknown_lints, clippy::thread_local_initializer_can_be_made_const)]
+    static HELD_GUARDS: RefCell<Vec<(String, u64)>> = RefCell::new(Vec::new());
+}
+
+/// Contract state with an owner, checked by `#[only(owner)]`.
+pub trait Owner {
+    /// Returns the account which owns the contract.
+    fn owner(&self) -> AccountId;
+}
+
+/// Contract state assigning roles to accounts, checked by `#[only(role = "...")]`.
+pub trait Roles {
+    /// Returns `true` if `account_id` has the `role`.
+    fn has_role(&self, account_id: &AccountId, role: &str) -> bool;
+}
+
+/// Returns `true` if the contract is paused, see `#[near_bindgen(pausable)]`.
+pub fn is_paused() -> bool {
+    env::storage_has_key(PAUSED_KEY)
+}
+
+/// Pauses or unpauses the methods marked with `#[when_not_paused]`. The generated `pause` and
+/// `unpause` methods call this function after checking the owner.
+pub fn set_paused(paused: bool) {
+    if paused {
+        env::storage_write(PAUSED_KEY, &[]);
+    } else {
+        env::storage_remove(PAUSED_KEY);
+    }
+}
+
+/// Returns `true` if a call holds the `#[non_reentrant]` guard named `guard`. The guard of the
+/// methods which do not name one is `"default"`.
+pub fn is_guard_held(guard: &str) -> bool {
+    env::storage_has_key(&guard_key(guard))
+}
+
+/// Releases the `#[non_reentrant]` guard named `guard`, whatever call holds it. This is meant for
+/// the owner of the contract, to release a guard which a callback left held by panicking.
+pub fn release_guard(guard: &str) {
+    env::storage_remove(&guard_key(guard));
+}
+
+fn guard_key(guard: &str) -> Vec<u8> {
+    [GUARD_KEY_PREFIX, guard.as_bytes()].concat()
+}
+
+/// Reads the token stored in a guard. Guards stored without a token have none.
+fn stored_token(key: &[u8]) -> Option<Option<u64>> {
+    env::storage_read(key).map(|value| value.try_into().ok().map(u64::from_le_bytes))
+}
+
+fn held_token(guard: &str) -> Option<u64> {
+    HELD_GUARDS
+        .with(|held| held.borrow().iter().find(|(name, _)| name == guard).map(|&(_, token)| token))
+}
+
+/// Reads the guard token passed to a callback by [`attach_guard_token`], if any.
+fn input_token() -> Option<u64> {
+    #[derive(Deserialize)]
+    #[serde(crate = "crate::serde")]
+    struct Input {
+        __guard_token: Option<u64>,
+    }
+    let input = env::input()?;
+    serde_json::from_slice::<Input>(&input).ok()?.__guard_token
+}
+
+/// Takes the guard of a `#[non_reentrant]` method, panicking with `error` if it is held. A
+/// callback of the contract with the token of the guard continues to hold it.
+#[doc(hidden)]
+pub fn enter_guard(guard: &str, error: &str) {
+    let key = guard_key(guard);
+    let token = match stored_token(&key) {
+        None => {
+            let nonce = env::storage_read(GUARD_NONCE_KEY)
+                .and_then(|nonce| nonce.try_into().ok())
+                .map_or(0, u64::from_le_bytes);
+            let token = nonce.wrapping_add(1);
+            env::storage_write(GUARD_NONCE_KEY, &token.to_le_bytes());
+            env::storage_write(&key, &token.to_le_bytes());
+            token
+        }
+        Some(Some(token))
+            if env::promise_results_count() > 0
+                && env::predecessor_account_id() == env::current_account_id()
+                && input_token() == Some(token) =>
+        {
+            token
+        }
+        Some(_) => env::panic_str(error),
+    };
+    HELD_GUARDS.with(|held| held.borrow_mut().push((guard.to_string(), token)));
+}
+
+/// Releases the guard of a `#[non_reentrant]` method which did not return a promise, if the call
+/// still holds it.
+#[doc(hidden)]
+pub fn exit_guard(guard: &str) {
+    let key = guard_key(guard);
+    if let Some(token) = held_token(guard) {
+        if stored_token(&key) == Some(Some(token)) {
+            env::storage_remove(&key);
+        }
+    }
+    HELD_GUARDS.with(|held| held.borrow_mut().retain(|(name, _)| name != guard));
+}
+
+/// Adds the token of `guard` to the JSON arguments of a call to a `#[non_reentrant]` method of
+/// the contract, if the current call holds the guard.
+#[doc(hidden)]
+pub fn attach_guard_token(guard: &str, args: Vec<u8>) -> Vec<u8> {
+    let token = match held_token(guard) {
+        Some(token) => token,
+        None => return args,
+    };
+    let field = format!("\"__guard_token\":{}", token);
+    match args.split_last() {
+        // The arguments are serialized as a JSON object, or empty if there are none.
+        Some((&b'}', [b'{'])) | None => format!("{{{}}}", field).into_bytes(),
+        Some((&b'}', object)) => [object, b",", field.as_bytes(), b"}"].concat(),
+        Some(_) => args,
+    }
+}
+
+/// Forgets the guards held by the previous call, when the blockchain interface is replaced in
+/// tests.
+#[cfg(all(not(target_arch = "wasm32"), feature = "unit-testing"))]
+pub(crate) fn reset_held_guards() {
+    HELD_GUARDS.with(|held| held.borrow_mut().clear());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_env::{alice, bob};
+    use crate::test_utils::VMContextBuilder;
+    use crate::{testing_env, PromiseResult, RuntimeFeesConfig, VMConfig};
+
+    #[test]
+    fn pause() {
+        assert!(!is_paused());
+        set_paused(true);
+        assert!(is_paused());
+        set_paused(false);
+        assert!(!is_paused());
+    }
+
+    #[test]
+    fn guards() {
+        enter_guard("a", "a is held");
+        enter_guard("b", "b is held");
+        assert!(is_guard_held("a") && is_guard_held("b"));
+        exit_guard("a");
+        assert!(!is_guard_held("a"));
+        enter_guard("a", "a is held");
+        assert!(is_guard_held("a"));
+    }
+
+    #[test]
+    #[should_panic(expected = "default is held")]
+    fn reentrant_call() {
+        enter_guard("default", "default is held");
+        enter_guard("default", "default is held");
+    }
+
+    fn callback_context(input: Vec<u8>) {
+        // The callback scheduled by the contract runs while the guard is held.
+        let mut context = VMContextBuilder::new().predecessor_account_id(alice()).build();
+        context.input = input;
+        testing_env!(
+            context,
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])],
+        );
+    }
+
+    #[test]
+    fn callback_releases_guard() {
+        enter_guard("default", "default is held");
+        let args = attach_guard_token("default", br#"{"amount":1}"#.to_vec());
+
+        callback_context(args);
+        enter_guard("default", "default is held");
+        exit_guard("default");
+        assert!(!is_guard_held("default"));
+    }
+
+    #[test]
+    #[should_panic(expected = "default is held")]
+    fn callback_of_other_call() {
+        enter_guard("default", "default is held");
+
+        callback_context(br#"{"amount":1}"#.to_vec());
+        enter_guard("default", "default is held");
+    }
+
+    #[test]
+    #[should_panic(expected = "default is held")]
+    fn callback_after_release() {
+        enter_guard("default", "default is held");
+        let args = attach_guard_token("default", vec![]);
+        release_guard("default");
+
+        // Another call takes the guard before the callback of the first one runs.
+        testing_env!(VMContextBuilder::new().predecessor_account_id(bob()).build());
+        enter_guard("default", "default is held");
+        callback_context(args);
+        enter_guard("default", "default is held");
+    }
+
+    #[test]
+    fn guard_token_args() {
+        assert_eq!(attach_guard_token("default", b"{}".to_vec()), b"{}");
+        enter_guard("default", "default is held");
+        assert_eq!(attach_guard_token("default", vec![]), br#"{"__guard_token":1}"#);
+        assert_eq!(attach_guard_token("default", b"{}".to_vec()), br#"{"__guard_token":1}"#);
+        assert_eq!(
+            attach_guard_token("default", br#"{"a":1}"#.to_vec()),
+            br#"{"a":1,"__guard_token":1}"#
+        );
+        assert_eq!(attach_guard_token("other", b"{}".to_vec()), b"{}");
+    }
+
+    #[test]
+    #[should_panic(expected = "default is held")]
+    fn external_call_while_held() {
+        enter_guard("default", "default is held");
+
+        testing_env!(VMContextBuilder::new().predecessor_account_id(bob()).build());
+        enter_guard("default", "default is held");
+    }
+}
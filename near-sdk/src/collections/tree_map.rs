@@ -2,7 +2,7 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use std::ops::Bound;
 
 use crate::collections::LookupMap;
-use crate::collections::{append, Vector};
+use crate::collections::{append, Vector, ERR_INCONSISTENT_STATE};
 use crate::{env, IntoStorageKey};
 
 /// TreeMap based on AVL-tree
@@ -117,6 +117,22 @@ where
         }
     }
 
+    /// Returns the prefix the map was created with.
+    pub(crate) fn prefix(&self) -> &[u8] {
+        let values_prefix = self.val.prefix();
+        &values_prefix[..values_prefix.len() - 1]
+    }
+
+    /// Removes the entry of the last node of the tree without relinking the other nodes, so only
+    /// `get` and `contains_key` can still be used on the map. Used to move the entries to a
+    /// [`store::TreeMap`](crate::store::TreeMap) one at a time.
+    pub(crate) fn pop_unlinked(&mut self) -> Option<(K, V)> {
+        let node = self.tree.pop()?;
+        let value =
+            self.val.remove(&node.key).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+        Some((node.key, value))
+    }
+
     /// Returns true if the map contains a given key.
     ///
     /// # Examples
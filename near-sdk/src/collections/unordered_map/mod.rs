@@ -81,6 +81,11 @@ impl<K, V> UnorderedMap<K, V> {
         }
     }
 
+    /// Returns the prefix the map was created with.
+    pub(crate) fn prefix(&self) -> &[u8] {
+        &self.key_index_prefix[..self.key_index_prefix.len() - 1]
+    }
+
     fn serialize_index(index: u64) -> [u8; size_of::<u64>()] {
         index.to_le_bytes()
     }
@@ -72,6 +72,10 @@ impl<T> Vector<T> {
         }
     }
 
+    pub(crate) fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
     fn index_to_lookup_key(&self, index: u64) -> Vec<u8> {
         append_slice(&self.prefix, &index.to_le_bytes()[..])
     }
@@ -3,6 +3,7 @@
 //! whenever possible. In case of cross-contract calls prefer using even higher-level API available
 //! through `callback_args`, `callback_args_vec`, `ext_contract`, `Promise`, and `PromiseOrValue`.
 
+use std::cell::Cell;
 use std::convert::TryInto;
 use std::mem::size_of;
 use std::panic as std_panic;
@@ -10,6 +11,9 @@ use std::{convert::TryFrom, mem::MaybeUninit};
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "unit-testing"))]
 use crate::mock::MockedBlockchain;
+use crate::types::alt_bn128::{
+    G1Point, G2Point, Scalar, G1_POINT_SIZE, G2_POINT_SIZE, SCALAR_SIZE,
+};
 use crate::types::{
     AccountId, Balance, BlockHeight, Gas, PromiseIndex, PromiseResult, PublicKey, StorageUsage,
 };
@@ -25,6 +29,16 @@ const REGISTER_EXPECTED_ERR: &str =
 const ATOMIC_OP_REGISTER: u64 = std::u64::MAX - 2;
 /// Register used to record evicted values from the storage.
 const EVICTED_REGISTER: u64 = std::u64::MAX - 1;
+/// Register used to read values from a storage iterator. Keys are read through
+/// `ATOMIC_OP_REGISTER`.
+const STORAGE_ITER_VALUE_REGISTER: u64 = u64::MAX - 3;
+
+thread_local! {
+    /// Name of the method that prefixes panic messages, see [`set_panic_method_name`].
+    // `const` initializers are not supported by the minimum supported Rust version.
+    #[allow(unknown_lints, clippy::thread_local_initializer_can_be_made_const)]
+    static PANIC_METHOD_NAME: Cell<Option<&'static str>> = Cell::new(None);
+}
 
 /// Key used to store the state of the contract.
 const STATE_KEY: &[u8] = b"STATE";
@@ -99,7 +113,10 @@ pub(crate) unsafe fn read_register_fixed_64(register_id: u64) -> [u8; 64] {
 pub fn set_blockchain_interface(blockchain_interface: MockedBlockchain) {
     crate::mock::with_mocked_blockchain(|b| {
         *b = blockchain_interface;
-    })
+    });
+    // The method name of a previous test must not prefix the panics of the next one.
+    PANIC_METHOD_NAME.with(|name| name.set(None));
+    crate::access::reset_held_guards();
 }
 
 /// Implements panic hook that converts `PanicInfo` into a string and provides it through the
@@ -110,6 +127,7 @@ fn panic_hook_impl(info: &std_panic::PanicInfo) {
 
 /// Setups panic hook to expose error info to the blockchain.
 pub fn setup_panic_hook() {
+    PANIC_METHOD_NAME.with(|name| name.set(None));
     std_panic::set_hook(Box::new(panic_hook_impl));
 }
 
@@ -145,6 +163,99 @@ pub fn register_len(register_id: u64) -> Option<u64> {
     }
 }
 
+/// Writes `data` into the `register_id`, overwriting any data the register held before.
+pub fn write_register(register_id: u64, data: &[u8]) {
+    unsafe { sys::write_register(register_id, data.len() as _, data.as_ptr() as _) }
+}
+
+/// Handle to a register of the host. Registers hold data produced by host functions and can be
+/// passed back into host functions, which avoids copying the data in and out of the contract's
+/// memory when it is only forwarded.
+///
+/// Register ids close to `u64::MAX` are used internally by the `env` functions, so data in those
+/// registers is not preserved across calls to `env`.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::env::{self, Register};
+///
+/// let register = Register::new(0);
+/// env::storage_write(b"key", b"value");
+///
+/// assert!(env::storage_read_into_register(b"key", register));
+/// assert_eq!(register.len(), Some(5));
+/// assert_eq!(register.read_array(), Some(*b"value"));
+///
+/// // Return the value as the result of the call without reading it into a buffer.
+/// env::value_return_from_register(register);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Register(u64);
+
+impl Register {
+    /// Creates a handle to the register with the given id.
+    pub const fn new(register_id: u64) -> Self {
+        Self(register_id)
+    }
+
+    /// Returns the id of the register.
+    pub const fn id(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the length of the data in the register. If register is not used returns `None`.
+    pub fn len(self) -> Option<u64> {
+        register_len(self.0)
+    }
+
+    /// Returns `true` if the register is used and holds no data.
+    pub fn is_empty(self) -> bool {
+        self.len() == Some(0)
+    }
+
+    /// Reads the content of the register into a newly allocated buffer. If register is not used
+    /// returns `None`.
+    pub fn read(self) -> Option<Vec<u8>> {
+        read_register(self.0)
+    }
+
+    /// Reads the content of the register into the start of `buf` and returns the number of bytes
+    /// read. If register is not used returns `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` is smaller than the data in the register.
+    pub fn read_into(self, buf: &mut [u8]) -> Option<usize> {
+        let len = self.len()? as usize;
+        if len > buf.len() {
+            panic_str("Register data does not fit in the buffer.");
+        }
+        //* SAFETY: The length of the buffer was checked to fit the register data above.
+        unsafe { sys::read_register(self.0, buf.as_mut_ptr() as _) };
+        Some(len)
+    }
+
+    /// Reads the content of the register into a fixed size array. If register is not used
+    /// returns `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the data in the register is not `N`.
+    pub fn read_array<const N: usize>(self) -> Option<[u8; N]> {
+        let mut buf = [0; N];
+        if self.read_into(&mut buf)? != N {
+            panic_str("Register data does not match the expected length.");
+        }
+        Some(buf)
+    }
+
+    /// Writes `data` into the register, overwriting any data the register held before.
+    pub fn write(self, data: &[u8]) {
+        write_register(self.0, data)
+    }
+}
+
 // ###############
 // # Context API #
 // ###############
@@ -183,6 +294,13 @@ pub fn input() -> Option<Vec<u8>> {
     try_method_into_register!(input)
 }
 
+/// Writes the input to the contract call into `register`. Returns `false` if input is not
+/// provided.
+pub fn input_into_register(register: Register) -> bool {
+    unsafe { sys::input(register.id()) };
+    register.len().is_some()
+}
+
 /// Current block index.
 #[deprecated(since = "4.0.0", note = "Use block_height instead")]
 pub fn block_index() -> BlockHeight {
@@ -250,6 +368,13 @@ pub fn used_gas() -> Gas {
     Gas(unsafe { sys::used_gas() })
 }
 
+/// The gas that is left to be used during the contract execution, which is `prepaid_gas` minus
+/// `used_gas`.
+pub fn remaining_gas() -> Gas {
+    let prepaid_gas = prepaid_gas();
+    Gas(prepaid_gas.0.saturating_sub(used_gas().0))
+}
+
 // ############
 // # Math API #
 // ############
@@ -391,6 +516,109 @@ pub fn ecrecover(
     }
 }
 
+/// Verifies that the ed25519 `signature` over `message` was produced by the owner of
+/// `public_key`. Returns `true` if the signature is valid.
+///
+/// The public key is the raw 32 byte key. For a [`PublicKey`] with [`CurveType::ED25519`], these
+/// are the bytes following the curve type byte.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::env::ed25519_verify;
+/// use hex;
+///
+/// let public_key: [u8; 32] =
+///     hex::decode("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a")
+///         .unwrap()
+///         .try_into()
+///         .unwrap();
+/// let signature: [u8; 64] = hex::decode(
+///     "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+/// )
+/// .unwrap()
+/// .try_into()
+/// .unwrap();
+///
+/// assert!(ed25519_verify(&signature, b"", &public_key));
+/// assert!(!ed25519_verify(&signature, b"message", &public_key));
+/// ```
+///
+/// [`CurveType::ED25519`]: crate::CurveType::ED25519
+pub fn ed25519_verify(signature: &[u8; 64], message: &[u8], public_key: &[u8; 32]) -> bool {
+    match unsafe {
+        sys::ed25519_verify(
+            signature.len() as _,
+            signature.as_ptr() as _,
+            message.len() as _,
+            message.as_ptr() as _,
+            public_key.len() as _,
+            public_key.as_ptr() as _,
+        )
+    } {
+        0 => false,
+        1 => true,
+        _ => abort(),
+    }
+}
+
+/// Computes the multiexponentiation `sum(g1 * scalar)` over the given elements of the `alt_bn128`
+/// G1 group.
+///
+/// Panics if any of the points is not on the curve or if a scalar is not a valid field element.
+pub fn alt_bn128_g1_multiexp(elements: &[(G1Point, Scalar)]) -> G1Point {
+    let mut data = Vec::with_capacity(elements.len() * (G1_POINT_SIZE + SCALAR_SIZE));
+    for (point, scalar) in elements {
+        data.extend_from_slice(&point.to_bytes());
+        data.extend_from_slice(&scalar.0);
+    }
+    //* SAFETY: alt_bn128_g1_multiexp syscall will always generate 64 bytes inside of the atomic op
+    //*         register so the read will have a sufficient buffer of 64, and can transmute from
+    //*         uninit because all bytes are filled.
+    let result = unsafe {
+        sys::alt_bn128_g1_multiexp(data.len() as _, data.as_ptr() as _, ATOMIC_OP_REGISTER);
+        read_register_fixed_64(ATOMIC_OP_REGISTER)
+    };
+    G1Point::from_bytes(&result)
+}
+
+/// Computes the sum of the given elements of the `alt_bn128` G1 group. Each point is paired with
+/// a sign, where `true` means that the point is negated before being added.
+///
+/// Panics if any of the points is not on the curve.
+pub fn alt_bn128_g1_sum(elements: &[(bool, G1Point)]) -> G1Point {
+    let mut data = Vec::with_capacity(elements.len() * (1 + G1_POINT_SIZE));
+    for (negate, point) in elements {
+        data.push(*negate as u8);
+        data.extend_from_slice(&point.to_bytes());
+    }
+    //* SAFETY: alt_bn128_g1_sum syscall will always generate 64 bytes inside of the atomic op
+    //*         register so the read will have a sufficient buffer of 64, and can transmute from
+    //*         uninit because all bytes are filled.
+    let result = unsafe {
+        sys::alt_bn128_g1_sum(data.len() as _, data.as_ptr() as _, ATOMIC_OP_REGISTER);
+        read_register_fixed_64(ATOMIC_OP_REGISTER)
+    };
+    G1Point::from_bytes(&result)
+}
+
+/// Checks whether the product of the pairings `e(g1, g2)` of all given elements equals one, which
+/// is the check needed to verify pairing-based proofs such as Groth16.
+///
+/// Panics if any of the points is not on its curve.
+pub fn alt_bn128_pairing_check(elements: &[(G1Point, G2Point)]) -> bool {
+    let mut data = Vec::with_capacity(elements.len() * (G1_POINT_SIZE + G2_POINT_SIZE));
+    for (g1, g2) in elements {
+        data.extend_from_slice(&g1.to_bytes());
+        data.extend_from_slice(&g2.to_bytes());
+    }
+    match unsafe { sys::alt_bn128_pairing_check(data.len() as _, data.as_ptr() as _) } {
+        0 => false,
+        1 => true,
+        _ => abort(),
+    }
+}
+
 // ################
 // # Promises API #
 // ################
@@ -619,6 +847,19 @@ pub fn promise_result(result_idx: u64) -> PromiseResult {
     }
 }
 
+/// Writes the data of a successful promise result into `register`, see [`promise_result`].
+pub fn promise_result_into_register(
+    result_idx: u64,
+    register: Register,
+) -> Result<(), PromiseError> {
+    match unsafe { sys::promise_result(result_idx, register.id()) } {
+        0 => Err(PromiseError::NotReady),
+        1 => Ok(()),
+        2 => Err(PromiseError::Failed),
+        _ => abort(),
+    }
+}
+
 pub(crate) fn promise_result_internal(result_idx: u64) -> Result<(), PromiseError> {
     match unsafe { sys::promise_result(result_idx, ATOMIC_OP_REGISTER) } {
         0 => Err(PromiseError::NotReady),
@@ -661,6 +902,11 @@ pub fn validator_total_stake() -> Balance {
 pub fn value_return(value: &[u8]) {
     unsafe { sys::value_return(value.len() as _, value.as_ptr() as _) }
 }
+/// Sets the data in `register` as the return value of the contract, without reading it into the
+/// contract's memory.
+pub fn value_return_from_register(register: Register) {
+    unsafe { sys::value_return(u64::MAX, register.id()) }
+}
 /// Terminates the execution of the program with the UTF-8 encoded message.
 /// [`panic_str`] should be used as the bytes are required to be UTF-8
 #[deprecated(since = "4.0.0", note = "Use env::panic_str to panic with a message.")]
@@ -669,7 +915,56 @@ pub fn panic(message: &[u8]) -> ! {
 }
 
 /// Terminates the execution of the program with the UTF-8 encoded message.
+///
+/// If a method name was set with [`set_panic_method_name`], the message is prefixed with it.
 pub fn panic_str(message: &str) -> ! {
+    match PANIC_METHOD_NAME.with(|name| name.get()) {
+        Some(method_name) => panic_utf8(&[method_name, ": ", message].concat()),
+        None => panic_utf8(message),
+    }
+}
+
+/// Terminates the execution of the program with a machine-parseable JSON payload of the form
+/// `{"code":<code>,"msg":<message>}`. The `code` can be any serializable value, such as an integer
+/// or a unit enum variant, which allows frontends to map errors to localized messages.
+///
+/// If a method name was set with [`set_panic_method_name`], it is included in the payload under
+/// the `"method"` key instead of prefixing the message.
+///
+/// # Examples
+///
+/// ```should_panic
+/// use near_sdk::env;
+///
+/// env::panic_with_code(&404, "not found");
+/// // Panics with `{"code":404,"msg":"not found"}`
+/// ```
+pub fn panic_with_code<C: serde::Serialize + ?Sized>(code: &C, message: &str) -> ! {
+    #[derive(serde::Serialize)]
+    struct CodedPanic<'a, C: ?Sized> {
+        code: &'a C,
+        msg: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        method: Option<&'static str>,
+    }
+
+    let payload = CodedPanic { code, msg: message, method: PANIC_METHOD_NAME.with(|n| n.get()) };
+    match serde_json::to_string(&payload) {
+        Ok(payload) => panic_utf8(&payload),
+        Err(_) => panic_str("Failed to serialize the panic code."),
+    }
+}
+
+/// Sets the name of the method currently executing, which will then prefix all messages passed
+/// to [`panic_str`] and be included in the payload of [`panic_with_code`].
+///
+/// This is called by the code generated for methods of `#[near_bindgen(panic_with_method_name)]`
+/// impl blocks, so it rarely needs to be called manually.
+pub fn set_panic_method_name(method_name: &'static str) {
+    PANIC_METHOD_NAME.with(|name| name.set(Some(method_name)));
+}
+
+fn panic_utf8(message: &str) -> ! {
     unsafe { sys::panic_utf8(message.len() as _, message.as_ptr() as _) }
 }
 
@@ -736,6 +1031,24 @@ pub fn storage_write(key: &[u8], value: &[u8]) -> bool {
         _ => abort(),
     }
 }
+/// Writes the data in `register` as the value stored under the given key, without reading it
+/// into the contract's memory.
+/// If another key-value existed in the storage with the same key it returns `true`, otherwise `false`.
+pub fn storage_write_from_register(key: &[u8], register: Register) -> bool {
+    match unsafe {
+        sys::storage_write(
+            key.len() as _,
+            key.as_ptr() as _,
+            u64::MAX,
+            register.id(),
+            EVICTED_REGISTER,
+        )
+    } {
+        0 => false,
+        1 => true,
+        _ => abort(),
+    }
+}
 /// Reads the value stored under the given key.
 ///
 /// # Examples
@@ -754,6 +1067,15 @@ pub fn storage_read(key: &[u8]) -> Option<Vec<u8>> {
         _ => abort(),
     }
 }
+/// Writes the value stored under the given key into `register`. Returns `false` if there is no
+/// value stored under the key, in which case the register is left untouched.
+pub fn storage_read_into_register(key: &[u8], register: Register) -> bool {
+    match unsafe { sys::storage_read(key.len() as _, key.as_ptr() as _, register.id()) } {
+        0 => false,
+        1 => true,
+        _ => abort(),
+    }
+}
 /// Removes the value stored under the given key.
 /// If key-value existed returns `true`, otherwise `false`.
 pub fn storage_remove(key: &[u8]) -> bool {
@@ -776,6 +1098,92 @@ pub fn storage_has_key(key: &[u8]) -> bool {
     }
 }
 
+/// Iterates over all key-value pairs in storage whose key starts with `prefix`, in
+/// lexicographic order of the keys.
+///
+/// Note that the storage iteration host functions are deprecated, and disabled on current
+/// nearcore protocol versions, where calling this function fails the call. They are available in
+/// the mocked blockchain of unit tests.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::env::{storage_iter_prefix, storage_write};
+///
+/// storage_write(b"a/2", b"two");
+/// storage_write(b"a/1", b"one");
+/// storage_write(b"b/1", b"other");
+///
+/// let entries: Vec<_> = storage_iter_prefix(b"a/").collect();
+/// assert_eq!(
+///     entries,
+///     [(b"a/1".to_vec(), b"one".to_vec()), (b"a/2".to_vec(), b"two".to_vec())]
+/// );
+/// ```
+pub fn storage_iter_prefix(prefix: &[u8]) -> StorageIter {
+    let iterator_id = unsafe { sys::storage_iter_prefix(prefix.len() as _, prefix.as_ptr() as _) };
+    StorageIter { iterator_id }
+}
+/// Iterates over all key-value pairs in storage whose key is in the range `[start, end)`, in
+/// lexicographic order of the keys.
+///
+/// Note that the storage iteration host functions are deprecated, and disabled on current
+/// nearcore protocol versions, where calling this function fails the call. They are available in
+/// the mocked blockchain of unit tests.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::env::{storage_iter_range, storage_write};
+///
+/// storage_write(b"a", b"1");
+/// storage_write(b"b", b"2");
+/// storage_write(b"c", b"3");
+///
+/// let keys: Vec<_> = storage_iter_range(b"a", b"c").map(|(key, _)| key).collect();
+/// assert_eq!(keys, [b"a".to_vec(), b"b".to_vec()]);
+/// ```
+pub fn storage_iter_range(start: &[u8], end: &[u8]) -> StorageIter {
+    let iterator_id = unsafe {
+        sys::storage_iter_range(
+            start.len() as _,
+            start.as_ptr() as _,
+            end.len() as _,
+            end.as_ptr() as _,
+        )
+    };
+    StorageIter { iterator_id }
+}
+
+/// Iterator over key-value pairs in storage, created through [`storage_iter_prefix`] or
+/// [`storage_iter_range`].
+#[derive(Debug)]
+pub struct StorageIter {
+    iterator_id: u64,
+}
+
+impl Iterator for StorageIter {
+    type Item = (Vec<u8>, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match unsafe {
+            sys::storage_iter_next(
+                self.iterator_id,
+                ATOMIC_OP_REGISTER,
+                STORAGE_ITER_VALUE_REGISTER,
+            )
+        } {
+            0 => None,
+            1 => {
+                let key = expect_register(read_register(ATOMIC_OP_REGISTER));
+                let value = expect_register(read_register(STORAGE_ITER_VALUE_REGISTER));
+                Some((key, value))
+            }
+            _ => abort(),
+        }
+    }
+}
+
 // ############################################
 // # Saving and loading of the contract state #
 // ############################################
@@ -1021,4 +1429,239 @@ mod tests {
             .build());
         assert_eq!(super::signer_account_pk(), key);
     }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn storage_iter() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().build());
+
+        for key in [b"k3", b"k1", b"k2", b"j1"] {
+            super::storage_write(key, key);
+        }
+
+        let mut iter = super::storage_iter_prefix(b"k");
+        assert_eq!(iter.next(), Some((b"k1".to_vec(), b"k1".to_vec())));
+        // Keys removed after the iterator was created are skipped.
+        super::storage_remove(b"k2");
+        assert_eq!(iter.next(), Some((b"k3".to_vec(), b"k3".to_vec())));
+        assert_eq!(iter.next(), None);
+
+        let keys: Vec<_> = super::storage_iter_range(b"j", b"k3").map(|(k, _)| k).collect();
+        assert_eq!(keys, [b"j1".to_vec(), b"k1".to_vec()]);
+        assert_eq!(super::storage_iter_range(b"k3", b"k1").next(), None);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    #[should_panic(expected = "Iterator index 3 does not exist")]
+    fn storage_iter_invalid_index() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().build());
+        super::StorageIter { iterator_id: 3 }.next();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn alt_bn128_smoke_test() {
+        use crate::types::alt_bn128::{G1Point, G2Point, Scalar};
+
+        crate::test_utils::test_env::setup_free();
+
+        let mut one = [0; 32];
+        one[0] = 1;
+        let mut two = [0; 32];
+        two[0] = 2;
+        let g1 = G1Point { x: one, y: two };
+        let neg_g1 = G1Point {
+            x: one,
+            y: hex::decode("45fd7cd8168c203c8dca7168916a81975d588181b64550b829a031e1724e6430")
+                .unwrap()
+                .try_into()
+                .unwrap(),
+        };
+        let double_g1 = G1Point::from_bytes(
+            &hex::decode(
+                "d3cf876dc108c2d3a81c8716a91678d9851518685b04859b021a132ee7440603\
+                 c4a2185a7abf3effc78f53e349a4a6680a9caeb2965f84e7927c0a0e8c73ed15",
+            )
+            .unwrap()
+            .try_into()
+            .unwrap(),
+        );
+        let g2 = G2Point::from_bytes(
+            &hex::decode(
+                "edf692d95cbdde46ddda5ef7d422436779445c5e66006a42761e1f12efde0018\
+                 c212f3aeb785e49712e7a9353349aaf1255dfb31b7bf60723a480d9293938e19\
+                 aa7dfa6601cce64c7bd3430c69e7d1e38f40cb8d8071ab4aeb6d8cdba55ec812\
+                 5b9722d1dcdaac55f38eb37033314bbc95330c69ad999eec75f05f58d0890609",
+            )
+            .unwrap()
+            .try_into()
+            .unwrap(),
+        );
+
+        assert_eq!(super::alt_bn128_g1_multiexp(&[(g1, Scalar::from(2u64))]), double_g1);
+        assert_eq!(super::alt_bn128_g1_multiexp(&[]), G1Point::ZERO);
+        assert_eq!(super::alt_bn128_g1_sum(&[(false, g1), (false, g1)]), double_g1);
+        assert_eq!(super::alt_bn128_g1_sum(&[(false, double_g1), (true, g1)]), g1);
+        assert_eq!(super::alt_bn128_g1_sum(&[(false, g1), (false, neg_g1)]), G1Point::ZERO);
+
+        assert!(super::alt_bn128_pairing_check(&[]));
+        assert!(super::alt_bn128_pairing_check(&[(g1, g2), (neg_g1, g2)]));
+        assert!(!super::alt_bn128_pairing_check(&[(g1, g2)]));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn ed25519_verify() {
+        use near_crypto::{KeyType, SecretKey, Signature};
+
+        crate::test_utils::test_env::setup_free();
+
+        let secret_key = SecretKey::from_seed(KeyType::ED25519, "test");
+        let public_key: PublicKey = secret_key.public_key().to_string().parse().unwrap();
+        let public_key: [u8; 32] = public_key.as_bytes()[1..].try_into().unwrap();
+        let signature = match secret_key.sign(b"message") {
+            Signature::ED25519(signature) => signature.to_bytes(),
+            _ => unreachable!(),
+        };
+
+        assert!(super::ed25519_verify(&signature, b"message", &public_key));
+        assert!(!super::ed25519_verify(&signature, b"other message", &public_key));
+        assert!(!super::ed25519_verify(&[0; 64], b"message", &public_key));
+        // Malformed signatures and keys are rejected without failing the call.
+        assert!(!super::ed25519_verify(&[0xff; 64], b"message", &public_key));
+        assert!(!super::ed25519_verify(&signature, b"message", &[0xff; 32]));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn ed25519_verify_gas() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().build());
+
+        let before = super::used_gas();
+        super::ed25519_verify(&[0; 64], b"message", &[0; 32]);
+        assert!(super::used_gas().0 - before.0 > 210_849_552_000 + 7 * 9_059_150);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    #[should_panic(expected = "ed25519_verify: invalid signature length")]
+    fn ed25519_verify_invalid_length() {
+        crate::test_utils::test_env::setup_free();
+
+        let message = b"message";
+        unsafe {
+            super::sys::ed25519_verify(
+                63,
+                [0u8; 63].as_ptr() as _,
+                message.len() as _,
+                message.as_ptr() as _,
+                32,
+                [0u8; 32].as_ptr() as _,
+            );
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn register_roundtrip() {
+        let mut context = crate::test_utils::VMContextBuilder::new().build();
+        context.input = b"input".to_vec();
+        crate::testing_env!(context);
+
+        let register = Register::new(0);
+        assert_eq!(register.len(), None);
+        assert_eq!(register.read(), None);
+
+        register.write(b"data");
+        assert_eq!(register.len(), Some(4));
+        let mut buf = [0u8; 8];
+        assert_eq!(register.read_into(&mut buf), Some(4));
+        assert_eq!(&buf[..4], b"data");
+        assert_eq!(register.read_array::<4>(), Some(*b"data"));
+
+        assert!(input_into_register(register));
+        assert_eq!(register.read().unwrap(), b"input");
+
+        // Chain the register into storage without reading it.
+        assert!(!storage_write_from_register(b"key", register));
+        assert_eq!(storage_read(b"key").unwrap(), b"input");
+        assert!(!storage_read_into_register(b"missing", register));
+        assert!(storage_read_into_register(b"key", Register::new(1)));
+        assert_eq!(Register::new(1).read().unwrap(), b"input");
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    #[should_panic(expected = "Register data does not fit in the buffer.")]
+    fn register_read_into_small_buffer() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().build());
+
+        let register = Register::new(0);
+        register.write(b"data");
+        register.read_into(&mut [0u8; 3]);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn remaining_gas() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new()
+            .prepaid_gas(Gas::ONE_TERA * 10)
+            .build());
+
+        storage_write(b"key", b"value");
+        let used_gas = super::used_gas();
+        let remaining_gas = super::remaining_gas();
+        assert!(used_gas > Gas(0));
+        // Reading the gas counters burns gas as well.
+        assert!(used_gas + remaining_gas < Gas::ONE_TERA * 10);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    #[should_panic(expected = "method_name: custom message")]
+    fn panic_str_with_method_name() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().build());
+
+        super::set_panic_method_name("method_name");
+        super::panic_str("custom message");
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn testing_env_resets_panic_method_name() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().build());
+        super::set_panic_method_name("method_name");
+
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().build());
+        let err = std::panic::catch_unwind(|| super::panic_str("custom message")).unwrap_err();
+        let message = err.downcast_ref::<String>().unwrap();
+        assert!(message.contains("custom message") && !message.contains("method_name"));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    #[should_panic(expected = r#"{\"code\":404,\"msg\":\"not found\"}"#)]
+    fn panic_with_code() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().build());
+
+        super::panic_with_code(&404, "not found");
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    #[should_panic(
+        expected = r#"{\"code\":\"NotOwner\",\"msg\":\"not the owner\",\"method\":\"set\"}"#
+    )]
+    fn require_with_code_and_method_name() {
+        #[derive(serde::Serialize)]
+        enum ErrorCode {
+            NotOwner,
+        }
+
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().build());
+
+        super::set_panic_method_name("set");
+        crate::require!(false, ErrorCode::NotOwner, "not the owner");
+    }
 }
@@ -1,7 +1,7 @@
 use near_primitives::types::TrieNodesCount;
 use near_primitives_core::hash::{hash, CryptoHash};
 use near_primitives_core::types::{AccountId, Balance};
-use near_vm_logic::{External, ValuePtr};
+use near_vm_logic::{External, HostError, VMLogicError, ValuePtr};
 use std::collections::HashMap;
 
 type Result<T> = ::core::result::Result<T, near_vm_logic::VMLogicError>;
@@ -13,6 +13,8 @@ pub(crate) struct SdkExternal {
     pub fake_trie: HashMap<Vec<u8>, Vec<u8>>,
     pub validators: HashMap<AccountId, Balance>,
     data_count: u64,
+    /// Keys captured by each storage iterator, in sorted order. Iterators are indexed by their id.
+    iterators: Vec<std::vec::IntoIter<Vec<u8>>>,
 }
 
 pub struct MockedValuePtr {
@@ -33,6 +35,34 @@ impl SdkExternal {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Creates an iterator over all keys that start with `prefix` and returns its id.
+    pub fn storage_iter_prefix(&mut self, prefix: &[u8]) -> u64 {
+        self.create_iterator(|key| key.starts_with(prefix))
+    }
+
+    /// Creates an iterator over all keys in the range `[start, end)` and returns its id.
+    pub fn storage_iter_range(&mut self, start: &[u8], end: &[u8]) -> u64 {
+        self.create_iterator(|key| key >= start && key < end)
+    }
+
+    /// Advances the iterator with the given id. Keys removed from the trie after the iterator
+    /// was created are skipped.
+    pub fn storage_iter_next(&mut self, iterator_id: u64) -> Result<Option<(Vec<u8>, Vec<u8>)>> {
+        let iter = self.iterators.get_mut(iterator_id as usize).ok_or(VMLogicError::HostError(
+            HostError::InvalidIteratorIndex { iterator_index: iterator_id },
+        ))?;
+        let fake_trie = &self.fake_trie;
+        Ok(iter.find_map(|key| fake_trie.get(&key).map(|value| (key, value.clone()))))
+    }
+
+    fn create_iterator(&mut self, filter: impl Fn(&[u8]) -> bool) -> u64 {
+        let mut keys: Vec<Vec<u8>> =
+            self.fake_trie.keys().filter(|key| filter(key)).cloned().collect();
+        keys.sort();
+        self.iterators.push(keys.into_iter());
+        (self.iterators.len() - 1) as u64
+    }
 }
 
 impl External for SdkExternal {
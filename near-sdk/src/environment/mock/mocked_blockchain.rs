@@ -4,11 +4,11 @@ use crate::test_utils::VMContextBuilder;
 use crate::types::{Balance, PromiseResult};
 use crate::{Gas, RuntimeFeesConfig};
 use crate::{PublicKey, VMContext};
-use near_crypto::PublicKey as VmPublicKey;
+use near_crypto::{KeyType, PublicKey as VmPublicKey};
 use near_primitives::transaction::Action as PrimitivesAction;
 use near_vm_logic::mocks::mock_memory::MockedMemory;
 use near_vm_logic::types::PromiseResult as VmPromiseResult;
-use near_vm_logic::{External, MemoryLike, VMConfig, VMLogic};
+use near_vm_logic::{External, MemoryLike, VMConfig, VMLogic, VMLogicError};
 use std::cell::RefCell;
 use std::collections::HashMap;
 
@@ -19,6 +19,7 @@ use std::collections::HashMap;
 /// message.
 pub struct MockedBlockchain {
     logic: RefCell<VMLogic<'static>>,
+    gas_meters: Vec<(String, Gas)>,
     // We keep ownership over logic fixture so that references in `VMLogic` are valid.
     #[allow(dead_code)]
     logic_fixture: LogicFixture,
@@ -81,7 +82,7 @@ impl MockedBlockchain {
         };
 
         let logic = RefCell::new(logic);
-        Self { logic, logic_fixture }
+        Self { logic, gas_meters: Vec::new(), logic_fixture }
     }
 
     pub fn take_storage(&mut self) -> HashMap<Vec<u8>, Vec<u8>> {
@@ -109,6 +110,153 @@ impl MockedBlockchain {
     pub fn logs(&self) -> Vec<String> {
         self.logic.borrow().logs().to_vec()
     }
+
+    /// Returns the name and used gas of each [`GasMeter`] dropped so far, in the order they were
+    /// dropped.
+    ///
+    /// [`GasMeter`]: crate::GasMeter
+    pub fn gas_meters(&self) -> Vec<(String, Gas)> {
+        self.gas_meters.clone()
+    }
+
+    pub(crate) fn record_gas_meter(&mut self, name: &str, used_gas: Gas) {
+        self.gas_meters.push((name.to_string(), used_gas));
+    }
+
+    fn memory_get_vec(&self, ptr: u64, len: u64) -> Vec<u8> {
+        let mut buf = vec![0; len as usize];
+        self.logic_fixture.memory.read_memory(ptr, &mut buf);
+        buf
+    }
+
+    // `VMLogic` does not implement `ed25519_verify`, so the signature is checked with `near_crypto`.
+    // Its `ext_costs` have no ed25519 parameters either, so the ones of nearcore are charged,
+    // unless the config is free.
+    fn ed25519_verify(
+        &mut self,
+        sig_len: u64,
+        sig_ptr: u64,
+        msg_len: u64,
+        msg_ptr: u64,
+        pub_key_len: u64,
+        pub_key_ptr: u64,
+    ) -> Result<u64, VMLogicError> {
+        const ED25519_VERIFY_BASE: u64 = 210_849_552_000;
+        const ED25519_VERIFY_BYTE: u64 = 9_059_150;
+
+        let costs = &self.logic_fixture.config.ext_costs;
+        if costs.base > 0 {
+            self.burn_emulated_gas(
+                costs.base
+                    + 3 * costs.read_memory_base
+                    + costs.read_memory_byte * (sig_len + msg_len + pub_key_len)
+                    + ED25519_VERIFY_BASE
+                    + ED25519_VERIFY_BYTE * msg_len,
+            )?;
+        }
+
+        // Inputs of the wrong length fail the call, malformed ones are not valid signatures.
+        if sig_len != 64 {
+            panic!("ed25519_verify: invalid signature length");
+        }
+        if pub_key_len != 32 {
+            panic!("ed25519_verify: invalid public key length");
+        }
+        let signature = self.memory_get_vec(sig_ptr, sig_len);
+        let message = self.memory_get_vec(msg_ptr, msg_len);
+        let public_key = self.memory_get_vec(pub_key_ptr, pub_key_len);
+        let signature = match near_crypto::Signature::from_parts(KeyType::ED25519, &signature) {
+            Ok(signature) => signature,
+            Err(_) => return Ok(0),
+        };
+        let public_key = match near_crypto::ED25519PublicKey::try_from(public_key.as_slice()) {
+            Ok(public_key) => public_key,
+            Err(_) => return Ok(0),
+        };
+        Ok(signature.verify(&message, &VmPublicKey::ED25519(public_key)) as u64)
+    }
+
+    // Storage iteration is deprecated in `VMLogic`, so it is emulated here over the fake trie.
+    // The costs of the emulated functions are charged from the `ext_costs` of the config, which
+    // prices them at zero by default.
+    fn storage_iter_prefix(
+        &mut self,
+        prefix_len: u64,
+        prefix_ptr: u64,
+    ) -> Result<u64, VMLogicError> {
+        let prefix = self.memory_get_vec(prefix_ptr, prefix_len);
+        let costs = &self.logic_fixture.config.ext_costs;
+        self.burn_emulated_gas(
+            costs.base
+                + costs.storage_iter_create_prefix_base
+                + costs.storage_iter_create_prefix_byte * prefix_len,
+        )?;
+        Ok(self.logic_fixture.ext.storage_iter_prefix(&prefix))
+    }
+
+    fn storage_iter_range(
+        &mut self,
+        start_len: u64,
+        start_ptr: u64,
+        end_len: u64,
+        end_ptr: u64,
+    ) -> Result<u64, VMLogicError> {
+        let start = self.memory_get_vec(start_ptr, start_len);
+        let end = self.memory_get_vec(end_ptr, end_len);
+        let costs = &self.logic_fixture.config.ext_costs;
+        self.burn_emulated_gas(
+            costs.base
+                + costs.storage_iter_create_range_base
+                + costs.storage_iter_create_from_byte * start_len
+                + costs.storage_iter_create_to_byte * end_len,
+        )?;
+        Ok(self.logic_fixture.ext.storage_iter_range(&start, &end))
+    }
+
+    fn storage_iter_next(
+        &mut self,
+        iterator_id: u64,
+        key_register_id: u64,
+        value_register_id: u64,
+    ) -> Result<u64, VMLogicError> {
+        let costs = &self.logic_fixture.config.ext_costs;
+        self.burn_emulated_gas(costs.base + costs.storage_iter_next_base)?;
+        match self.logic_fixture.ext.storage_iter_next(iterator_id)? {
+            Some((key, value)) => {
+                let costs = &self.logic_fixture.config.ext_costs;
+                self.burn_emulated_gas(
+                    costs.storage_iter_next_key_byte * key.len() as u64
+                        + costs.storage_iter_next_value_byte * value.len() as u64,
+                )?;
+                let mut logic = self.logic.borrow_mut();
+                logic.wrapped_internal_write_register(key_register_id, &key)?;
+                logic.wrapped_internal_write_register(value_register_id, &value)?;
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Burns the gas of an emulated host function, failing like `VMLogic` does when the gas
+    /// limit is exceeded.
+    fn burn_emulated_gas(&mut self, gas: u64) -> Result<(), VMLogicError> {
+        let mut logic = self.logic.borrow_mut();
+        // SAFETY: `gas_counter_pointer` is the address of the fast gas counter field of
+        // `logic`, valid and aligned for as long as `logic` is alive and not moved. `logic` is
+        // mutably borrowed from the `RefCell` until the end of the function, so the counter is not
+        // moved, and no other reference to it exists while it is written through the pointer: the
+        // reference created from it is dropped at the end of the block, before `logic` is used
+        // again.
+        let exceeded = unsafe {
+            let counter = &mut *logic.gas_counter_pointer();
+            counter.burnt_gas = counter.burnt_gas.saturating_add(gas);
+            counter.burnt_gas > counter.gas_limit
+        };
+        if exceeded {
+            return Err(logic.process_gas_limit().into());
+        }
+        Ok(())
+    }
 }
 
 fn sdk_context_to_vm_context(context: VMContext) -> near_vm_logic::VMContext {
@@ -192,6 +340,16 @@ mod mock_chain {
         crate::mock::with_mocked_blockchain(|b| f(&mut b.logic.borrow_mut()).unwrap())
     }
 
+    /// Aborts the call with the message of the host error, like the runtime does, for the host
+    /// functions which are emulated outside of `VMLogic`.
+    fn expect_host<R>(result: Result<R, VMLogicError>) -> R {
+        // `VMLogicError` displays as its `Debug` form, so the message of a host error is unwrapped.
+        result.unwrap_or_else(|err| match err {
+            VMLogicError::HostError(err) => panic!("{}", err),
+            err => panic!("{}", err),
+        })
+    }
+
     #[no_mangle]
     extern "C" fn read_register(register_id: u64, ptr: u64) {
         with_mock_interface(|b| b.read_register(register_id, ptr))
@@ -201,6 +359,10 @@ mod mock_chain {
         with_mock_interface(|b| b.register_len(register_id))
     }
     #[no_mangle]
+    extern "C" fn write_register(register_id: u64, data_len: u64, data_ptr: u64) {
+        with_mock_interface(|b| b.write_register(register_id, data_len, data_ptr))
+    }
+    #[no_mangle]
     extern "C" fn current_account_id(register_id: u64) {
         with_mock_interface(|b| b.current_account_id(register_id))
     }
@@ -291,6 +453,26 @@ mod mock_chain {
         })
     }
     #[no_mangle]
+    extern "C" fn ed25519_verify(
+        sig_len: u64,
+        sig_ptr: u64,
+        msg_len: u64,
+        msg_ptr: u64,
+        pub_key_len: u64,
+        pub_key_ptr: u64,
+    ) -> u64 {
+        crate::mock::with_mocked_blockchain(|b| {
+            expect_host(b.ed25519_verify(
+                sig_len,
+                sig_ptr,
+                msg_len,
+                msg_ptr,
+                pub_key_len,
+                pub_key_ptr,
+            ))
+        })
+    }
+    #[no_mangle]
     extern "C" fn value_return(value_len: u64, value_ptr: u64) {
         with_mock_interface(|b| b.value_return(value_len, value_ptr))
     }
@@ -558,6 +740,33 @@ mod mock_chain {
         with_mock_interface(|b| b.storage_has_key(key_len, key_ptr))
     }
     #[no_mangle]
+    extern "C" fn storage_iter_prefix(prefix_len: u64, prefix_ptr: u64) -> u64 {
+        crate::mock::with_mocked_blockchain(|b| {
+            expect_host(b.storage_iter_prefix(prefix_len, prefix_ptr))
+        })
+    }
+    #[no_mangle]
+    extern "C" fn storage_iter_range(
+        start_len: u64,
+        start_ptr: u64,
+        end_len: u64,
+        end_ptr: u64,
+    ) -> u64 {
+        crate::mock::with_mocked_blockchain(|b| {
+            expect_host(b.storage_iter_range(start_len, start_ptr, end_len, end_ptr))
+        })
+    }
+    #[no_mangle]
+    extern "C" fn storage_iter_next(
+        iterator_id: u64,
+        key_register_id: u64,
+        value_register_id: u64,
+    ) -> u64 {
+        crate::mock::with_mocked_blockchain(|b| {
+            expect_host(b.storage_iter_next(iterator_id, key_register_id, value_register_id))
+        })
+    }
+    #[no_mangle]
     extern "C" fn validator_stake(account_id_len: u64, account_id_ptr: u64, stake_ptr: u64) {
         with_mock_interface(|b| b.validator_stake(account_id_len, account_id_ptr, stake_ptr))
     }
@@ -19,7 +19,7 @@ pub use environment::env;
 pub use near_sys as sys;
 
 mod promise;
-pub use promise::{Promise, PromiseOrValue};
+pub use promise::{Promise, PromiseActionView, PromiseJoin, PromiseJoinResults, PromiseOrValue};
 
 // Private types just used within macro generation, not stable to be used.
 #[doc(hidden)]
@@ -28,6 +28,8 @@ pub mod __private;
 
 pub mod json_types;
 
+pub mod access;
+
 mod types;
 pub use crate::types::*;
 
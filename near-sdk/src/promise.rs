@@ -1,11 +1,14 @@
 use borsh::BorshSchema;
+use serde::de::DeserializeOwned;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::io::{Error, Write};
+use std::marker::PhantomData;
 use std::rc::Rc;
 
-use crate::{AccountId, Balance, Gas, GasWeight, PromiseIndex, PublicKey};
+use crate::{AccountId, Balance, Gas, GasWeight, PromiseError, PromiseIndex, PublicKey};
 
+#[derive(Clone, PartialEq, Eq)]
 enum PromiseAction {
     CreateAccount,
     DeployContract {
@@ -51,7 +54,7 @@ enum PromiseAction {
 }
 
 impl PromiseAction {
-    pub fn add(&self, promise_index: PromiseIndex) {
+    fn add(&self, promise_index: PromiseIndex) {
         use PromiseAction::*;
         match self {
             CreateAccount => crate::env::promise_batch_action_create_account(promise_index),
@@ -110,6 +113,191 @@ impl PromiseAction {
     }
 }
 
+/// A read-only view of an action queued on a [`Promise`], see [`Promise::actions`]. The accessors
+/// return `None` for the actions which don't have the requested property.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PromiseActionView(PromiseAction);
+
+impl PromiseActionView {
+    /// Name of the kind of the action, like `"FunctionCall"` or `"Transfer"`, as in the actions of
+    /// a receipt. Both kinds of access keys are added with `"AddKey"`.
+    pub fn name(&self) -> &'static str {
+        use PromiseAction::*;
+        match &self.0 {
+            CreateAccount => "CreateAccount",
+            DeployContract { .. } => "DeployContract",
+            FunctionCall { .. } | FunctionCallWeight { .. } => "FunctionCall",
+            Transfer { .. } => "Transfer",
+            Stake { .. } => "Stake",
+            AddFullAccessKey { .. } | AddAccessKey { .. } => "AddKey",
+            DeleteKey { .. } => "DeleteKey",
+            DeleteAccount { .. } => "DeleteAccount",
+        }
+    }
+
+    /// Code of the contract being deployed.
+    pub fn code(&self) -> Option<&[u8]> {
+        match &self.0 {
+            PromiseAction::DeployContract { code } => Some(code),
+            _ => None,
+        }
+    }
+
+    /// Name of the method being called.
+    pub fn function_name(&self) -> Option<&str> {
+        match &self.0 {
+            PromiseAction::FunctionCall { function_name, .. }
+            | PromiseAction::FunctionCallWeight { function_name, .. } => Some(function_name),
+            _ => None,
+        }
+    }
+
+    /// Arguments of the method being called.
+    pub fn arguments(&self) -> Option<&[u8]> {
+        match &self.0 {
+            PromiseAction::FunctionCall { arguments, .. }
+            | PromiseAction::FunctionCallWeight { arguments, .. } => Some(arguments),
+            _ => None,
+        }
+    }
+
+    /// Tokens attached to a function call or transferred.
+    pub fn deposit(&self) -> Option<Balance> {
+        match &self.0 {
+            PromiseAction::FunctionCall { amount, .. }
+            | PromiseAction::FunctionCallWeight { amount, .. }
+            | PromiseAction::Transfer { amount } => Some(*amount),
+            _ => None,
+        }
+    }
+
+    /// Static gas attached to a function call.
+    pub fn gas(&self) -> Option<Gas> {
+        match &self.0 {
+            PromiseAction::FunctionCall { gas, .. }
+            | PromiseAction::FunctionCallWeight { gas, .. } => Some(*gas),
+            _ => None,
+        }
+    }
+
+    /// Weight of the unused gas distributed to a function call, see
+    /// [`Promise::function_call_weight`].
+    pub fn gas_weight(&self) -> Option<GasWeight> {
+        match &self.0 {
+            PromiseAction::FunctionCallWeight { weight, .. } => Some(*weight),
+            _ => None,
+        }
+    }
+
+    /// Tokens being staked.
+    pub fn stake(&self) -> Option<Balance> {
+        match &self.0 {
+            PromiseAction::Stake { amount, .. } => Some(*amount),
+            _ => None,
+        }
+    }
+
+    /// Key being staked with, added or deleted.
+    pub fn public_key(&self) -> Option<&PublicKey> {
+        match &self.0 {
+            PromiseAction::Stake { public_key, .. }
+            | PromiseAction::AddFullAccessKey { public_key, .. }
+            | PromiseAction::AddAccessKey { public_key, .. }
+            | PromiseAction::DeleteKey { public_key } => Some(public_key),
+            _ => None,
+        }
+    }
+
+    /// Nonce of the access key being added.
+    pub fn nonce(&self) -> Option<u64> {
+        match &self.0 {
+            PromiseAction::AddFullAccessKey { nonce, .. }
+            | PromiseAction::AddAccessKey { nonce, .. } => Some(*nonce),
+            _ => None,
+        }
+    }
+
+    /// Allowance of the function call access key being added.
+    pub fn allowance(&self) -> Option<Balance> {
+        match &self.0 {
+            PromiseAction::AddAccessKey { allowance, .. } => Some(*allowance),
+            _ => None,
+        }
+    }
+
+    /// Account the function call access key being added can call.
+    pub fn receiver_id(&self) -> Option<&AccountId> {
+        match &self.0 {
+            PromiseAction::AddAccessKey { receiver_id, .. } => Some(receiver_id),
+            _ => None,
+        }
+    }
+
+    /// Comma-separated methods the function call access key being added can call.
+    pub fn function_names(&self) -> Option<&str> {
+        match &self.0 {
+            PromiseAction::AddAccessKey { function_names, .. } => Some(function_names),
+            _ => None,
+        }
+    }
+
+    /// Account receiving the remaining balance of the account being deleted.
+    pub fn beneficiary_id(&self) -> Option<&AccountId> {
+        match &self.0 {
+            PromiseAction::DeleteAccount { beneficiary_id } => Some(beneficiary_id),
+            _ => None,
+        }
+    }
+}
+
+/// Renders the properties the action has, with the length of the deployed code instead of the
+/// code itself.
+impl std::fmt::Debug for PromiseActionView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut s = f.debug_struct(self.name());
+        if let Some(code) = self.code() {
+            s.field("code_len", &code.len());
+        }
+        if let Some(function_name) = self.function_name() {
+            s.field("function_name", &function_name);
+        }
+        if let Some(arguments) = self.arguments() {
+            s.field("arguments", &arguments);
+        }
+        if let Some(deposit) = self.deposit() {
+            s.field("deposit", &deposit);
+        }
+        if let Some(gas) = self.gas() {
+            s.field("gas", &gas);
+        }
+        if let Some(gas_weight) = self.gas_weight() {
+            s.field("gas_weight", &gas_weight);
+        }
+        if let Some(stake) = self.stake() {
+            s.field("stake", &stake);
+        }
+        if let Some(public_key) = self.public_key() {
+            s.field("public_key", public_key);
+        }
+        if let Some(allowance) = self.allowance() {
+            s.field("allowance", &allowance);
+        }
+        if let Some(receiver_id) = self.receiver_id() {
+            s.field("receiver_id", receiver_id);
+        }
+        if let Some(function_names) = self.function_names() {
+            s.field("function_names", &function_names);
+        }
+        if let Some(nonce) = self.nonce() {
+            s.field("nonce", &nonce);
+        }
+        if let Some(beneficiary_id) = self.beneficiary_id() {
+            s.field("beneficiary_id", beneficiary_id);
+        }
+        s.finish()
+    }
+}
+
 struct PromiseSingle {
     pub account_id: AccountId,
     pub actions: RefCell<Vec<PromiseAction>>,
@@ -374,6 +562,48 @@ impl Promise {
         }
     }
 
+    /// Joins two promises whose results are of types `A` and `B`, so that a callback scheduled
+    /// with [`PromiseJoin::then`] can read both results with their types through
+    /// [`PromiseJoin::results`] or a `#[callback_result]` tuple parameter.
+    ///
+    /// ```no_run
+    /// # use near_sdk::{Gas, Promise, PromiseError};
+    /// # use borsh::{BorshDeserialize, BorshSerialize};
+    /// # use near_sdk::near_bindgen;
+    /// # #[near_bindgen]
+    /// # #[derive(Default, BorshDeserialize, BorshSerialize)]
+    /// # struct Contract {}
+    /// #[near_bindgen]
+    /// impl Contract {
+    ///     pub fn query(&self) -> Promise {
+    ///         let balance = Promise::new("token.near".parse().unwrap())
+    ///             .function_call("balance".into(), vec![], 0, Gas(5_000_000_000_000));
+    ///         let name = Promise::new("token.near".parse().unwrap())
+    ///             .function_call("name".into(), vec![], 0, Gas(5_000_000_000_000));
+    ///         Promise::join2::<u64, String>(balance, name).then(
+    ///             Promise::new(near_sdk::env::current_account_id())
+    ///                 .function_call("on_query".into(), vec![], 0, Gas(5_000_000_000_000)),
+    ///         )
+    ///     }
+    ///
+    ///     #[private]
+    ///     pub fn on_query(
+    ///         &self,
+    ///         #[callback_result] results: (Result<u64, PromiseError>, Result<String, PromiseError>),
+    ///     ) {
+    ///         let (balance, name) = results;
+    ///     }
+    /// }
+    /// ```
+    pub fn join2<A, B>(a: Promise, b: Promise) -> PromiseJoin<(A, B)> {
+        PromiseJoin::new(a.and(b))
+    }
+
+    /// Joins three promises whose results are of types `A`, `B` and `C`, see [`Promise::join2`].
+    pub fn join3<A, B, C>(a: Promise, b: Promise, c: Promise) -> PromiseJoin<(A, B, C)> {
+        PromiseJoin::new(a.and(b).and(c))
+    }
+
     /// Schedules execution of another promise right after the current promise finish executing.
     ///
     /// In the following code `bob_near` and `dave_near` will be created concurrently. `carol_near`
@@ -403,6 +633,120 @@ impl Promise {
         other
     }
 
+    /// Schedules `callback` right after the current promise, reserving `callback_gas` for each of
+    /// its function calls. The function calls of the callback are given at least `callback_gas` of
+    /// static gas and no unused gas weight, so that the unused gas of the current method goes to
+    /// the function calls of the current promise, which use a weight of 1 by default.
+    ///
+    /// This avoids subtracting the gas of the callback from the prepaid gas by hand:
+    ///
+    /// ```no_run
+    /// # use near_sdk::{env, ext_contract, near_bindgen, AccountId, Gas, Promise};
+    /// # use borsh::{BorshDeserialize, BorshSerialize};
+    /// #[ext_contract(ext_receiver)]
+    /// pub trait Receiver {
+    ///     fn on_transfer(&mut self, amount: u64) -> bool;
+    /// }
+    ///
+    /// const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
+    ///
+    /// #[near_bindgen]
+    /// #[derive(Default, BorshDeserialize, BorshSerialize)]
+    /// pub struct Contract {}
+    ///
+    /// #[near_bindgen]
+    /// impl Contract {
+    ///     pub fn transfer_call(&mut self, receiver_id: AccountId, amount: u64) -> Promise {
+    ///         ext_receiver::ext(receiver_id).on_transfer(amount).then_callback(
+    ///             GAS_FOR_RESOLVE_TRANSFER,
+    ///             Self::ext(env::current_account_id()).resolve_transfer(amount),
+    ///         )
+    ///     }
+    ///
+    ///     #[private]
+    ///     pub fn resolve_transfer(&mut self, amount: u64) {}
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `callback` is a joint promise, created with [`Promise::and`], or if it has no
+    /// function call.
+    pub fn then_callback(self, callback_gas: Gas, callback: Promise) -> Promise {
+        match &callback.subtype {
+            PromiseSubtype::Single(x) => {
+                let mut actions = x.actions.borrow_mut();
+                let mut has_function_call = false;
+                for action in actions.iter_mut() {
+                    match action {
+                        PromiseAction::FunctionCall { gas, .. } => {
+                            *gas = std::cmp::max(*gas, callback_gas);
+                        }
+                        PromiseAction::FunctionCallWeight { gas, weight, .. } => {
+                            *gas = std::cmp::max(*gas, callback_gas);
+                            *weight = GasWeight(0);
+                        }
+                        _ => continue,
+                    }
+                    has_function_call = true;
+                }
+                if !has_function_call {
+                    crate::env::panic_str("Callback promise must contain a function call.");
+                }
+            }
+            PromiseSubtype::Joint(_) => crate::env::panic_str("Cannot callback joint promise."),
+        }
+        self.then(callback)
+    }
+
+    /// Returns the account the promise acts on, or `None` if this is a joint promise created with
+    /// [`Promise::and`].
+    pub fn receiver_id(&self) -> Option<&AccountId> {
+        match &self.subtype {
+            PromiseSubtype::Single(x) => Some(&x.account_id),
+            PromiseSubtype::Joint(_) => None,
+        }
+    }
+
+    /// Returns the actions queued on the promise, in the order they will be executed. Joint
+    /// promises have no actions of their own.
+    ///
+    /// ```
+    /// # use near_sdk::{Promise, test_utils::VMContextBuilder, testing_env};
+    /// # testing_env!(VMContextBuilder::new().build());
+    /// let promise = Promise::new("bob_near".parse().unwrap()).create_account().transfer(1000);
+    /// let actions = promise.actions();
+    /// assert_eq!(actions[0].name(), "CreateAccount");
+    /// assert_eq!(actions[1].name(), "Transfer");
+    /// assert_eq!(actions[1].deposit(), Some(1000));
+    /// ```
+    pub fn actions(&self) -> Vec<PromiseActionView> {
+        match &self.subtype {
+            PromiseSubtype::Single(x) => {
+                x.actions.borrow().iter().cloned().map(PromiseActionView).collect()
+            }
+            PromiseSubtype::Joint(_) => vec![],
+        }
+    }
+
+    /// Returns the two promises joined by [`Promise::and`], or `None` if this is not a joint
+    /// promise.
+    pub fn joined(&self) -> Option<(&Promise, &Promise)> {
+        match &self.subtype {
+            PromiseSubtype::Single(_) => None,
+            PromiseSubtype::Joint(x) => Some((&x.promise_a, &x.promise_b)),
+        }
+    }
+
+    /// Returns whether the promise has a promise it is scheduled after with [`Promise::then`].
+    /// The whole structure can be inspected through the [`Debug`](std::fmt::Debug) rendering.
+    pub fn is_scheduled_after(&self) -> bool {
+        match &self.subtype {
+            PromiseSubtype::Single(x) => x.after.borrow().is_some(),
+            PromiseSubtype::Joint(_) => false,
+        }
+    }
+
     /// A specialized, relatively low-level API method. Allows to mark the given promise as the one
     /// that should be considered as a return value.
     ///
@@ -448,6 +792,134 @@ impl Promise {
     }
 }
 
+/// Joint promise which keeps track of the result types `T` of the joined promises, created with
+/// [`Promise::join2`] or [`Promise::join3`].
+pub struct PromiseJoin<T> {
+    promise: Promise,
+    _results: PhantomData<T>,
+}
+
+impl<T> PromiseJoin<T> {
+    fn new(promise: Promise) -> Self {
+        Self { promise, _results: PhantomData }
+    }
+
+    /// Schedules execution of the callback promise after all the joined promises finish
+    /// executing. The callback can read the typed results with [`PromiseJoin::results`].
+    pub fn then(self, callback: Promise) -> Promise {
+        self.promise.then(callback)
+    }
+
+    /// Returns the underlying untyped joint promise.
+    pub fn into_promise(self) -> Promise {
+        self.promise
+    }
+}
+
+impl<T: PromiseJoinResults> PromiseJoin<T> {
+    /// Reads the results of the joined promises when called from the callback, deserializing each
+    /// successful result from JSON.
+    ///
+    /// Panics if the number of promise results doesn't match the number of joined promises.
+    pub fn results() -> T::Output {
+        T::read()
+    }
+}
+
+impl<T> From<PromiseJoin<T>> for Promise {
+    fn from(join: PromiseJoin<T>) -> Self {
+        join.promise
+    }
+}
+
+/// Tuple of result types of joined promises, see [`PromiseJoin::results`].
+pub trait PromiseJoinResults {
+    /// Tuple with a `Result<_, PromiseError>` for each of the joined promises.
+    type Output;
+
+    /// Reads and deserializes the results of the joined promises.
+    fn read() -> Self::Output;
+}
+
+macro_rules! impl_promise_join_results {
+    ($count:expr; $($ty:ident: $idx:expr),+) => {
+        impl<$($ty: DeserializeOwned),+> PromiseJoinResults for ($($ty,)+) {
+            type Output = ($(Result<$ty, PromiseError>,)+);
+
+            fn read() -> Self::Output {
+                crate::require!(
+                    crate::env::promise_results_count() == $count,
+                    concat!("Expected ", $count, " promise results")
+                );
+                ($(read_promise_result::<$ty>($idx),)+)
+            }
+        }
+    };
+}
+
+impl_promise_join_results!(2; A: 0, B: 1);
+impl_promise_join_results!(3; A: 0, B: 1, C: 2);
+
+fn read_promise_result<T: DeserializeOwned>(result_idx: u64) -> Result<T, PromiseError> {
+    let data = match crate::env::promise_result(result_idx) {
+        crate::PromiseResult::Successful(data) => data,
+        crate::PromiseResult::NotReady => return Err(PromiseError::NotReady),
+        crate::PromiseResult::Failed => return Err(PromiseError::Failed),
+    };
+    // Methods without a return value produce no data, which only deserializes as `()`, like
+    // `#[callback_result]` does.
+    let result =
+        if data.is_empty() { T::deserialize(EmptyResult) } else { serde_json::from_slice(&data) };
+    Ok(result
+        .unwrap_or_else(|_| crate::env::panic_str("Failed to deserialize callback using JSON")))
+}
+
+/// Deserializer of the empty result of a method without a return value, which only accepts `()`.
+struct EmptyResult;
+
+impl<'de> serde::Deserializer<'de> for EmptyResult {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: serde::de::Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(serde::de::Error::custom("EOF while parsing a value"))
+    }
+
+    fn deserialize_unit<V: serde::de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf option
+        unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl std::fmt::Debug for Promise {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.subtype {
+            PromiseSubtype::Single(x) => f
+                .debug_struct("Promise")
+                .field("receiver_id", &x.account_id)
+                .field("actions", &self.actions())
+                .field("after", &*x.after.borrow())
+                .field("should_return", &*self.should_return.borrow())
+                .finish(),
+            PromiseSubtype::Joint(x) => f
+                .debug_struct("PromiseJoint")
+                .field("promise_a", &x.promise_a)
+                .field("promise_b", &x.promise_b)
+                .field("should_return", &*self.should_return.borrow())
+                .finish(),
+        }
+    }
+}
+
 impl Drop for Promise {
     fn drop(&mut self) {
         self.construct_recursively();
@@ -553,3 +1025,161 @@ impl<T: schemars::JsonSchema> schemars::JsonSchema for PromiseOrValue<T> {
         T::json_schema(gen)
     }
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::test_utils::VMContextBuilder;
+    use crate::{testing_env, PromiseResult, RuntimeFeesConfig, VMConfig};
+
+    #[test]
+    fn join_results() {
+        testing_env!(
+            VMContextBuilder::new().build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![
+                PromiseResult::Successful(b"42".to_vec()),
+                PromiseResult::Failed,
+                PromiseResult::Successful(vec![]),
+            ],
+        );
+
+        let (a, b, c) = PromiseJoin::<(u64, String, ())>::results();
+        assert_eq!(a, Ok(42));
+        assert_eq!(b, Err(PromiseError::Failed));
+        assert_eq!(c, Ok(()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected 2 promise results")]
+    fn join_results_count_mismatch() {
+        testing_env!(
+            VMContextBuilder::new().build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(b"42".to_vec())],
+        );
+
+        let _ = PromiseJoin::<(u64, u64)>::results();
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to deserialize callback using JSON")]
+    fn join_results_empty_non_unit() {
+        testing_env!(
+            VMContextBuilder::new().build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(b"42".to_vec()), PromiseResult::Successful(vec![])],
+        );
+
+        let _ = PromiseJoin::<(u64, Option<u64>)>::results();
+    }
+
+    #[test]
+    fn join_schedules_callback() {
+        testing_env!(VMContextBuilder::new().build());
+
+        let a = Promise::new("a.near".parse().unwrap()).create_account();
+        let b = Promise::new("b.near".parse().unwrap()).create_account();
+        let callback = Promise::new("c.near".parse().unwrap()).create_account();
+        drop(Promise::join2::<(), ()>(a, b).then(callback));
+
+        let receipts = crate::test_utils::get_created_receipts();
+        assert_eq!(receipts.len(), 3);
+        assert_eq!(receipts[2].receiver_id, "c.near".parse().unwrap());
+    }
+
+    #[test]
+    fn then_callback_reserves_gas() {
+        use crate::mock::VmAction;
+
+        testing_env!(VMContextBuilder::new().build());
+
+        let call = Promise::new("a.near".parse().unwrap()).function_call_weight(
+            "call".to_string(),
+            vec![],
+            0,
+            Gas(0),
+            GasWeight(1),
+        );
+        let callback = Promise::new("b.near".parse().unwrap()).function_call_weight(
+            "callback".to_string(),
+            vec![],
+            0,
+            Gas(0),
+            GasWeight(1),
+        );
+        drop(call.then_callback(Gas(5_000_000_000_000), callback));
+
+        let receipts = crate::test_utils::get_created_receipts();
+        assert_eq!(receipts.len(), 2);
+        match &receipts[1].actions[..] {
+            [VmAction::FunctionCall { function_name, gas, .. }] => {
+                assert_eq!(function_name, "callback");
+                assert_eq!(*gas, Gas(5_000_000_000_000));
+            }
+            actions => panic!("unexpected actions {:?}", actions),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Callback promise must contain a function call.")]
+    fn then_callback_without_function_call() {
+        testing_env!(VMContextBuilder::new().build());
+
+        let call = Promise::new("a.near".parse().unwrap()).create_account();
+        let callback = Promise::new("b.near".parse().unwrap()).create_account();
+        drop(call.then_callback(Gas(5_000_000_000_000), callback));
+    }
+
+    #[test]
+    fn inspect_promise_structure() {
+        testing_env!(VMContextBuilder::new().build());
+
+        let a = Promise::new("a.near".parse().unwrap()).create_account();
+        let b = Promise::new("b.near".parse().unwrap()).transfer(10);
+        let callback = Promise::new("c.near".parse().unwrap()).function_call(
+            "callback".to_string(),
+            vec![],
+            0,
+            Gas(10),
+        );
+        let promise = a.and(b).then(callback);
+
+        assert_eq!(promise.receiver_id(), Some(&"c.near".parse().unwrap()));
+        let actions = promise.actions();
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].name(), "FunctionCall");
+        assert_eq!(actions[0].function_name(), Some("callback"));
+        assert_eq!(actions[0].gas(), Some(Gas(10)));
+        assert_eq!(actions[0].gas_weight(), None);
+        assert!(promise.is_scheduled_after());
+        assert!(promise.joined().is_none());
+        assert_eq!(
+            format!("{:?}", promise),
+            "Promise { receiver_id: AccountId(\"c.near\"), actions: [FunctionCall { \
+            function_name: \"callback\", arguments: [], deposit: 0, gas: Gas(10) }], after: \
+            Some(PromiseJoint { promise_a: Promise { receiver_id: AccountId(\"a.near\"), \
+            actions: [CreateAccount], after: None, should_return: false }, promise_b: Promise { \
+            receiver_id: AccountId(\"b.near\"), actions: [Transfer { deposit: 10 }], after: None, \
+            should_return: false }, should_return: false }), should_return: false }"
+        );
+
+        let joint =
+            Promise::new("a.near".parse().unwrap()).and(Promise::new("b.near".parse().unwrap()));
+        assert_eq!(joint.receiver_id(), None);
+        assert!(joint.actions().is_empty());
+        let (a, b) = joint.joined().unwrap();
+        assert_eq!(a.receiver_id(), Some(&"a.near".parse().unwrap()));
+        assert_eq!(b.receiver_id(), Some(&"b.near".parse().unwrap()));
+
+        let deploy = Promise::new("a.near".parse().unwrap()).deploy_contract(vec![0; 1000]);
+        assert_eq!(deploy.actions()[0].code().map(<[u8]>::len), Some(1000));
+        assert_eq!(format!("{:?}", deploy.actions()), "[DeployContract { code_len: 1000 }]");
+    }
+}
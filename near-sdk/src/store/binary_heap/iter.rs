@@ -0,0 +1,99 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use core::iter::FusedIterator;
+
+use super::BinaryHeap;
+
+/// A draining iterator which removes the elements of a [`BinaryHeap`] from the greatest to the
+/// smallest.
+///
+/// This `struct` is created by [`BinaryHeap::drain_sorted`]. Elements which are not yielded are
+/// kept in the heap.
+#[derive(Debug)]
+pub struct DrainSorted<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    heap: &'a mut BinaryHeap<T>,
+}
+
+impl<'a, T> DrainSorted<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    pub(super) fn new(heap: &'a mut BinaryHeap<T>) -> Self {
+        Self { heap }
+    }
+}
+
+impl<'a, T> Iterator for DrainSorted<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.heap.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.heap.len() as usize;
+        (remaining, Some(remaining))
+    }
+
+    fn count(self) -> usize {
+        self.heap.len() as usize
+    }
+}
+
+impl<'a, T> ExactSizeIterator for DrainSorted<'a, T> where T: BorshSerialize + BorshDeserialize + Ord
+{}
+impl<'a, T> FusedIterator for DrainSorted<'a, T> where T: BorshSerialize + BorshDeserialize + Ord {}
+
+/// An owning iterator which yields the elements of a [`BinaryHeap`] from the greatest to the
+/// smallest.
+///
+/// This `struct` is created by [`BinaryHeap::into_sorted_iter`].
+#[derive(Debug)]
+pub struct IntoSortedIter<T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    heap: BinaryHeap<T>,
+}
+
+impl<T> IntoSortedIter<T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    pub(super) fn new(heap: BinaryHeap<T>) -> Self {
+        Self { heap }
+    }
+
+    /// Returns the heap of the elements which were not yielded yet.
+    pub fn into_heap(self) -> BinaryHeap<T> {
+        self.heap
+    }
+}
+
+impl<T> Iterator for IntoSortedIter<T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.heap.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.heap.len() as usize;
+        (remaining, Some(remaining))
+    }
+
+    fn count(self) -> usize {
+        self.heap.len() as usize
+    }
+}
+
+impl<T> ExactSizeIterator for IntoSortedIter<T> where T: BorshSerialize + BorshDeserialize + Ord {}
+impl<T> FusedIterator for IntoSortedIter<T> where T: BorshSerialize + BorshDeserialize + Ord {}
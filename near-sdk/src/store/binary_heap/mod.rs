@@ -0,0 +1,553 @@
+//! A priority queue with values persisted to storage and lazily loaded.
+//!
+//! The [`BinaryHeap`] is a max-heap stored in a [`Vector`], so only the elements on the path
+//! between the root and the modified position are loaded and written by each operation.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::store::BinaryHeap;
+//!
+//! let mut heap: BinaryHeap<u32> = BinaryHeap::new(b"h");
+//! heap.extend([3, 1, 5]);
+//!
+//! assert_eq!(heap.peek(), Some(&5));
+//! assert_eq!(heap.pop(), Some(5));
+//! assert_eq!(heap.pop(), Some(3));
+//! ```
+//!
+//! A min-heap can be built by storing a type with a reversed [`Ord`] implementation, as
+//! [`Reverse`](core::cmp::Reverse) does not implement the borsh traits.
+//!
+//! [`Vector`]: super::Vector
+
+mod iter;
+
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+pub use self::iter::{DrainSorted, IntoSortedIter};
+use super::vec::{self, Vector};
+use super::ERR_INCONSISTENT_STATE;
+use crate::{env, IntoStorageKey};
+
+/// A priority queue implemented with a binary heap that stores its content on the trie. This
+/// implementation will load and store values in the underlying storage lazily.
+///
+/// This is a max-heap, the greatest element according to [`Ord`] is at the top of the heap.
+/// Pushing and popping an element loads and writes `O(log n)` elements, and [`BinaryHeap::peek`]
+/// only loads the top element. Compared to a [`TreeMap`], no rebalancing is needed on inserts,
+/// but the elements can only be retrieved in order by removing them.
+///
+/// The elements are stored in a [`Vector`], and follow its caching semantics: changes are only
+/// written to storage when the heap is dropped or [`BinaryHeap::flush`] is called.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::BinaryHeap;
+///
+/// let mut heap = BinaryHeap::new(b"h");
+/// assert!(heap.is_empty());
+///
+/// heap.push(1);
+/// heap.push(5);
+/// heap.push(2);
+///
+/// assert_eq!(heap.len(), 3);
+/// assert_eq!(heap.peek(), Some(&5));
+///
+/// // Remove the two greatest elements, leaving the rest in the heap.
+/// let page: Vec<_> = heap.drain_sorted().take(2).collect();
+/// assert_eq!(page, [5, 2]);
+/// assert_eq!(heap.peek(), Some(&1));
+/// ```
+///
+/// [`TreeMap`]: super::TreeMap
+pub struct BinaryHeap<T>
+where
+    T: BorshSerialize,
+{
+    data: Vector<T>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<T> BorshSerialize for BinaryHeap<T>
+where
+    T: BorshSerialize,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.data, writer)
+    }
+}
+
+impl<T> BorshDeserialize for BinaryHeap<T>
+where
+    T: BorshSerialize,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self { data: BorshDeserialize::deserialize(buf)? })
+    }
+}
+
+impl<T> BinaryHeap<T>
+where
+    T: BorshSerialize,
+{
+    /// Create new heap with zero elements. Prefixes storage accesss with the prefix provided.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::BinaryHeap;
+    ///
+    /// let mut heap: BinaryHeap<u8> = BinaryHeap::new(b"h");
+    /// ```
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { data: Vector::new(prefix) }
+    }
+
+    /// Returns the number of elements in the heap.
+    pub fn len(&self) -> u32 {
+        self.data.len()
+    }
+
+    /// Returns `true` if the heap contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Removes all elements from the heap. This will remove all storage values for the length of
+    /// the [`BinaryHeap`].
+    pub fn clear(&mut self) {
+        self.data.clear()
+    }
+
+    /// Flushes the cache and writes all modified values to storage.
+    ///
+    /// This operation is performed on [`Drop`], but this method can be called to persist
+    /// intermediate writes in cases where [`Drop`] is not called or to identify storage changes.
+    pub fn flush(&mut self) {
+        self.data.flush()
+    }
+}
+
+impl<T> BinaryHeap<T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    /// Returns the greatest element of the heap, or `None` if it is empty. Only the top element
+    /// is loaded from storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new(b"h");
+    /// assert_eq!(heap.peek(), None);
+    ///
+    /// heap.extend([1, 5, 2]);
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    pub fn peek(&self) -> Option<&T> {
+        self.data.get(0)
+    }
+
+    /// Returns a mutable reference to the greatest element of the heap, or `None` if it is empty.
+    ///
+    /// The heap is restored when the returned [`PeekMut`] is dropped, if the element was
+    /// modified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new(b"h");
+    /// heap.extend([1, 5, 2]);
+    ///
+    /// if let Some(mut top) = heap.peek_mut() {
+    ///     *top = 0;
+    /// }
+    /// assert_eq!(heap.peek(), Some(&2));
+    /// ```
+    pub fn peek_mut(&mut self) -> Option<PeekMut<'_, T>> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(PeekMut { heap: self, modified: false })
+        }
+    }
+
+    /// Pushes an element onto the heap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if new length exceeds `u32::MAX`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new(b"h");
+    /// heap.push(3);
+    /// heap.push(7);
+    ///
+    /// assert_eq!(heap.len(), 2);
+    /// assert_eq!(heap.peek(), Some(&7));
+    /// ```
+    pub fn push(&mut self, element: T) {
+        self.data.push(element);
+        self.sift_up(self.data.len() - 1);
+    }
+
+    /// Removes the greatest element from the heap and returns it, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new(b"h");
+    /// heap.extend([1, 3]);
+    ///
+    /// assert_eq!(heap.pop(), Some(3));
+    /// assert_eq!(heap.pop(), Some(1));
+    /// assert_eq!(heap.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        let last = self.data.pop()?;
+        if self.data.is_empty() {
+            return Some(last);
+        }
+        let top = self.data.replace(0, last);
+        self.sift_down(0);
+        Some(top)
+    }
+
+    /// Returns an iterator visiting all values in the heap, in arbitrary order. This iterator
+    /// will lazily load any values iterated over from storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new(b"h");
+    /// heap.extend([1, 2, 3]);
+    ///
+    /// let mut values: Vec<_> = heap.iter().copied().collect();
+    /// values.sort();
+    /// assert_eq!(values, [1, 2, 3]);
+    /// ```
+    pub fn iter(&self) -> vec::Iter<T> {
+        self.data.iter()
+    }
+
+    /// Returns an iterator which removes and yields the elements of the heap from the greatest to
+    /// the smallest. Elements are only removed when they are yielded, so the iterator can be used
+    /// to paginate over the heap with [`Iterator::take`], leaving the remaining elements in the
+    /// heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new(b"h");
+    /// heap.extend([1, 4, 2, 3]);
+    ///
+    /// assert_eq!(heap.drain_sorted().take(2).collect::<Vec<_>>(), [4, 3]);
+    /// assert_eq!(heap.drain_sorted().collect::<Vec<_>>(), [2, 1]);
+    /// assert!(heap.is_empty());
+    /// ```
+    pub fn drain_sorted(&mut self) -> DrainSorted<'_, T> {
+        DrainSorted::new(self)
+    }
+
+    /// Consumes the heap and returns an iterator which yields the elements from the greatest to
+    /// the smallest. The iterator can be converted back into a heap of the elements which were
+    /// not yielded with [`IntoSortedIter::into_heap`], which allows to paginate over the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new(b"h");
+    /// heap.extend([1, 4, 2, 3]);
+    ///
+    /// let mut iter = heap.into_sorted_iter();
+    /// assert_eq!(iter.by_ref().take(3).collect::<Vec<_>>(), [4, 3, 2]);
+    ///
+    /// let heap = iter.into_heap();
+    /// assert_eq!(heap.len(), 1);
+    /// ```
+    pub fn into_sorted_iter(self) -> IntoSortedIter<T> {
+        IntoSortedIter::new(self)
+    }
+
+    /// Moves the element at `pos` up the heap until its parent is greater or equal.
+    fn sift_up(&mut self, mut pos: u32) {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if self.data[pos] <= self.data[parent] {
+                break;
+            }
+            self.data.values.swap(pos, parent);
+            pos = parent;
+        }
+    }
+
+    /// Moves the element at `pos` down the heap until both of its children are smaller or equal.
+    fn sift_down(&mut self, mut pos: u32) {
+        let len = self.data.len();
+        loop {
+            let left = match pos.checked_mul(2).and_then(|i| i.checked_add(1)) {
+                Some(left) if left < len => left,
+                _ => break,
+            };
+            let right = left + 1;
+            let child =
+                if right < len && self.data[right] > self.data[left] { right } else { left };
+            if self.data[pos] >= self.data[child] {
+                break;
+            }
+            self.data.values.swap(pos, child);
+            pos = child;
+        }
+    }
+}
+
+impl<T> Extend<T> for BinaryHeap<T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.push(item)
+        }
+    }
+}
+
+impl<T> fmt::Debug for BinaryHeap<T>
+where
+    T: BorshSerialize + BorshDeserialize + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if cfg!(feature = "expensive-debug") {
+            fmt::Debug::fmt(&self.data.iter().collect::<Vec<_>>(), f)
+        } else {
+            f.debug_struct("BinaryHeap")
+                .field("len", &self.data.len())
+                .field("prefix", &self.data.values.prefix)
+                .finish()
+        }
+    }
+}
+
+/// Structure wrapping a mutable reference to the greatest element of a [`BinaryHeap`].
+///
+/// This is created by [`BinaryHeap::peek_mut`]. The heap is restored when it is dropped.
+pub struct PeekMut<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    heap: &'a mut BinaryHeap<T>,
+    modified: bool,
+}
+
+impl<'a, T> PeekMut<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    /// Removes the peeked value from the heap and returns it.
+    pub fn pop(mut this: Self) -> T {
+        // The element is removed, no need to restore the heap on drop.
+        this.modified = false;
+        this.heap.pop().unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+    }
+}
+
+impl<'a, T> Deref for PeekMut<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.heap.data[0]
+    }
+}
+
+impl<'a, T> DerefMut for PeekMut<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        self.modified = true;
+        &mut self.heap.data[0]
+    }
+}
+
+impl<'a, T> Drop for PeekMut<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    fn drop(&mut self) {
+        if self.modified {
+            self.heap.sift_down(0);
+        }
+    }
+}
+
+impl<'a, T> fmt::Debug for PeekMut<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PeekMut").field(&self.heap.data[0]).finish()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use rand::{Rng, RngCore, SeedableRng};
+
+    use super::{BinaryHeap, PeekMut};
+    use crate::test_utils::test_env::setup_free;
+
+    #[test]
+    fn test_push_pop() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut heap = BinaryHeap::new(b"h".to_vec());
+        let mut baseline = std::collections::BinaryHeap::new();
+        for _ in 0..500 {
+            let value = rng.gen::<u8>();
+            heap.push(value);
+            baseline.push(value);
+            assert_eq!(heap.peek(), baseline.peek());
+        }
+        for _ in 0..501 {
+            assert_eq!(heap.pop(), baseline.pop());
+        }
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn peek_mut() {
+        let mut heap = BinaryHeap::new(b"h");
+        heap.extend([2u32, 8, 4, 6]);
+
+        // Reading through the guard does not restore the heap
+        assert_eq!(*heap.peek_mut().unwrap(), 8);
+        *heap.peek_mut().unwrap() = 1;
+        assert_eq!(heap.peek(), Some(&6));
+
+        let top = heap.peek_mut().unwrap();
+        assert_eq!(PeekMut::pop(top), 6);
+        assert_eq!(heap.into_sorted_iter().collect::<Vec<_>>(), [4, 2, 1]);
+    }
+
+    #[test]
+    fn sorted_pagination() {
+        let mut heap = BinaryHeap::new(b"h");
+        heap.extend(0u32..20);
+
+        let first: Vec<_> = heap.drain_sorted().take(5).collect();
+        assert_eq!(first, [19, 18, 17, 16, 15]);
+        assert_eq!(heap.len(), 15);
+
+        let mut iter = heap.into_sorted_iter();
+        assert_eq!(iter.len(), 15);
+        assert_eq!(iter.by_ref().take(5).collect::<Vec<_>>(), [14, 13, 12, 11, 10]);
+        let heap = iter.into_heap();
+
+        // Remaining elements are persisted with the heap metadata.
+        let serialized = heap.try_to_vec().unwrap();
+        drop(heap);
+        let heap = BinaryHeap::<u32>::deserialize(&mut serialized.as_slice()).unwrap();
+        assert!(Iterator::eq(heap.into_sorted_iter(), (0..10).rev()));
+    }
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        Push(u8),
+        Pop,
+        Peek,
+        PeekMut(u8),
+        Flush,
+        Reset,
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..1024 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut sh = BinaryHeap::new(b"h");
+            let mut mh = std::collections::BinaryHeap::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::Push(v) => {
+                            sh.push(v);
+                            mh.push(v);
+                        }
+                        Op::Pop => {
+                            assert_eq!(sh.pop(), mh.pop());
+                        }
+                        Op::Peek => {
+                            assert_eq!(sh.peek(), mh.peek());
+                        }
+                        Op::PeekMut(v) => {
+                            if let (Some(mut s), Some(mut m)) = (sh.peek_mut(), mh.peek_mut()) {
+                                *s = v;
+                                *m = v;
+                            }
+                        }
+                        Op::Flush => {
+                            sh.flush();
+                        }
+                        Op::Reset => {
+                            let serialized = sh.try_to_vec().unwrap();
+                            sh = BinaryHeap::deserialize(&mut serialized.as_slice()).unwrap();
+                        }
+                    }
+                    assert_eq!(sh.len() as usize, mh.len());
+                }
+            }
+
+            // After all operations, compare both heaps
+            assert!(Iterator::eq(sh.into_sorted_iter(), mh.into_sorted_vec().into_iter().rev()));
+        }
+    }
+}
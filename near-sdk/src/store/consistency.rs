@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+/// An inconsistency between the index structures of a collection and the values stored for it,
+/// as reported by the `validate` and `repair` methods of [`Vector`], [`UnorderedMap`],
+/// [`UnorderedSet`] and [`TreeMap`].
+///
+/// Collections panic with an "inconsistent state" error when they reach one of these while
+/// being used. Validating the collection lists all of them at once, and repairing it rebuilds the
+/// index structures from the data which survived. Both read every element of the collection, so
+/// they are meant for unit tests and for maintenance methods of the contract, which should only be
+/// callable by the owner:
+///
+/// ```
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::store::{Inconsistency, UnorderedMap};
+/// use near_sdk::{env, near_bindgen, require, AccountId};
+///
+/// #[near_bindgen]
+/// #[derive(BorshDeserialize, BorshSerialize)]
+/// pub struct Contract {
+///     owner_id: AccountId,
+///     balances: UnorderedMap<AccountId, u128>,
+/// }
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     pub fn validate_balances(&mut self) -> Vec<Inconsistency> {
+///         require!(env::predecessor_account_id() == self.owner_id, "Owner only");
+///         self.balances.validate()
+///     }
+///
+///     pub fn repair_balances(&mut self) -> Vec<Inconsistency> {
+///         require!(env::predecessor_account_id() == self.owner_id, "Owner only");
+///         self.balances.repair()
+///     }
+/// }
+/// ```
+///
+/// The indices are the indices of the elements for [`Vector`], and the indices of the slots
+/// holding the keys of the other collections.
+///
+/// [`Vector`]: super::Vector
+/// [`UnorderedMap`]: super::UnorderedMap
+/// [`UnorderedSet`]: super::UnorderedSet
+/// [`TreeMap`]: super::TreeMap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "abi", derive(schemars::JsonSchema))]
+pub enum Inconsistency {
+    /// The slot at `index` is within the length of the collection, but is missing from storage.
+    MissingSlot { index: u32 },
+    /// The bytes stored for the slot at `index` cannot be deserialized.
+    CorruptedSlot { index: u32 },
+    /// A slot is stored at `index`, past the length of the collection. Only the slots stored
+    /// right after the end, without a gap, are found.
+    OrphanedSlot { index: u32 },
+    /// The recorded length of the collection differs from the number of stored elements.
+    LengthMismatch { recorded: u32, actual: u32 },
+    /// The list of removed slots, which are reused by insertions, reaches the slot at `index`
+    /// while it is out of bounds, occupied or already reached.
+    BrokenFreeList { index: u32 },
+    /// The removed slot at `index` is not part of the list of removed slots, so it is never
+    /// reused.
+    UnlinkedFreeSlot { index: u32 },
+    /// The key in the slot at `index` has no entry in the map, or in the index of the set.
+    DanglingIndex { index: u32 },
+    /// The entry of the key in the slot at `index` cannot be deserialized.
+    CorruptedEntry { index: u32 },
+    /// The entry of the key in the slot at `index` refers to the slot at `recorded` instead.
+    IndexMismatch { index: u32, recorded: u32 },
+    /// The tree node at `from`, or the root of the tree if `None`, links to the slot at `to`,
+    /// which is not a node or is already linked from another node.
+    InvalidLink { from: Option<u32>, to: u32 },
+    /// The tree node at `index` is not reachable from the root of the tree.
+    OrphanedNode { index: u32 },
+    /// The key of the tree node at `index` is out of order with the keys of its ancestors.
+    UnorderedNode { index: u32 },
+    /// The recorded height of the tree node at `index` does not match its subtrees, or the
+    /// subtrees are unbalanced.
+    InvalidHeight { index: u32 },
+}
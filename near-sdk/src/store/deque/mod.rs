@@ -0,0 +1,825 @@
+//! A double-ended queue with values persisted to storage and lazily loaded.
+//!
+//! Values in the [`Deque`] are kept in an in-memory cache and are only persisted on [`Drop`].
+//!
+//! The elements are stored in a ring buffer over the [`u32`] index space, so pushing to either
+//! end of the queue is `O(1)` and never moves existing elements in storage.
+//!
+//! # Examples
+//!
+//! You can push values to and pop values from both ends of a [`Deque`]:
+//!
+//! ```
+//! use near_sdk::store::Deque;
+//!
+//! let mut queue: Deque<u32> = Deque::new(b"q");
+//!
+//! queue.push_back(2);
+//! queue.push_back(3);
+//! queue.push_front(1);
+//!
+//! assert_eq!(queue.pop_front(), Some(1));
+//! assert_eq!(queue.pop_back(), Some(3));
+//! ```
+//!
+//! Deques also support indexing (through the [`Index`] and [`IndexMut`] traits), relative to the
+//! front of the queue:
+//!
+//! ```
+//! use near_sdk::store::Deque;
+//!
+//! let mut queue: Deque<u32> = Deque::new(b"q");
+//! queue.extend([1, 2, 3]);
+//! queue.push_front(0);
+//!
+//! assert_eq!(queue[0], 0);
+//! queue[1] += 5;
+//! assert_eq!(queue[1], 6);
+//! ```
+//!
+//! [`Index`]: std::ops::Index
+//! [`IndexMut`]: std::ops::IndexMut
+
+mod impls;
+mod iter;
+
+use std::{
+    fmt,
+    ops::{Bound, Range, RangeBounds},
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+pub use self::iter::{Iter, IterMut};
+use crate::{env, IntoStorageKey};
+
+use super::{IndexMap, Page, ERR_INCONSISTENT_STATE};
+
+const ERR_INDEX_OUT_OF_BOUNDS: &str = "Index out of bounds";
+
+/// A double-ended queue implemented with a growable ring buffer that stores its content on the
+/// trie. This implementation will load and store values in the underlying storage lazily.
+///
+/// Uses the following map: index -> element. The index of the front element is persisted with the
+/// length of the queue, and the remaining elements follow it, wrapping around at [`u32::MAX`].
+/// Because the data is sharded to avoid reading/writing large chunks of data, the values cannot be
+/// accessed as a contiguous piece of memory.
+///
+/// This implementation will cache all changes and loads and only updates values that are changed
+/// in storage after it's dropped through it's [`Drop`] implementation. These changes can be updated
+/// in storage before the variable is dropped by using [`Deque::flush`]. During the lifetime of
+/// this type, storage will only be read a maximum of one time per index and only written once per
+/// index unless specifically flushed.
+///
+/// This type should be a drop in replacement for [`VecDeque`] in most cases and is a better fit
+/// than a [`Vector`] with manually tracked head and tail indices for FIFO queues.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::Deque;
+///
+/// let mut queue = Deque::new(b"q");
+/// assert!(queue.is_empty());
+///
+/// queue.push_back(1);
+/// queue.push_back(2);
+/// queue.push_front(0);
+///
+/// assert_eq!(queue.len(), 3);
+/// assert_eq!(queue.front(), Some(&0));
+/// assert_eq!(queue.back(), Some(&2));
+///
+/// assert_eq!(queue.pop_front(), Some(0));
+/// assert_eq!(queue.len(), 2);
+///
+/// queue.extend([3, 4]);
+/// assert!(Iterator::eq(queue.range(1..3), [2, 3].iter()));
+/// assert!(Iterator::eq(queue.into_iter(), [1, 2, 3, 4].iter()));
+/// ```
+///
+/// [`VecDeque`]: std::collections::VecDeque
+/// [`Vector`]: super::Vector
+pub struct Deque<T>
+where
+    T: BorshSerialize,
+{
+    pub(crate) head: u32,
+    pub(crate) len: u32,
+    pub(crate) values: IndexMap<T>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<T> BorshSerialize for Deque<T>
+where
+    T: BorshSerialize,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.head, writer)?;
+        BorshSerialize::serialize(&self.len, writer)?;
+        BorshSerialize::serialize(&self.values, writer)?;
+        Ok(())
+    }
+}
+
+impl<T> BorshDeserialize for Deque<T>
+where
+    T: BorshSerialize,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            head: BorshDeserialize::deserialize(buf)?,
+            len: BorshDeserialize::deserialize(buf)?,
+            values: BorshDeserialize::deserialize(buf)?,
+        })
+    }
+}
+
+impl<T> Deque<T>
+where
+    T: BorshSerialize,
+{
+    /// Returns the number of elements in the queue, also referred to as its size.
+    /// This function returns a `u32` rather than the [`VecDeque`] equivalent of `usize` to have
+    /// consistency between targets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.push_back(1);
+    /// queue.push_front(2);
+    /// assert_eq!(queue.len(), 2);
+    /// ```
+    ///
+    /// [`VecDeque`]: std::collections::VecDeque
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns `true` if the queue contains no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// assert!(queue.is_empty());
+    ///
+    /// queue.push_back(1);
+    /// assert!(!queue.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Create new queue with zero elements. Prefixes storage accesss with the prefix provided.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue: Deque<u8> = Deque::new(b"q");
+    /// ```
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { head: 0, len: 0, values: IndexMap::new(prefix) }
+    }
+
+    /// Converts an index relative to the front of the queue into the index used in storage.
+    fn physical_index(&self, index: u32) -> u32 {
+        self.head.wrapping_add(index)
+    }
+
+    /// Removes all elements from the collection. This will remove all storage values for the
+    /// length of the [`Deque`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.push_back(1);
+    /// queue.push_front(0);
+    ///
+    /// queue.clear();
+    ///
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        for i in 0..self.len {
+            let index = self.physical_index(i);
+            self.values.set(index, None);
+        }
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Flushes the cache and writes all modified values to storage.
+    ///
+    /// This operation is performed on [`Drop`], but this method can be called to persist
+    /// intermediate writes in cases where [`Drop`] is not called or to identify storage changes.
+    pub fn flush(&mut self) {
+        self.values.flush();
+    }
+
+    /// Sets a value at a given index, relative to the front of the queue, to the value provided.
+    ///
+    /// The reason to use this over modifying with [`Deque::get_mut`] or
+    /// [`IndexMut::index_mut`](core::ops::IndexMut::index_mut) is to avoid loading the existing
+    /// value from storage. This method will just write the new value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.push_back("test".to_string());
+    ///
+    /// queue.set(0, "new_value".to_string());
+    ///
+    /// assert_eq!(queue.get(0), Some(&"new_value".to_string()));
+    /// ```
+    pub fn set(&mut self, index: u32, value: T) {
+        if index >= self.len() {
+            env::panic_str(ERR_INDEX_OUT_OF_BOUNDS);
+        }
+
+        let index = self.physical_index(index);
+        self.values.set(index, Some(value));
+    }
+
+    /// Appends an element to the back of the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if new length exceeds `u32::MAX`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.push_back(1);
+    /// queue.push_back(2);
+    ///
+    /// assert_eq!(queue.back(), Some(&2));
+    /// ```
+    pub fn push_back(&mut self, element: T) {
+        let last_idx = self.len();
+        self.len =
+            self.len.checked_add(1).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS));
+        self.set(last_idx, element)
+    }
+
+    /// Prepends an element to the front of the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if new length exceeds `u32::MAX`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.push_front(1);
+    /// queue.push_front(2);
+    ///
+    /// assert_eq!(queue.front(), Some(&2));
+    /// ```
+    pub fn push_front(&mut self, element: T) {
+        self.len =
+            self.len.checked_add(1).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS));
+        self.head = self.head.wrapping_sub(1);
+        self.set(0, element)
+    }
+}
+
+impl<T> Deque<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    /// Returns the element at `index`, relative to the front of the queue, or `None` if it is
+    /// out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.push_back("back".to_string());
+    /// queue.push_front("front".to_string());
+    ///
+    /// assert_eq!(Some(&"front".to_string()), queue.get(0));
+    /// assert_eq!(Some(&"back".to_string()), queue.get(1));
+    /// assert_eq!(None, queue.get(3));
+    /// ```
+    pub fn get(&self, index: u32) -> Option<&T> {
+        if index >= self.len() {
+            return None;
+        }
+        self.values.get(self.physical_index(index))
+    }
+
+    /// Returns a mutable reference to the element at `index`, relative to the front of the queue,
+    /// or `None` if it is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.extend([0, 1, 2]);
+    ///
+    /// if let Some(elem) = queue.get_mut(1) {
+    ///     *elem = 42;
+    /// }
+    ///
+    /// let actual: Vec<_> = queue.iter().cloned().collect();
+    /// assert_eq!(actual, &[0, 42, 2]);
+    /// ```
+    pub fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        if index >= self.len() {
+            return None;
+        }
+        let index = self.physical_index(index);
+        self.values.get_mut(index)
+    }
+
+    /// Returns a reference to the front element, or `None` if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// assert_eq!(queue.front(), None);
+    ///
+    /// queue.extend([1, 2]);
+    /// assert_eq!(queue.front(), Some(&1));
+    /// ```
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns a mutable reference to the front element, or `None` if the queue is empty.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    /// Returns a reference to the back element, or `None` if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// assert_eq!(queue.back(), None);
+    ///
+    /// queue.extend([1, 2]);
+    /// assert_eq!(queue.back(), Some(&2));
+    /// ```
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// Returns a mutable reference to the back element, or `None` if the queue is empty.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.checked_sub(1)?)
+    }
+
+    /// Removes the first element and returns it, or [`None`] if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.extend([1, 2]);
+    ///
+    /// assert_eq!(queue.pop_front(), Some(1));
+    /// assert_eq!(queue.pop_front(), Some(2));
+    /// assert_eq!(queue.pop_front(), None);
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        let new_len = self.len.checked_sub(1)?;
+        let prev = self.values.remove(self.head);
+        self.head = self.head.wrapping_add(1);
+        self.len = new_len;
+        prev
+    }
+
+    /// Removes the last element and returns it, or [`None`] if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.extend([1, 2]);
+    ///
+    /// assert_eq!(queue.pop_back(), Some(2));
+    /// assert_eq!(queue.pop_back(), Some(1));
+    /// assert_eq!(queue.pop_back(), None);
+    /// ```
+    pub fn pop_back(&mut self) -> Option<T> {
+        let new_len = self.len.checked_sub(1)?;
+        let prev = self.values.remove(self.physical_index(new_len));
+        self.len = new_len;
+        prev
+    }
+
+    /// Returns a front-to-back iterator over the queue. This iterator will lazily load any values
+    /// iterated over from storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.extend([2, 4]);
+    /// queue.push_front(1);
+    /// let mut iterator = queue.iter();
+    ///
+    /// assert_eq!(iterator.next(), Some(&1));
+    /// assert_eq!(iterator.next(), Some(&2));
+    /// assert_eq!(iterator.next(), Some(&4));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    pub fn iter(&self) -> Iter<T> {
+        Iter::new(self, Range { start: 0, end: self.len() })
+    }
+
+    /// Returns a front-to-back iterator over the [`Deque`] that allows modifying each value. This
+    /// iterator will lazily load any values iterated over from storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.extend([1u32, 2, 4]);
+    ///
+    /// for elem in queue.iter_mut() {
+    ///     *elem += 2;
+    /// }
+    /// assert_eq!(queue.iter().copied().collect::<Vec<_>>(), &[3u32, 4, 6]);
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<T> {
+        let range = Range { start: 0, end: self.len() };
+        IterMut::new(self, range)
+    }
+
+    /// Returns a page of at most `limit` elements starting at `from_index`, relative to the front
+    /// of the queue, with the index to start the next page from. Only the elements of the page
+    /// are loaded from storage.
+    ///
+    /// Popping elements from the front of the queue shifts the indices of the remaining elements,
+    /// so the cursor of a page is only valid as long as the front of the queue is not modified.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.extend([2, 3, 4]);
+    /// queue.push_front(1);
+    ///
+    /// let page = queue.paginate(0, 3);
+    /// assert_eq!(page.items, [&1, &2, &3]);
+    /// assert_eq!(page.next_cursor, Some(3));
+    /// ```
+    pub fn paginate(&self, from_index: u32, limit: u32) -> Page<&T> {
+        Page::from_indices(from_index, limit, self.len(), |i| {
+            Some(self.get(i).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE)))
+        })
+    }
+
+    /// Returns an iterator over the elements in the given range of indices, relative to the front
+    /// of the queue. Only the values in the range are loaded from storage, which makes this the
+    /// way to paginate over a queue.
+    ///
+    /// This will not panic on invalid ranges (`end > length` or `end < start`) and instead the
+    /// iterator will be cut at the end of the queue or be empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.extend([1, 2, 3, 4]);
+    /// queue.push_front(0);
+    ///
+    /// assert_eq!(queue.range(1..3).copied().collect::<Vec<_>>(), &[1, 2]);
+    /// assert_eq!(queue.range(3..).copied().collect::<Vec<_>>(), &[3, 4]);
+    /// assert_eq!(queue.range(4..10).count(), 1);
+    /// ```
+    pub fn range<R>(&self, range: R) -> Iter<T>
+    where
+        R: RangeBounds<u32>,
+    {
+        Iter::new(self, self.clamp_range(range))
+    }
+
+    /// Returns a mutable iterator over the elements in the given range of indices, relative to the
+    /// front of the queue. Only the values in the range are loaded from storage.
+    ///
+    /// This will not panic on invalid ranges and behaves like [`Deque::range`].
+    pub fn range_mut<R>(&mut self, range: R) -> IterMut<T>
+    where
+        R: RangeBounds<u32>,
+    {
+        let range = self.clamp_range(range);
+        IterMut::new(self, range)
+    }
+
+    fn clamp_range<R>(&self, range: R) -> Range<u32>
+    where
+        R: RangeBounds<u32>,
+    {
+        let start = match range.start_bound() {
+            Bound::Excluded(i) => {
+                i.checked_add(1).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS))
+            }
+            Bound::Included(i) => *i,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Excluded(i) => *i,
+            Bound::Included(i) => {
+                i.checked_add(1).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS))
+            }
+            Bound::Unbounded => self.len(),
+        };
+
+        // Note: don't need to do bounds check if end < start, will just return None when iterating
+        // This will also cap the max length at the length of the queue.
+        Range { start, end: core::cmp::min(end, self.len()) }
+    }
+}
+
+impl<T> fmt::Debug for Deque<T>
+where
+    T: BorshSerialize + BorshDeserialize + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if cfg!(feature = "expensive-debug") {
+            fmt::Debug::fmt(&self.iter().collect::<Vec<_>>(), f)
+        } else {
+            f.debug_struct("Deque")
+                .field("head", &self.head)
+                .field("len", &self.len)
+                .field("prefix", &self.values.prefix)
+                .finish()
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use arbitrary::{Arbitrary, Unstructured};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use rand::{Rng, RngCore, SeedableRng};
+
+    use super::Deque;
+    use crate::test_utils::test_env::setup_free;
+
+    #[test]
+    fn test_push_pop() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut queue = Deque::new(b"q".to_vec());
+        let mut baseline = VecDeque::new();
+        for _ in 0..500 {
+            let value = rng.gen::<u64>();
+            if rng.gen::<bool>() {
+                queue.push_back(value);
+                baseline.push_back(value);
+            } else {
+                queue.push_front(value);
+                baseline.push_front(value);
+            }
+        }
+        assert!(Iterator::eq(queue.iter(), baseline.iter()));
+        for _ in 0..501 {
+            if rng.gen::<bool>() {
+                assert_eq!(baseline.pop_back(), queue.pop_back());
+            } else {
+                assert_eq!(baseline.pop_front(), queue.pop_front());
+            }
+        }
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn wraps_around_index_space() {
+        let mut queue = Deque::new(b"q");
+        queue.push_front(1u8);
+        queue.push_front(0);
+        queue.push_back(2);
+        assert_eq!(queue.head, u32::MAX - 1);
+        assert!(Iterator::eq(queue.iter(), [0, 1, 2].iter()));
+        assert!(Iterator::eq(queue.range(1..).rev(), [2, 1].iter()));
+
+        queue.flush();
+        let mut keys: Vec<_> =
+            crate::mock::with_mocked_blockchain(|m| m.take_storage().into_keys().collect());
+        keys.sort();
+        let key = |index: u32| [b"q".as_slice(), &index.to_le_bytes()].concat();
+        assert_eq!(keys, vec![key(0), key(u32::MAX - 1), key(u32::MAX)]);
+    }
+
+    #[test]
+    fn iterator_checks() {
+        let mut queue = Deque::new(b"q");
+        let mut baseline = VecDeque::new();
+        for i in 0..10 {
+            queue.push_front(i);
+            baseline.push_front(i);
+        }
+
+        let mut queue_iter = queue.iter();
+        let mut bl_iter = baseline.iter();
+        assert_eq!(queue_iter.next(), bl_iter.next());
+        assert_eq!(queue_iter.next_back(), bl_iter.next_back());
+        assert_eq!(queue_iter.nth(3), bl_iter.nth(3));
+        assert_eq!(queue_iter.nth_back(2), bl_iter.nth_back(2));
+
+        // Check to make sure indexing overflow is handled correctly
+        assert!(queue_iter.nth(5).is_none());
+        assert!(bl_iter.nth(5).is_none());
+
+        assert!(queue_iter.next().is_none());
+        assert!(bl_iter.next().is_none());
+
+        // Count and range checks
+        assert_eq!(queue.iter().count(), baseline.len());
+        assert!(Iterator::eq(queue.range(2..=5), baseline.range(2..=5)));
+        assert_eq!(queue.range(8..20).len(), 2);
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty_range = queue.range(5..2);
+        assert_eq!(empty_range.count(), 0);
+
+        for elem in queue.range_mut(..5) {
+            *elem += 100;
+        }
+        baseline.range_mut(..5).for_each(|elem| *elem += 100);
+        assert!(Iterator::eq(queue.iter(), baseline.iter()));
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut queue = Deque::new(b"q".to_vec());
+        queue.extend([1u64, 2, 3]);
+        queue.push_front(0);
+        if cfg!(feature = "expensive-debug") {
+            assert_eq!(format!("{:?}", queue), "[0, 1, 2, 3]");
+        } else {
+            assert_eq!(
+                format!("{:?}", queue),
+                format!(
+                    "Deque {{ head: {}, len: 4, prefix: {:?} }}",
+                    u32::MAX,
+                    queue.values.prefix
+                )
+            );
+        }
+    }
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        PushBack(u8),
+        PushFront(u8),
+        PopBack,
+        PopFront,
+        Set(u32, u8),
+        Get(u32),
+        Clear,
+        Flush,
+        Reset,
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..1024 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut sq = Deque::new(b"q");
+            let mut mq = VecDeque::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::PushBack(v) => {
+                            sq.push_back(v);
+                            mq.push_back(v);
+                        }
+                        Op::PushFront(v) => {
+                            sq.push_front(v);
+                            mq.push_front(v);
+                        }
+                        Op::PopBack => {
+                            assert_eq!(sq.pop_back(), mq.pop_back());
+                        }
+                        Op::PopFront => {
+                            assert_eq!(sq.pop_front(), mq.pop_front());
+                        }
+                        Op::Set(k, v) => {
+                            if sq.is_empty() {
+                                continue;
+                            }
+                            let k = k % sq.len();
+
+                            sq.set(k, v);
+                            mq[k as usize] = v;
+
+                            // Extra get just to make sure set happened correctly
+                            assert_eq!(sq[k], mq[k as usize]);
+                        }
+                        Op::Get(k) => {
+                            assert_eq!(sq.get(k), mq.get(k as usize));
+                        }
+                        Op::Clear => {
+                            sq.clear();
+                            mq.clear();
+                        }
+                        Op::Flush => {
+                            sq.flush();
+                        }
+                        Op::Reset => {
+                            let serialized = sq.try_to_vec().unwrap();
+                            sq = Deque::deserialize(&mut serialized.as_slice()).unwrap();
+                        }
+                    }
+                    assert_eq!(sq.len() as usize, mq.len());
+                }
+            }
+
+            // After all operations, compare both queues
+            assert!(Iterator::eq(sq.iter(), mq.iter()));
+        }
+    }
+
+    #[test]
+    fn serialized_bytes() {
+        let mut queue = Deque::new(b"q".to_vec());
+        queue.push_back("Some data");
+        queue.push_front("Front");
+        let serialized = queue.try_to_vec().unwrap();
+
+        // Expected to serialize head, len then prefix
+        let mut expected_buf = Vec::new();
+        u32::MAX.serialize(&mut expected_buf).unwrap();
+        2u32.serialize(&mut expected_buf).unwrap();
+        (b"q".to_vec()).serialize(&mut expected_buf).unwrap();
+
+        assert_eq!(serialized, expected_buf);
+        drop(queue);
+        let queue = Deque::<String>::deserialize(&mut serialized.as_slice()).unwrap();
+        assert_eq!(queue[0], "Front");
+        assert_eq!(queue[1], "Some data");
+    }
+}
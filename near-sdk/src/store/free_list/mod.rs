@@ -1,11 +1,12 @@
 mod iter;
 pub use self::iter::{Drain, Iter, IterMut};
 
-use super::{Vector, ERR_INCONSISTENT_STATE};
+use super::{Inconsistency, Page, Vector, ERR_INCONSISTENT_STATE};
 use crate::{env, IntoStorageKey};
 
 use borsh::{BorshDeserialize, BorshSerialize};
 
+use std::collections::BTreeMap;
 use std::{fmt, mem};
 
 /// Index for value within a bucket.
@@ -63,6 +64,10 @@ enum Slot<T> {
     Empty { next_free: Option<FreeListIndex> },
 }
 
+fn expect_slot<T>(slot: Option<T>) -> T {
+    slot.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+}
+
 impl<T> Slot<T> {
     fn into_value(self) -> Option<T> {
         if let Slot::Occupied(value) = self {
@@ -210,6 +215,18 @@ where
         IterMut::new(self)
     }
 
+    /// Returns a page of the values of at most `limit` slots from `from_index`, with the index of
+    /// the slot to continue from. Empty slots count towards the limit, so that the cost of a page
+    /// is bounded.
+    pub fn paginate(&self, from_index: u32, limit: u32) -> Page<&T> {
+        Page::from_indices(from_index, limit, self.elements.len(), |i| {
+            match expect_slot(self.elements.get(i)) {
+                Slot::Occupied(value) => Some(value),
+                Slot::Empty { .. } => None,
+            }
+        })
+    }
+
     /// Creates a draining iterator that removes all elements from the FreeList and yields
     /// the removed items.
     ///
@@ -220,6 +237,109 @@ where
     pub fn drain(&mut self) -> Drain<T> {
         Drain::new(self)
     }
+
+    /// Checks the stored slots against the count of occupied slots and the list of removed slots,
+    /// returning the inconsistencies found. The cached changes are flushed first, and each slot is
+    /// then read again from storage.
+    ///
+    /// The slots stored after the end are read from the index following the last slot until one
+    /// is not stored, so slots stored after such a gap are not found.
+    ///
+    /// The list is not exposed on its own, but checked by the `validate` methods of the
+    /// collections it stores the elements or keys of, see [`Inconsistency`].
+    pub fn validate(&mut self) -> Vec<Inconsistency> {
+        self.validate_with(|_, _| {})
+    }
+
+    /// Same as [`FreeList::validate`], calling `occupied` with the index and value of each
+    /// occupied slot which could be deserialized, to check the values against other structures.
+    pub(crate) fn validate_with<F>(&mut self, mut occupied: F) -> Vec<Inconsistency>
+    where
+        F: FnMut(FreeListIndex, T),
+    {
+        self.elements.values.reset_cache();
+        let mut issues = Vec::new();
+        let mut count = 0;
+        // Removed slots, by index, with the index of the next removed slot they link to.
+        let mut free = BTreeMap::new();
+        for index in 0..self.elements.len() {
+            match self.elements.values.read_stored(index) {
+                None => issues.push(Inconsistency::MissingSlot { index }),
+                Some(Err(_)) => issues.push(Inconsistency::CorruptedSlot { index }),
+                Some(Ok(Slot::Occupied(value))) => {
+                    count += 1;
+                    occupied(FreeListIndex(index), value);
+                }
+                Some(Ok(Slot::Empty { next_free })) => {
+                    free.insert(index, next_free);
+                }
+            }
+        }
+        let mut index = self.elements.len();
+        while index < u32::MAX && self.elements.values.is_stored(index) {
+            issues.push(Inconsistency::OrphanedSlot { index });
+            index += 1;
+        }
+        if count != self.occupied_count {
+            issues.push(Inconsistency::LengthMismatch {
+                recorded: self.occupied_count,
+                actual: count,
+            });
+        }
+
+        // Each removed slot is taken out of `free` when reached, which also catches cycles.
+        let mut next = self.first_free;
+        while let Some(FreeListIndex(index)) = next {
+            match free.remove(&index) {
+                Some(next_free) => next = next_free,
+                None => {
+                    issues.push(Inconsistency::BrokenFreeList { index });
+                    break;
+                }
+            }
+        }
+        issues.extend(free.keys().map(|&index| Inconsistency::UnlinkedFreeSlot { index }));
+        issues
+    }
+
+    /// Repairs the list after [`FreeList::validate`] found inconsistencies, which are returned.
+    /// Like `validate`, it flushes the cached changes first, and nothing else is written if there
+    /// are no inconsistencies.
+    ///
+    /// Missing and corrupted slots become removed slots, the list of removed slots and the count
+    /// of occupied slots are rebuilt from the stored slots, and the slots found past the end are
+    /// removed from storage.
+    pub fn repair(&mut self) -> Vec<Inconsistency> {
+        let issues = self.validate();
+        if issues.is_empty() {
+            return issues;
+        }
+        let len = self.elements.len();
+        let mut first_free = None;
+        let mut count = 0;
+        // Linked from the end, so that the slots with the lowest indices are reused first.
+        for index in (0..len).rev() {
+            match self.elements.values.read_stored(index) {
+                Some(Ok(Slot::Occupied(_))) => count += 1,
+                Some(Ok(Slot::Empty { next_free })) if next_free == first_free => {
+                    first_free = Some(FreeListIndex(index));
+                }
+                _ => {
+                    self.elements.values.set(index, Some(Slot::Empty { next_free: first_free }));
+                    first_free = Some(FreeListIndex(index));
+                }
+            }
+        }
+        let mut index = len;
+        while index < u32::MAX && self.elements.values.is_stored(index) {
+            self.elements.values.set(index, None);
+            index += 1;
+        }
+        self.first_free = first_free;
+        self.occupied_count = count;
+        self.flush();
+        issues
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -407,4 +527,35 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn validate_and_repair() {
+        let key = |index: u32| [b"b".as_slice(), &index.to_le_bytes()].concat();
+        let mut bucket = FreeList::new(b"b");
+        bucket.extend(0u8..6);
+        bucket.remove(FreeListIndex(1));
+        bucket.remove(FreeListIndex(3));
+        assert!(bucket.validate().is_empty());
+
+        // Slot 3 links to itself, which leaves slot 1 out of the list.
+        let cycle = Slot::<u8>::Empty { next_free: Some(FreeListIndex(3)) };
+        env::storage_write(&key(3), &cycle.try_to_vec().unwrap());
+        env::storage_write(&key(4), &[7]);
+        bucket.occupied_count = 10;
+        let issues = [
+            Inconsistency::CorruptedSlot { index: 4 },
+            Inconsistency::LengthMismatch { recorded: 10, actual: 3 },
+            Inconsistency::BrokenFreeList { index: 3 },
+            Inconsistency::UnlinkedFreeSlot { index: 1 },
+        ];
+        assert_eq!(bucket.validate(), issues);
+
+        assert_eq!(bucket.repair(), issues);
+        assert!(bucket.validate().is_empty());
+        assert_eq!(bucket.iter().collect::<Vec<_>>(), [&0, &2, &5]);
+        assert_eq!(bucket.insert(8), FreeListIndex(1));
+        assert_eq!(bucket.insert(9), FreeListIndex(3));
+        assert_eq!(bucket.insert(10), FreeListIndex(4));
+        assert_eq!(bucket.insert(11), FreeListIndex(6));
+    }
 }
@@ -1,8 +1,11 @@
 use std::fmt;
+use std::marker::PhantomData;
 
+use borsh::maybestd::io;
 use borsh::{BorshDeserialize, BorshSerialize};
 use once_cell::unsync::OnceCell;
 
+use super::serializer::{Borsh, ValueDeserializer, ValueSerializer};
 use crate::utils::StableMap;
 use crate::{env, CacheEntry, EntryState, IntoStorageKey};
 
@@ -10,9 +13,9 @@ const ERR_ELEMENT_DESERIALIZATION: &str = "Cannot deserialize element";
 const ERR_ELEMENT_SERIALIZATION: &str = "Cannot serialize element";
 
 #[derive(BorshSerialize, BorshDeserialize)]
-pub(crate) struct IndexMap<T>
+pub(crate) struct IndexMap<T, S = Borsh>
 where
-    T: BorshSerialize,
+    S: ValueSerializer<T>,
 {
     pub(crate) prefix: Box<[u8]>,
     /// Cache for loads and intermediate changes to the underlying index map.
@@ -23,9 +26,11 @@ where
     /// Some functionality would be different from tests to Wasm if exceeding 32-bit length.
     #[borsh_skip]
     pub(crate) cache: StableMap<u32, OnceCell<CacheEntry<T>>>,
+    #[borsh_skip]
+    serializer: PhantomData<S>,
 }
 
-impl<T> IndexMap<T>
+impl<T> IndexMap<T, Borsh>
 where
     T: BorshSerialize,
 {
@@ -33,11 +38,28 @@ where
     ///
     /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
     /// storing and looking up values in storage to ensure no collisions with other collections.
-    pub fn new<S>(prefix: S) -> Self
+    pub fn new<P>(prefix: P) -> Self
+    where
+        P: IntoStorageKey,
+    {
+        Self::with_serializer(prefix)
+    }
+}
+
+impl<T, S> IndexMap<T, S>
+where
+    S: ValueSerializer<T>,
+{
+    /// Create new index map which serializes its values with `S`, see [`IndexMap::new`].
+    pub fn with_serializer<P>(prefix: P) -> Self
     where
-        S: IntoStorageKey,
+        P: IntoStorageKey,
     {
-        Self { prefix: prefix.into_storage_key().into_boxed_slice(), cache: Default::default() }
+        Self {
+            prefix: prefix.into_storage_key().into_boxed_slice(),
+            cache: Default::default(),
+            serializer: PhantomData,
+        }
     }
 
     fn index_to_lookup_key(prefix: &[u8], index: u32, buf: &mut Vec<u8>) {
@@ -58,7 +80,7 @@ where
                     match v.value().as_ref() {
                         Some(modified) => {
                             buf.clear();
-                            BorshSerialize::serialize(modified, &mut buf)
+                            S::serialize_value(modified, &mut buf)
                                 .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
                             env::storage_write(&key_buf, &buf);
                         }
@@ -76,6 +98,19 @@ where
         }
     }
 
+    /// Flushes the cache and drops the cached values, so that they are read from storage again.
+    pub(crate) fn reset_cache(&mut self) {
+        self.flush();
+        self.cache.inner().clear();
+    }
+
+    /// Returns `true` if a value is stored at `index`, without checking the cache.
+    pub(crate) fn is_stored(&self, index: u32) -> bool {
+        let mut key = Vec::with_capacity(self.prefix.len() + 4);
+        Self::index_to_lookup_key(&self.prefix, index, &mut key);
+        env::storage_has_key(&key)
+    }
+
     /// Sets a value at a given index to the value provided. If none is provided, this index will
     /// be removed from storage.
     pub fn set(&mut self, index: u32, value: Option<T>) {
@@ -89,15 +124,23 @@ where
     }
 }
 
-impl<T> IndexMap<T>
+impl<T, S> IndexMap<T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     fn deserialize_element(raw_element: &[u8]) -> T {
-        T::try_from_slice(raw_element)
+        S::deserialize_value(raw_element)
             .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_DESERIALIZATION))
     }
 
+    /// Reads the element at `index` from storage, without checking or filling the cache, and
+    /// returns the deserialization error instead of panicking on it.
+    pub(crate) fn read_stored(&self, index: u32) -> Option<io::Result<T>> {
+        let mut key = Vec::with_capacity(self.prefix.len() + 4);
+        Self::index_to_lookup_key(&self.prefix, index, &mut key);
+        env::storage_read(&key).map(|bytes| S::deserialize_value(&bytes))
+    }
+
     /// Returns the element by index or `None` if it is not present.
     pub fn get(&self, index: u32) -> Option<&T> {
         let entry = self.cache.get(index).get_or_init(|| {
@@ -153,16 +196,17 @@ where
     }
 }
 
-impl<T> fmt::Debug for IndexMap<T>
+impl<T, S> fmt::Debug for IndexMap<T, S>
 where
-    T: BorshSerialize + BorshDeserialize + fmt::Debug,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
+    T: fmt::Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("IndexMap").field("prefix", &self.prefix).finish()
     }
 }
 
-#[cfg(all(not(target_arch = "wasm32"), feature = "unstable"))]
+#[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
     use super::IndexMap;
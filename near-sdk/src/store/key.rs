@@ -1,21 +1,58 @@
 use borsh::BorshSerialize;
 
-use crate::env;
-
-mod private {
-    /// Seal `ToKey` implementations to limit usage to the builtin implementations
-    pub trait Sealed {}
-
-    impl Sealed for super::Sha256 {}
-    impl Sealed for super::Keccak256 {}
-    impl Sealed for super::Identity {}
-}
+use crate::{env, AccountId};
 
 /// Trait used to generate keys to store data based on a serializable structure.
-pub trait ToKey: self::private::Sealed {
+///
+/// Besides the builtin [`Sha256`], [`Keccak256`] and [`Identity`], contracts can implement this
+/// trait to derive the storage keys of the maps in their own way. An implementation must:
+///
+/// - be deterministic: the same prefix and key always give the same storage key, across calls
+///   and contract upgrades, otherwise stored values are lost;
+/// - give distinct storage keys for distinct prefixes or serialized keys, otherwise entries of the
+///   same map, or of different collections, overwrite each other.
+///
+/// [`test_utils::check_to_key`](crate::test_utils::check_to_key) can be used in unit tests to
+/// check these requirements over a set of sample keys.
+///
+/// # Examples
+///
+/// Storage keys truncated to 16 bytes, to save storage when collisions are not a concern:
+///
+/// ```
+/// use near_sdk::borsh::BorshSerialize;
+/// use near_sdk::env;
+/// use near_sdk::store::key::ToKey;
+/// use near_sdk::store::LookupMap;
+///
+/// enum Sha256Truncated {}
+///
+/// impl ToKey for Sha256Truncated {
+///     type KeyType = [u8; 16];
+///
+///     fn to_key<Q: ?Sized>(prefix: &[u8], key: &Q, buffer: &mut Vec<u8>) -> Self::KeyType
+///     where
+///         Q: BorshSerialize,
+///     {
+///         buffer.extend(prefix);
+///         key.serialize(buffer).unwrap_or_else(|_| env::abort());
+///
+///         let mut truncated = [0; 16];
+///         truncated.copy_from_slice(&env::sha256_array(buffer)[..16]);
+///         truncated
+///     }
+/// }
+///
+/// # near_sdk::test_utils::check_to_key::<Sha256Truncated, _>(&["alice", "bob"]);
+/// let mut map: LookupMap<String, u64, Sha256Truncated> = LookupMap::with_hasher(b"m");
+/// map.insert("alice".to_string(), 1);
+/// ```
+pub trait ToKey {
     /// Output type for the generated lookup key.
     type KeyType: AsRef<[u8]>;
 
+    /// Generates the storage key of `key` in the collection with the given `prefix`. The
+    /// `buffer` is an empty vector which can be used to serialize the key without allocating.
     fn to_key<Q: ?Sized>(prefix: &[u8], key: &Q, buffer: &mut Vec<u8>) -> Self::KeyType
     where
         Q: BorshSerialize;
@@ -76,3 +113,150 @@ impl ToKey for Identity {
         buffer.clone()
     }
 }
+
+/// Key type which can be encoded to bytes in the same order as the keys, so that the entries of a
+/// [`SortedMap`](super::SortedMap) (`unstable`) are stored in key order and can be read with range
+/// queries over the raw storage.
+///
+/// For any keys `a` and `b`, the encoding of `a` must be lexicographically less than the encoding
+/// of `b` if and only if `a < b`. Unsigned integers are encoded as big endian, signed integers as
+/// big endian with the sign bit flipped, and strings and byte vectors as their bytes.
+pub trait OrderedKey: Ord + Sized {
+    /// Appends the encoded key to `buffer`.
+    fn encode_ordered(&self, buffer: &mut Vec<u8>);
+
+    /// Decodes a key from all of the given `bytes`, or returns `None` if they are not the
+    /// encoding of a key.
+    fn decode_ordered(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! impl_ordered_key_for_int {
+    ($($ty:ty),*) => {
+        $(
+            impl OrderedKey for $ty {
+                fn encode_ordered(&self, buffer: &mut Vec<u8>) {
+                    // Flipping the sign bit orders negative numbers before positive ones, it is
+                    // a no-op for unsigned integers.
+                    buffer.extend_from_slice(&(self ^ <$ty>::MIN).to_be_bytes());
+                }
+
+                fn decode_ordered(bytes: &[u8]) -> Option<Self> {
+                    let bytes = bytes.try_into().ok()?;
+                    Some(<$ty>::from_be_bytes(bytes) ^ <$ty>::MIN)
+                }
+            }
+        )*
+    };
+}
+
+impl_ordered_key_for_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl OrderedKey for Vec<u8> {
+    fn encode_ordered(&self, buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(self);
+    }
+
+    fn decode_ordered(bytes: &[u8]) -> Option<Self> {
+        Some(bytes.to_vec())
+    }
+}
+
+impl<const N: usize> OrderedKey for [u8; N] {
+    fn encode_ordered(&self, buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(self);
+    }
+
+    fn decode_ordered(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok()
+    }
+}
+
+impl OrderedKey for String {
+    fn encode_ordered(&self, buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode_ordered(bytes: &[u8]) -> Option<Self> {
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+impl OrderedKey for AccountId {
+    fn encode_ordered(&self, buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode_ordered(bytes: &[u8]) -> Option<Self> {
+        core::str::from_utf8(bytes).ok()?.parse().ok()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::{Identity, Keccak256, Sha256, ToKey};
+    use crate::store::LookupMap;
+    use crate::test_utils::{check_to_key, check_to_key_order};
+    use borsh::BorshSerialize;
+
+    /// Order preserving keys for fixed size integers, which are serialized as little endian.
+    enum BigEndian {}
+
+    impl ToKey for BigEndian {
+        type KeyType = Vec<u8>;
+
+        fn to_key<Q: ?Sized>(prefix: &[u8], key: &Q, buffer: &mut Vec<u8>) -> Self::KeyType
+        where
+            Q: BorshSerialize,
+        {
+            key.serialize(buffer).unwrap();
+            buffer.reverse();
+            [prefix, buffer].concat()
+        }
+    }
+
+    #[test]
+    fn builtin_hashers() {
+        let keys = ["", "a", "ab", "b"];
+        check_to_key::<Sha256, _>(&keys);
+        check_to_key::<Keccak256, _>(&keys);
+        check_to_key::<Identity, _>(&keys);
+    }
+
+    #[test]
+    fn custom_order_preserving() {
+        let keys = [0u32, 1, 255, 256, 70_000, u32::MAX];
+        check_to_key::<BigEndian, _>(&keys);
+        check_to_key_order::<BigEndian, _>(&keys);
+
+        let mut map: LookupMap<u32, u8, BigEndian> = LookupMap::with_hasher(b"m");
+        map.insert(256, 1);
+        map.flush();
+        assert_eq!(crate::env::storage_read(&[b'm', 0, 0, 1, 0]), Some(1u8.try_to_vec().unwrap()));
+    }
+
+    #[test]
+    #[should_panic(expected = "to_key does not preserve the order")]
+    fn identity_is_not_order_preserving() {
+        check_to_key_order::<Identity, _>(&[1u32, 256]);
+    }
+
+    #[test]
+    #[should_panic(expected = "to_key is not deterministic")]
+    fn non_deterministic() {
+        enum Capacity {}
+
+        impl ToKey for Capacity {
+            type KeyType = Vec<u8>;
+
+            fn to_key<Q: ?Sized>(_: &[u8], _: &Q, buffer: &mut Vec<u8>) -> Self::KeyType
+            where
+                Q: BorshSerialize,
+            {
+                buffer.capacity().to_le_bytes().to_vec()
+            }
+        }
+
+        check_to_key::<Capacity, _>(&[1u8]);
+    }
+}
@@ -11,11 +11,12 @@ use once_cell::unsync::OnceCell;
 
 use crate::collections::ERR_INCONSISTENT_STATE;
 use crate::env;
+use crate::store::serializer::{Borsh, ValueDeserializer, ValueSerializer};
 use crate::utils::{CacheEntry, EntryState};
 use crate::IntoStorageKey;
 
-const ERR_VALUE_SERIALIZATION: &str = "Cannot serialize value with Borsh";
-const ERR_VALUE_DESERIALIZATION: &str = "Cannot deserialize value with Borsh";
+const ERR_VALUE_SERIALIZATION: &str = "Cannot serialize value";
+const ERR_VALUE_DESERIALIZATION: &str = "Cannot deserialize value";
 const ERR_NOT_FOUND: &str = "No value found for the given key";
 
 fn expect_key_exists<T>(val: Option<T>) -> T {
@@ -26,21 +27,23 @@ fn expect_consistent_state<T>(val: Option<T>) -> T {
     val.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
 }
 
-pub(crate) fn load_and_deserialize<T>(key: &[u8]) -> CacheEntry<T>
+pub(crate) fn load_and_deserialize<T, S>(key: &[u8]) -> CacheEntry<T>
 where
-    T: BorshDeserialize,
+    S: ValueDeserializer<T>,
 {
     let bytes = expect_key_exists(env::storage_read(key));
     let val =
-        T::try_from_slice(&bytes).unwrap_or_else(|_| env::panic_str(ERR_VALUE_DESERIALIZATION));
+        S::deserialize_value(&bytes).unwrap_or_else(|_| env::panic_str(ERR_VALUE_DESERIALIZATION));
     CacheEntry::new_cached(Some(val))
 }
 
-pub(crate) fn serialize_and_store<T>(key: &[u8], value: &T)
+pub(crate) fn serialize_and_store<T, S>(key: &[u8], value: &T)
 where
-    T: BorshSerialize,
+    S: ValueSerializer<T>,
 {
-    let serialized = value.try_to_vec().unwrap_or_else(|_| env::panic_str(ERR_VALUE_SERIALIZATION));
+    let mut serialized = Vec::new();
+    S::serialize_value(value, &mut serialized)
+        .unwrap_or_else(|_| env::panic_str(ERR_VALUE_SERIALIZATION));
     env::storage_write(key, &serialized);
 }
 
@@ -59,6 +62,27 @@ where
 /// *a = "new string".to_string();
 /// assert_eq!(a.get(), "new string");
 /// ```
+///
+/// # Migrating from `collections::LazyOption`
+///
+/// There is no `Lazy` in [`collections`](crate::collections), but a
+/// [`collections::LazyOption`](crate::collections::LazyOption) which always holds a value has the
+/// same storage layout, so its value can be read by changing the type of the field in the
+/// contract state:
+///
+/// ```
+/// use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+/// use near_sdk::{collections, store};
+///
+/// let old = collections::LazyOption::new(b"o", Some(&"a".to_string()));
+///
+/// let state = old.try_to_vec().unwrap();
+/// let new: store::Lazy<String> = BorshDeserialize::try_from_slice(&state).unwrap();
+/// assert_eq!(*new, "a");
+/// ```
+///
+/// Reading a `Lazy` without a value fails the call, so use [`LazyOption`](super::LazyOption) for
+/// a `collections::LazyOption` which can be `None`.
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct Lazy<T>
 where
@@ -109,7 +133,7 @@ where
             if v.is_modified() {
                 // Value was modified, serialize and put the serialized bytes in storage.
                 let value = expect_consistent_state(v.value().as_ref());
-                serialize_and_store(&self.storage_key, value);
+                serialize_and_store::<T, Borsh>(&self.storage_key, value);
 
                 // Replaces cache entry state to cached because the value in memory matches the
                 // stored value. This avoids writing the same value twice.
@@ -129,7 +153,7 @@ where
     ///
     /// This function will panic if the cache is not loaded and the value at the key does not exist.
     pub fn get(&self) -> &T {
-        let entry = self.cache.get_or_init(|| load_and_deserialize(&self.storage_key));
+        let entry = self.cache.get_or_init(|| load_and_deserialize::<T, Borsh>(&self.storage_key));
 
         expect_consistent_state(entry.value().as_ref())
     }
@@ -140,7 +164,7 @@ where
     ///
     /// This function will panic if the cache is not loaded and the value at the key does not exist.
     pub fn get_mut(&mut self) -> &mut T {
-        self.cache.get_or_init(|| load_and_deserialize(&self.storage_key));
+        self.cache.get_or_init(|| load_and_deserialize::<T, Borsh>(&self.storage_key));
         let entry = self.cache.get_mut().unwrap_or_else(|| env::abort());
 
         expect_consistent_state(entry.value_mut().as_mut())
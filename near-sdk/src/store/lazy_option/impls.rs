@@ -1,19 +1,18 @@
-use borsh::{BorshDeserialize, BorshSerialize};
-
 use super::LazyOption;
+use crate::store::serializer::{ValueDeserializer, ValueSerializer};
 
-impl<T> Drop for LazyOption<T>
+impl<T, S> Drop for LazyOption<T, S>
 where
-    T: BorshSerialize,
+    S: ValueSerializer<T>,
 {
     fn drop(&mut self) {
         self.flush()
     }
 }
 
-impl<T> core::ops::Deref for LazyOption<T>
+impl<T, S> core::ops::Deref for LazyOption<T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     type Target = Option<T>;
 
@@ -22,18 +21,19 @@ where
     }
 }
 
-impl<T> core::ops::DerefMut for LazyOption<T>
+impl<T, S> core::ops::DerefMut for LazyOption<T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     fn deref_mut(&mut self) -> &mut Self::Target {
         Self::get_mut(self)
     }
 }
 
-impl<T> std::fmt::Debug for LazyOption<T>
+impl<T, S> std::fmt::Debug for LazyOption<T, S>
 where
-    T: std::fmt::Debug + BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
+    T: std::fmt::Debug,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if cfg!(feature = "expensive-debug") {
@@ -1,10 +1,13 @@
 mod impls;
 
+use std::marker::PhantomData;
+
 use borsh::{BorshDeserialize, BorshSerialize};
 use once_cell::unsync::OnceCell;
 
 use crate::env;
 use crate::store::lazy::{load_and_deserialize, serialize_and_store};
+use crate::store::serializer::{Borsh, ValueDeserializer, ValueSerializer};
 use crate::utils::{CacheEntry, EntryState};
 use crate::IntoStorageKey;
 
@@ -30,11 +33,35 @@ use crate::IntoStorageKey;
 /// assert_eq!(old_str, Some("new value".to_owned()));
 /// assert_eq!(a.get(), &Some("new new value".to_owned()));
 /// ```
+///
+/// # Migrating from `collections::LazyOption`
+///
+/// The storage layout of this type is compatible with
+/// [`collections::LazyOption`](crate::collections::LazyOption), so an existing value can be read
+/// by changing the type of the field in the contract state, without any data migration:
+///
+/// ```
+/// use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+/// use near_sdk::{collections, store};
+///
+/// let old = collections::LazyOption::new(b"o", Some(&"a".to_string()));
+///
+/// let state = old.try_to_vec().unwrap();
+/// let mut new: store::LazyOption<String> = BorshDeserialize::try_from_slice(&state).unwrap();
+/// assert_eq!(new.get(), &Some("a".to_string()));
+///
+/// *new = Some("b".to_string());
+/// ```
+///
+/// The value is accessed by reference and written to storage when the option is
+/// [`flush`](Self::flush)ed or dropped, so `get()` becomes `get().clone()` and `set(&v)` becomes
+/// `set(Some(v))`.
+///
 /// [`Deref`]: std::ops::Deref
 #[derive(BorshSerialize, BorshDeserialize)]
-pub struct LazyOption<T>
+pub struct LazyOption<T, S = Borsh>
 where
-    T: BorshSerialize,
+    S: ValueSerializer<T>,
 {
     /// Key bytes to index the contract's storage.
     prefix: Box<[u8]>,
@@ -42,9 +69,12 @@ where
     /// Cached value which is lazily loaded and deserialized from storage.
     #[borsh_skip]
     cache: OnceCell<CacheEntry<T>>,
+
+    #[borsh_skip]
+    serializer: PhantomData<S>,
 }
 
-impl<T> LazyOption<T>
+impl<T> LazyOption<T, Borsh>
 where
     T: BorshSerialize,
 {
@@ -52,16 +82,46 @@ where
     ///
     /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
     /// storing and looking up values in storage to ensure no collisions with other collections.
-    pub fn new<S>(prefix: S, value: Option<T>) -> Self
+    pub fn new<P>(prefix: P, value: Option<T>) -> Self
     where
-        S: IntoStorageKey,
+        P: IntoStorageKey,
+    {
+        Self::with_serializer(prefix, value)
+    }
+}
+
+impl<T, S> LazyOption<T, S>
+where
+    S: ValueSerializer<T>,
+{
+    /// Create a new lazy option with the given `prefix` and the initial value, which serializes
+    /// the value with the serializer `S`. See the [`serializer`](crate::store::serializer) module
+    /// for the available formats.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::serializer::Raw;
+    /// use near_sdk::store::LazyOption;
+    ///
+    /// let mut image: LazyOption<Vec<u8>, Raw> = LazyOption::with_serializer(b"i", None);
+    /// image.set(Some(vec![0x89, 0x50, 0x4e, 0x47]));
+    /// image.flush();
+    /// assert_eq!(near_sdk::env::storage_read(b"i"), Some(vec![0x89, 0x50, 0x4e, 0x47]));
+    /// ```
+    pub fn with_serializer<P>(prefix: P, value: Option<T>) -> Self
+    where
+        P: IntoStorageKey,
     {
         let cache = match value {
             Some(value) => CacheEntry::new_modified(Some(value)),
             None => CacheEntry::new_cached(None),
         };
 
-        Self { prefix: prefix.into_storage_key().into_boxed_slice(), cache: OnceCell::from(cache) }
+        Self {
+            prefix: prefix.into_storage_key().into_boxed_slice(),
+            cache: OnceCell::from(cache),
+            serializer: PhantomData,
+        }
     }
 
     /// Updates the value with a new value. This does not load the current value from storage.
@@ -86,7 +146,7 @@ where
             }
 
             match v.value().as_ref() {
-                Some(value) => serialize_and_store(&self.prefix, value),
+                Some(value) => serialize_and_store::<T, S>(&self.prefix, value),
                 None => {
                     env::storage_remove(&self.prefix);
                 }
@@ -99,15 +159,15 @@ where
     }
 }
 
-impl<T> LazyOption<T>
+impl<T, S> LazyOption<T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     /// Returns a reference to the lazily loaded optional.
     /// The load from storage only happens once, and if the value is already cached, it will not
     /// be reloaded.
     pub fn get(&self) -> &Option<T> {
-        let entry = self.cache.get_or_init(|| load_and_deserialize(&self.prefix));
+        let entry = self.cache.get_or_init(|| load_and_deserialize::<T, S>(&self.prefix));
         entry.value()
     }
 
@@ -115,7 +175,7 @@ where
     /// The load from storage only happens once, and if the value is already cached, it will not
     /// be reloaded.
     pub fn get_mut(&mut self) -> &mut Option<T> {
-        self.cache.get_or_init(|| load_and_deserialize(&self.prefix));
+        self.cache.get_or_init(|| load_and_deserialize::<T, S>(&self.prefix));
         let entry = self.cache.get_mut().unwrap_or_else(|| env::abort());
         entry.value_mut()
     }
@@ -162,6 +222,14 @@ mod tests {
         assert!(!env::storage_has_key(b"a"));
     }
 
+    #[test]
+    pub fn collections_lazy_option_compat() {
+        let old = crate::collections::LazyOption::new(b"o", Some(&42u32));
+
+        let new = LazyOption::<u32>::try_from_slice(&old.try_to_vec().unwrap()).unwrap();
+        assert_eq!(new.get(), &Some(42));
+    }
+
     #[test]
     pub fn test_debug() {
         let mut lazy_option = LazyOption::new(b"m", None);
@@ -1,15 +1,16 @@
 use std::borrow::Borrow;
 
-use borsh::{BorshDeserialize, BorshSerialize};
+use borsh::BorshSerialize;
 
 use super::{LookupMap, ToKey, ERR_NOT_EXIST};
 use crate::env;
+use crate::store::serializer::{ValueDeserializer, ValueSerializer};
 
-impl<K, V, H> Extend<(K, V)> for LookupMap<K, V, H>
+impl<K, V, H, S> Extend<(K, V)> for LookupMap<K, V, H, S>
 where
     K: BorshSerialize + Ord,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
     fn extend<I>(&mut self, iter: I)
     where
@@ -21,11 +22,11 @@ where
     }
 }
 
-impl<K, V, H, Q: ?Sized> core::ops::Index<&Q> for LookupMap<K, V, H>
+impl<K, V, H, S, Q: ?Sized> core::ops::Index<&Q> for LookupMap<K, V, H, S>
 where
     K: BorshSerialize + Ord + Borrow<Q>,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 
     Q: BorshSerialize + ToOwned<Owned = K>,
 {
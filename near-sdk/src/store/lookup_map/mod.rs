@@ -3,14 +3,18 @@ mod impls;
 
 use std::borrow::Borrow;
 use std::fmt;
+use std::marker::PhantomData;
 
+use borsh::maybestd::io;
 use borsh::{BorshDeserialize, BorshSerialize};
 use once_cell::unsync::OnceCell;
 
+use super::nested::{self, NestedCollection};
 use super::ERR_NOT_EXIST;
 use crate::store::key::{Identity, ToKey};
+use crate::store::serializer::{Borsh, ValueDeserializer, ValueSerializer};
 use crate::utils::{EntryState, StableMap};
-use crate::{env, CacheEntry, IntoStorageKey};
+use crate::{collections, env, CacheEntry, IntoStorageKey};
 
 pub use entry::{Entry, OccupiedEntry, VacantEntry};
 
@@ -74,13 +78,38 @@ const ERR_ELEMENT_SERIALIZATION: &str = "Cannot serialize element";
 /// *stat += random_stat_buff();
 /// ```
 ///
+/// # Migrating from `collections::LookupMap`
+///
+/// With the default [`Identity`] hasher, this map uses the same storage layout as
+/// [`collections::LookupMap`](crate::collections::LookupMap), including the serialized map
+/// itself. Changing the type of the field in the contract state is enough, and [`from_legacy`]
+/// converts a legacy map in code, without rewriting any entry:
+///
+/// ```
+/// use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+/// use near_sdk::{collections, store};
+///
+/// let mut old: collections::LookupMap<u32, String> = collections::LookupMap::new(b"m");
+/// old.insert(&1, &"a".to_string());
+///
+/// let state = old.try_to_vec().unwrap();
+/// let new: store::LookupMap<u32, String> = BorshDeserialize::try_from_slice(&state).unwrap();
+/// assert_eq!(new.get(&1), Some(&"a".to_string()));
+/// ```
+///
+/// The values are accessed by reference and modifications are written to storage when the map is
+/// [`flush`]ed or dropped, so `get(&k)` becomes `get(&k).cloned()` and `insert(&k, &v)` becomes
+/// `insert(k, v)`.
+///
+/// [`from_legacy`]: Self::from_legacy
+/// [`flush`]: Self::flush
 /// [`with_hasher`]: Self::with_hasher
 #[derive(BorshSerialize, BorshDeserialize)]
-pub struct LookupMap<K, V, H = Identity>
+pub struct LookupMap<K, V, H = Identity, S = Borsh>
 where
     K: BorshSerialize + Ord,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
     prefix: Box<[u8]>,
     /// Cache for loads and intermediate changes to the underlying vector.
@@ -88,6 +117,8 @@ where
     /// invalidated.
     #[borsh_skip]
     cache: StableMap<K, EntryAndHash<V, H::KeyType>>,
+    #[borsh_skip]
+    serializer: PhantomData<S>,
 }
 
 struct EntryAndHash<V, T> {
@@ -101,29 +132,29 @@ impl<V, T> Default for EntryAndHash<V, T> {
     }
 }
 
-impl<K, V, H> Drop for LookupMap<K, V, H>
+impl<K, V, H, S> Drop for LookupMap<K, V, H, S>
 where
     K: BorshSerialize + Ord,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
     fn drop(&mut self) {
         self.flush()
     }
 }
 
-impl<K, V, H> fmt::Debug for LookupMap<K, V, H>
+impl<K, V, H, S> fmt::Debug for LookupMap<K, V, H, S>
 where
     K: BorshSerialize + Ord,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("LookupMap").field("prefix", &self.prefix).finish()
     }
 }
 
-impl<K, V> LookupMap<K, V, Identity>
+impl<K, V> LookupMap<K, V, Identity, Borsh>
 where
     K: BorshSerialize + Ord,
     V: BorshSerialize,
@@ -147,13 +178,31 @@ where
     {
         Self::with_hasher(prefix)
     }
+
+    /// Converts `legacy` into a map with the same prefix and entries. Both maps use the same
+    /// storage layout, so no entry is rewritten.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::{collections, store};
+    ///
+    /// let mut legacy: collections::LookupMap<u32, String> = collections::LookupMap::new(b"m");
+    /// legacy.insert(&1, &"a".to_string());
+    ///
+    /// let map = store::LookupMap::from_legacy(legacy);
+    /// assert_eq!(map.get(&1), Some(&"a".to_string()));
+    /// ```
+    pub fn from_legacy(legacy: collections::LookupMap<K, V>) -> Self {
+        Self::new(legacy.prefix())
+    }
 }
 
-impl<K, V, H> LookupMap<K, V, H>
+impl<K, V, H, S> LookupMap<K, V, H, S>
 where
     K: BorshSerialize + Ord,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
     /// Initialize a [`LookupMap`] with a custom hash function.
     ///
@@ -163,11 +212,23 @@ where
     ///
     /// let map = LookupMap::<String, String, Keccak256>::with_hasher(b"m");
     /// ```
-    pub fn with_hasher<S>(prefix: S) -> Self
+    ///
+    /// The serializer of the values can be set in the same way, see the
+    /// [`serializer`](crate::store::serializer) module:
+    /// ```
+    /// use near_sdk::store::{LookupMap, key::Identity, serializer::Json};
+    ///
+    /// let map = LookupMap::<String, String, Identity, Json>::with_hasher(b"m");
+    /// ```
+    pub fn with_hasher<P>(prefix: P) -> Self
     where
-        S: IntoStorageKey,
+        P: IntoStorageKey,
     {
-        Self { prefix: prefix.into_storage_key().into_boxed_slice(), cache: Default::default() }
+        Self {
+            prefix: prefix.into_storage_key().into_boxed_slice(),
+            cache: Default::default(),
+            serializer: PhantomData,
+        }
     }
 
     /// Overwrites the current value for the given key.
@@ -199,16 +260,43 @@ where
             }
         }
     }
+
+    /// Returns the storage prefix of the collection nested under `key`, derived from the prefix
+    /// of the map and the key. Use this to create a collection to be stored as the value of
+    /// `key`, or use [`LookupMap::get_or_insert_child`] which does it for you.
+    ///
+    /// With the default [`Identity`] hasher, an entry of the map can overwrite a value of a child
+    /// if the borsh serialization of its key starts with `0xff` and is 33 bytes or longer, like a
+    /// string whose length is 255 modulo 256. This can't happen when the map hashes its keys, like
+    /// with [`Sha256`](crate::store::key::Sha256).
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::{LookupMap, Vector};
+    ///
+    /// let mut map: LookupMap<String, Vector<u8>> = LookupMap::new(b"m");
+    ///
+    /// let prefix = map.child_prefix("alice");
+    /// map.insert("alice".to_string(), Vector::new(prefix));
+    /// assert_ne!(map.child_prefix("alice"), map.child_prefix("bob"));
+    /// ```
+    pub fn child_prefix<Q: ?Sized>(&self, key: &Q) -> Vec<u8>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize,
+    {
+        nested::child_prefix(&self.prefix, key)
+    }
 }
 
-impl<K, V, H> LookupMap<K, V, H>
+impl<K, V, H, S> LookupMap<K, V, H, S>
 where
     K: BorshSerialize + Ord,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
     fn deserialize_element(bytes: &[u8]) -> V {
-        V::try_from_slice(bytes).unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_DESERIALIZATION))
+        S::deserialize_value(bytes).unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_DESERIALIZATION))
     }
 
     fn load_element<Q: ?Sized>(prefix: &[u8], key: &Q) -> (H::KeyType, Option<V>)
@@ -221,6 +309,17 @@ where
         (key, storage_bytes.as_deref().map(Self::deserialize_element))
     }
 
+    /// Reads the value of the key from storage, without checking or filling the cache, and
+    /// returns the deserialization error instead of panicking on it.
+    pub(crate) fn read_stored<Q: ?Sized>(&self, k: &Q) -> Option<io::Result<V>>
+    where
+        Q: BorshSerialize,
+        K: Borrow<Q>,
+    {
+        let key = H::to_key(&self.prefix, k, &mut Vec::new());
+        env::storage_read(key.as_ref()).map(|bytes| S::deserialize_value(&bytes))
+    }
+
     /// Returns a reference to the value corresponding to the key.
     ///
     /// The key may be any borrowed form of the map's key type, but
@@ -415,13 +514,69 @@ where
             Entry::Vacant(VacantEntry { key, entry })
         }
     }
+
+    /// Returns a mutable reference to the collection nested under `key`, creating it with the
+    /// prefix from [`LookupMap::child_prefix`] if the entry is vacant.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::{LookupMap, UnorderedSet};
+    ///
+    /// let mut tokens_per_owner: LookupMap<String, UnorderedSet<u64>> = LookupMap::new(b"t");
+    ///
+    /// tokens_per_owner.get_or_insert_child("alice".to_string()).insert(1);
+    /// tokens_per_owner.get_or_insert_child("alice".to_string()).insert(2);
+    /// tokens_per_owner.get_or_insert_child("bob".to_string()).insert(1);
+    ///
+    /// assert_eq!(tokens_per_owner["alice"].len(), 2);
+    /// assert_eq!(tokens_per_owner["bob"].len(), 1);
+    /// ```
+    pub fn get_or_insert_child(&mut self, key: K) -> &mut V
+    where
+        K: Clone,
+        V: NestedCollection,
+    {
+        let prefix = self.child_prefix(&key);
+        self.entry(key).or_insert_with(|| V::new_nested(prefix))
+    }
+
+    /// Removes the collection nested under `key` from the map and clears all of its values from
+    /// storage. Returns `true` if the key was in the map.
+    ///
+    /// Removing the entry with [`LookupMap::remove`] only removes the metadata of the nested
+    /// collection, and leaves its values in storage.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::{LookupMap, Vector};
+    ///
+    /// let mut map: LookupMap<String, Vector<u8>> = LookupMap::new(b"m");
+    /// map.get_or_insert_child("alice".to_string()).extend([1, 2, 3]);
+    ///
+    /// assert!(map.remove_and_clear("alice"));
+    /// assert!(!map.remove_and_clear("alice"));
+    /// ```
+    pub fn remove_and_clear<Q: ?Sized>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+        V: NestedCollection,
+    {
+        match self.remove(key) {
+            Some(mut child) => {
+                child.clear_nested();
+                true
+            }
+            None => false,
+        }
+    }
 }
 
-impl<K, V, H> LookupMap<K, V, H>
+impl<K, V, H, S> LookupMap<K, V, H, S>
 where
     K: BorshSerialize + Ord,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
     /// Flushes the intermediate values of the map before this is called when the structure is
     /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
@@ -439,7 +594,7 @@ where
                     match val.value().as_ref() {
                         Some(modified) => {
                             buf.clear();
-                            BorshSerialize::serialize(modified, &mut buf)
+                            S::serialize_value(modified, &mut buf)
                                 .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
                             env::storage_write(key.as_ref(), &buf);
                         }
@@ -456,6 +611,12 @@ where
             }
         }
     }
+
+    /// Flushes the cache and drops the cached values, so that they are read from storage again.
+    pub(crate) fn reset_cache(&mut self) {
+        self.flush();
+        self.cache.inner().clear();
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -43,6 +43,24 @@ use std::marker::PhantomData;
 /// assert!(!books.contains("The Odyssey"));
 /// ```
 ///
+/// # Migrating from `collections::LookupSet`
+///
+/// With the default [`Identity`] hasher, this set uses the same storage layout as
+/// [`collections::LookupSet`](crate::collections::LookupSet), including the serialized set
+/// itself, so only the type of the field in the contract state has to change:
+///
+/// ```
+/// use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+/// use near_sdk::{collections, store};
+///
+/// let mut old: collections::LookupSet<String> = collections::LookupSet::new(b"s");
+/// old.insert(&"a".to_string());
+///
+/// let state = old.try_to_vec().unwrap();
+/// let new: store::LookupSet<String> = BorshDeserialize::try_from_slice(&state).unwrap();
+/// assert!(new.contains("a"));
+/// ```
+///
 /// [`with_hasher`]: Self::with_hasher
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct LookupSet<T, H = Identity>
@@ -0,0 +1,367 @@
+//! Conversion of the [`collections`](crate::collections) to the `store` collections, across
+//! several transactions.
+//!
+//! The storage layouts of [`Vector`], [`UnorderedMap`] and [`TreeMap`] differ from their
+//! `collections` equivalents, so their entries have to be rewritten. `from_legacy` takes the
+//! legacy collection and returns a [`Migration`], which moves the entries to the new collection
+//! in batches, under the prefix of the legacy collection. The migration is stored in the contract
+//! state between the batches, and the number of entries left to move is its progress.
+//!
+//! [`LookupMap`](super::LookupMap) with the [`Identity`](super::key::Identity) hasher uses the
+//! same layout as [`collections::LookupMap`](crate::collections::LookupMap), so
+//! [`LookupMap::from_legacy`](super::LookupMap::from_legacy) converts it without rewriting any
+//! entry.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+//! use near_sdk::store::migration::Migration;
+//! use near_sdk::{collections, near_bindgen, store, Gas};
+//!
+//! type Balances = store::UnorderedMap<String, u128>;
+//!
+//! #[near_bindgen]
+//! #[derive(BorshDeserialize, BorshSerialize)]
+//! pub struct Contract {
+//!     migration: Option<Migration<collections::UnorderedMap<String, u128>, Balances>>,
+//!     balances: Option<Balances>,
+//! }
+//!
+//! #[near_bindgen]
+//! impl Contract {
+//!     /// Moves as many balances as the gas allows, leaving 20 TGas to write the state, and
+//!     /// returns the number of balances left to move.
+//!     pub fn migrate_balances(&mut self) -> u64 {
+//!         let migration = self.migration.as_mut().expect("Balances are migrated");
+//!         if migration.migrate_within_gas(Gas::ONE_TERA * 20) {
+//!             self.balances = self.migration.take().map(Migration::finish);
+//!         }
+//!         self.migration.as_ref().map_or(0, Migration::remaining)
+//!     }
+//! }
+//! #
+//! # let mut legacy = collections::UnorderedMap::new(b"b");
+//! # legacy.insert(&"alice".to_string(), &10);
+//! # let migration = Some(Balances::from_legacy(legacy));
+//! # let mut contract = Contract { migration, balances: None };
+//! # assert_eq!(contract.migrate_balances(), 0);
+//! # assert_eq!(contract.balances.unwrap()["alice"], 10);
+//! ```
+//!
+//! [`Vector`]: super::Vector
+//! [`UnorderedMap`]: super::UnorderedMap
+//! [`TreeMap`]: super::TreeMap
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::key::ToKey;
+use super::{TreeMap, UnorderedMap, Vector, ERR_INCONSISTENT_STATE};
+use crate::{collections, env, Gas};
+
+const ERR_NOT_FINISHED: &str = "The migration of the collection is not finished";
+
+fn expect_consistent_state<T>(val: Option<T>) -> T {
+    val.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+}
+
+/// A collection of [`collections`](crate::collections) which can be converted into the `store`
+/// collection `T`, one entry at a time.
+pub trait LegacyCollection<T> {
+    /// Returns the number of entries left in the legacy collection.
+    fn remaining(&self) -> u64;
+
+    /// Moves the last entry of the legacy collection into `target`. Only called while entries
+    /// remain.
+    fn move_last(&mut self, target: &mut T);
+
+    /// Writes the entries moved into `target` to storage.
+    fn flush(target: &mut T);
+}
+
+/// The conversion of the legacy collection `L` into the `store` collection `T`, in progress.
+///
+/// Entries are moved from the end of the legacy collection, which keeps its remaining entries
+/// readable with `get` until the migration is finished. Iterating over either collection while
+/// migrating only yields part of the entries. See the [module documentation](self) for an example.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Migration<L, T> {
+    legacy: L,
+    target: T,
+}
+
+impl<L, T> Migration<L, T>
+where
+    L: LegacyCollection<T>,
+{
+    /// Starts moving the entries of `legacy` into `target`. The `from_legacy` functions of the
+    /// `store` collections create the target under the prefix of the legacy collection.
+    pub fn new(legacy: L, target: T) -> Self {
+        Self { legacy, target }
+    }
+
+    /// Returns the number of entries left to move.
+    pub fn remaining(&self) -> u64 {
+        self.legacy.remaining()
+    }
+
+    /// Returns `true` if all of the entries were moved.
+    pub fn is_finished(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Returns the legacy collection, holding the entries left to move.
+    pub fn legacy(&self) -> &L {
+        &self.legacy
+    }
+
+    /// Returns the new collection, holding the entries moved so far.
+    pub fn target(&self) -> &T {
+        &self.target
+    }
+
+    /// Moves at most `limit` entries and writes them to storage. Returns `true` if the migration
+    /// is finished.
+    pub fn migrate(&mut self, limit: u64) -> bool {
+        for _ in 0..core::cmp::min(limit, self.remaining()) {
+            self.legacy.move_last(&mut self.target);
+        }
+        L::flush(&mut self.target);
+        self.is_finished()
+    }
+
+    /// Moves entries one at a time, writing each of them to storage, while the remaining gas
+    /// covers `reserved` and the gas of the most expensive entry moved so far. Returns `true` if
+    /// the migration is finished.
+    ///
+    /// The reserved gas has to cover the rest of the call, including writing the contract state.
+    pub fn migrate_within_gas(&mut self, reserved: Gas) -> bool {
+        let mut entry_gas = 0;
+        while !self.is_finished() && env::remaining_gas().0 >= reserved.0.saturating_add(entry_gas)
+        {
+            let used_gas = env::used_gas().0;
+            self.legacy.move_last(&mut self.target);
+            L::flush(&mut self.target);
+            entry_gas = core::cmp::max(entry_gas, env::used_gas().0 - used_gas);
+        }
+        self.is_finished()
+    }
+
+    /// Returns the new collection.
+    ///
+    /// # Panics
+    ///
+    /// Panics if entries are left to move.
+    pub fn finish(self) -> T {
+        if !self.is_finished() {
+            env::panic_str(ERR_NOT_FINISHED);
+        }
+        self.target
+    }
+}
+
+impl<T> LegacyCollection<Vector<T>> for collections::Vector<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    fn remaining(&self) -> u64 {
+        self.len()
+    }
+
+    fn move_last(&mut self, target: &mut Vector<T>) {
+        // The target has the length of the legacy vector from the start, see `from_legacy`.
+        let index = self.len() - 1;
+        let element = expect_consistent_state(self.pop());
+        target.values.set(index as u32, Some(element));
+    }
+
+    fn flush(target: &mut Vector<T>) {
+        target.flush();
+    }
+}
+
+impl<K, V, H> LegacyCollection<UnorderedMap<K, V, H>> for collections::UnorderedMap<K, V>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    fn remaining(&self) -> u64 {
+        self.len()
+    }
+
+    fn move_last(&mut self, target: &mut UnorderedMap<K, V, H>) {
+        // Removing the last key does not move any other entry of the legacy map.
+        let key = expect_consistent_state(self.keys_as_vector().get(self.len() - 1));
+        let value = expect_consistent_state(self.remove(&key));
+        target.insert(key, value);
+    }
+
+    fn flush(target: &mut UnorderedMap<K, V, H>) {
+        target.flush();
+    }
+}
+
+impl<K, V, H> LegacyCollection<TreeMap<K, V, H>> for collections::TreeMap<K, V>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    fn remaining(&self) -> u64 {
+        self.len()
+    }
+
+    fn move_last(&mut self, target: &mut TreeMap<K, V, H>) {
+        let (key, value) = expect_consistent_state(self.pop_unlinked());
+        target.insert(key, value);
+    }
+
+    fn flush(target: &mut TreeMap<K, V, H>) {
+        target.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    use super::Migration;
+    use crate::store::{LookupMap, TreeMap, UnorderedMap, Vector};
+    use crate::test_utils::test_env::setup_free;
+    use crate::test_utils::VMContextBuilder;
+    use crate::{collections, env, testing_env, Gas};
+
+    fn take_storage() -> HashMap<Vec<u8>, Vec<u8>> {
+        crate::mock::with_mocked_blockchain(|b| b.take_storage())
+    }
+
+    /// Migrates in batches of `limit` entries, storing the migration between the batches.
+    fn migrate_all<L, T>(mut migration: Migration<L, T>, limit: u64) -> T
+    where
+        L: super::LegacyCollection<T>,
+        Migration<L, T>: BorshSerialize + BorshDeserialize,
+    {
+        while !migration.migrate(limit) {
+            let state = migration.try_to_vec().unwrap();
+            migration = Migration::try_from_slice(&state).unwrap();
+        }
+        migration.finish()
+    }
+
+    #[test]
+    fn vector() {
+        setup_free();
+        let mut legacy = collections::Vector::new(b"v");
+        legacy.extend(0u32..50);
+
+        let vec = migrate_all(Vector::from_legacy(legacy), 7);
+        assert!(Iterator::eq(vec.iter().copied(), 0..50));
+        drop(vec);
+        let migrated = take_storage();
+
+        let mut vec = Vector::new(b"v");
+        vec.extend(0u32..50);
+        vec.flush();
+        assert_eq!(migrated, take_storage());
+    }
+
+    #[test]
+    fn unordered_map() {
+        setup_free();
+        let mut legacy = collections::UnorderedMap::new(b"m");
+        for i in 0u32..50 {
+            legacy.insert(&i, &i.to_string());
+        }
+        legacy.remove(&10);
+
+        let mut migration = UnorderedMap::from_legacy(legacy);
+        migration.migrate(20);
+        // The entries left to move are still readable from the legacy map.
+        assert_eq!(migration.legacy().get(&5), Some("5".to_string()));
+        assert_eq!(migration.target().get(&5), None);
+        assert_eq!(migration.target().get(&30), Some(&"30".to_string()));
+
+        let map = migrate_all(migration, 7);
+        assert_eq!(map.len(), 49);
+        assert_eq!(map.get(&10), None);
+        drop(map);
+        let migrated = take_storage();
+
+        // Removing 10 moved 49 into its place, and entries are moved from the end.
+        let mut map = UnorderedMap::new(b"m");
+        for i in (11u32..49).rev().chain([49]).chain((0..10).rev()) {
+            map.insert(i, i.to_string());
+        }
+        map.flush();
+        assert_eq!(migrated, take_storage());
+    }
+
+    #[test]
+    fn tree_map() {
+        setup_free();
+        let mut legacy = collections::TreeMap::new(b"t");
+        for i in 0u32..50 {
+            legacy.insert(&i, &i.to_string());
+        }
+
+        let mut map = migrate_all(TreeMap::from_legacy(legacy), 7);
+        assert!(Iterator::eq(map.keys().copied(), 0..50));
+        assert_eq!(map.validate(), Vec::new());
+        drop(map);
+        let migrated = take_storage();
+
+        let mut map = TreeMap::new(b"t");
+        for i in (0u32..50).rev() {
+            map.insert(i, i.to_string());
+        }
+        map.flush();
+        assert_eq!(migrated, take_storage());
+    }
+
+    #[test]
+    fn lookup_map() {
+        setup_free();
+        let mut legacy = collections::LookupMap::new(b"l");
+        legacy.insert(&1u32, &"a".to_string());
+
+        let mut map = LookupMap::from_legacy(legacy);
+        assert_eq!(map.get(&1), Some(&"a".to_string()));
+        map.insert(2, "b".to_string());
+        map.flush();
+
+        let legacy: collections::LookupMap<u32, String> = collections::LookupMap::new(b"l");
+        assert_eq!(legacy.get(&2), Some("b".to_string()));
+    }
+
+    #[test]
+    fn migrate_within_gas() {
+        setup_free();
+        let mut legacy = collections::Vector::new(b"v");
+        legacy.extend(0u32..1000);
+        let mut migration = Vector::from_legacy(legacy);
+
+        let reserved = Gas::ONE_TERA * 5;
+        testing_env!(VMContextBuilder::new().prepaid_gas(Gas::ONE_TERA * 10).build());
+        assert!(!migration.migrate_within_gas(reserved));
+        assert!(migration.remaining() < 1000);
+        assert!(env::remaining_gas() >= reserved);
+
+        setup_free();
+        assert!(migration.migrate_within_gas(reserved));
+        assert!(Iterator::eq(migration.finish().iter().copied(), 0..1000));
+    }
+
+    #[test]
+    #[should_panic(expected = "The migration of the collection is not finished")]
+    fn finish_unfinished() {
+        setup_free();
+        let mut legacy = collections::Vector::new(b"v");
+        legacy.extend(0u32..2);
+        let mut migration = Vector::from_legacy(legacy);
+        migration.migrate(1);
+        migration.finish();
+    }
+}
@@ -17,7 +17,8 @@
 //! It can be expensive to load all values into memory, and because of this, `serde`
 //! [`Serialize`](serde::Serialize) and [`Deserialize`](serde::Deserialize) traits are
 //! intentionally not implemented. If you want to return all values from a storage collection from
-//! a function, consider using pagination with the collection iterators.
+//! a function, consider using pagination with the `paginate` methods of the collections, or
+//! [`TreeMap::range_after`], which return a serializable [`Page`].
 //!
 //! All of the collections implement [`BorshSerialize`](borsh::BorshSerialize) and
 //! [`BorshDeserialize`](borsh::BorshDeserialize) to be able to store the metadata of the
@@ -30,6 +31,12 @@
 //!
 //! - [`Vector`]: Analogous to [`Vec`] but not contiguous and persisted to storage.
 //!
+//! - [`Deque`]: Analogous to [`std::collections::VecDeque`], a double-ended queue persisted to
+//! storage.
+//!
+//! - [`BinaryHeap`]: Analogous to [`std::collections::BinaryHeap`], a priority queue persisted to
+//! storage.
+//!
 //! Maps:
 //!
 //! - [`LookupMap`]: Wrapper around key-value storage interactions, similar to
@@ -39,14 +46,17 @@
 //! - [`UnorderedMap`]: Storage version of [`std::collections::HashMap`]. No ordering
 //! guarantees.
 //!
-//! - [`TreeMap`] (`unstable`): Storage version of [`std::collections::BTreeMap`]. Ordered by key,
+//! - [`TreeMap`]: Storage version of [`std::collections::BTreeMap`]. Ordered by key,
 //! which comes at the cost of more expensive lookups and iteration.
 //!
+//! - [`SortedMap`] (`unstable`): Map ordered by key through an order-preserving encoding of the
+//! keys, with range queries over storage iteration, which current nearcore versions don't enable.
+//!
 //! Sets:
 //!
-//! - [`LookupSet`] (`unstable`): Non-iterable storage version of [`std::collections::HashSet`].
+//! - [`LookupSet`]: Non-iterable storage version of [`std::collections::HashSet`].
 //!
-//! - [`UnorderedSet`] (`unstable`): Analogous to [`std::collections::HashSet`], and is an iterable
+//! - [`UnorderedSet`]: Analogous to [`std::collections::HashSet`], and is an iterable
 //! version of [`LookupSet`] and persisted to storage.
 //!
 //! Basic Types:
@@ -57,41 +67,48 @@
 //! - [`LazyOption<T>`](LazyOption): Lazily loaded, optional type that can be used in
 //! place of a type [`Option<T>`](Option). Will only be loaded when interacted with and will
 //! persist on [`Drop`].
+//!
+//! Collections can be nested as the values of maps, with a storage prefix derived from the prefix
+//! of the map and the key of the entry. See the [`nested`] module.
+//!
+//! Values are serialized with Borsh by default. [`Vector`], [`LookupMap`], [`UnorderedMap`] and
+//! [`LazyOption`] can store their values in other formats, see the [`serializer`] module.
 
-#[cfg(feature = "unstable")]
 mod lazy;
-#[cfg(feature = "unstable")]
 pub use lazy::Lazy;
 
-#[cfg(feature = "unstable")]
 mod lazy_option;
-#[cfg(feature = "unstable")]
 pub use lazy_option::LazyOption;
 
 pub mod vec;
 pub use vec::Vector;
 
+pub mod deque;
+pub use deque::Deque;
+
+pub mod binary_heap;
+pub use binary_heap::BinaryHeap;
+
 pub mod lookup_map;
 pub use self::lookup_map::LookupMap;
 
-#[cfg(feature = "unstable")]
 mod lookup_set;
-#[cfg(feature = "unstable")]
 pub use self::lookup_set::LookupSet;
 
 pub mod unordered_map;
 pub use self::unordered_map::UnorderedMap;
 
-#[cfg(feature = "unstable")]
 pub mod unordered_set;
-#[cfg(feature = "unstable")]
 pub use self::unordered_set::UnorderedSet;
 
-#[cfg(feature = "unstable")]
 pub mod tree_map;
-#[cfg(feature = "unstable")]
 pub use self::tree_map::TreeMap;
 
+#[cfg(feature = "unstable")]
+pub mod sorted_map;
+#[cfg(feature = "unstable")]
+pub use self::sorted_map::SortedMap;
+
 mod index_map;
 pub(crate) use self::index_map::IndexMap;
 
@@ -101,6 +118,19 @@ pub(crate) use self::free_list::FreeList;
 /// Storage key hash function types and trait to override map hash functions.
 pub mod key;
 
+pub mod nested;
+pub use self::nested::NestedCollection;
+
+mod page;
+pub use self::page::Page;
+
+mod consistency;
+pub use self::consistency::Inconsistency;
+
+pub mod serializer;
+
+pub mod migration;
+
 pub(crate) const ERR_INCONSISTENT_STATE: &str =
     "The collection is an inconsistent state. Did previous smart \
         contract execution terminate unexpectedly?";
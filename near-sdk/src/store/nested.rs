@@ -0,0 +1,241 @@
+//! Support for collections nested in the values of other collections.
+//!
+//! A collection stored as the value of a map needs a storage prefix which is unique to its entry.
+//! Instead of building these prefixes by hand, the maps in this module can derive them from their
+//! own prefix and the entry key, see [`LookupMap::get_or_insert_child`].
+//!
+//! [`LookupMap::get_or_insert_child`]: super::LookupMap::get_or_insert_child
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::key::ToKey;
+use super::serializer::{ValueDeserializer, ValueSerializer};
+use super::{BinaryHeap, Deque, LazyOption, TreeMap, UnorderedMap, UnorderedSet, Vector};
+use crate::env;
+
+/// A collection which can be created with a derived prefix as the value of a map entry, and whose
+/// storage can be cleared when the entry is removed.
+///
+/// This is implemented for all of the collections of this module which can clear their storage.
+/// [`LookupMap`](super::LookupMap) and [`LookupSet`](super::LookupSet) don't keep track of their
+/// keys, so they cannot be cleared and are not nested collections.
+pub trait NestedCollection {
+    /// Creates an empty collection which stores its values under the given `prefix`.
+    fn new_nested(prefix: Vec<u8>) -> Self;
+
+    /// Removes all of the values of the collection from storage. The removals are written when
+    /// the collection is flushed or dropped.
+    ///
+    /// This is not recursive: collections nested in the values of this collection have to be
+    /// cleared before it.
+    fn clear_nested(&mut self);
+}
+
+/// Separates the prefixes of child collections from the keys of the parent's own entries, which
+/// share the parent prefix.
+const CHILD_PREFIX_TAG: u8 = 0xff;
+
+/// Derives the prefix of the child collection stored under `key`, from the prefix of the parent
+/// collection. The child prefix is the parent prefix, followed by [`CHILD_PREFIX_TAG`] and the
+/// sha256 hash of the borsh serialized key, so children of the same map never collide with each
+/// other.
+///
+/// The entries of the parent map are stored under its prefix followed by the key converted by its
+/// [`ToKey`] hasher, while all storage keys of a child start with the parent prefix, the tag and
+/// the 32 byte hash. The keys of a child never alias the entries of the parent if:
+///
+/// - the parent hashes its keys with [`Sha256`](super::key::Sha256) or
+///   [`Keccak256`](super::key::Keccak256), which produce 32 bytes, or
+/// - the parent stores its keys with [`Identity`](super::key::Identity) and no borsh serialized
+///   key both starts with `0xff` and is 33 bytes or longer. Strings, account IDs and vectors start
+///   with their length, so this excludes lengths which are 255 modulo 256, and arrays like
+///   `[u8; 37]` must not start with `0xff`.
+///
+/// Otherwise, the suffixes a child appends to its prefix are chosen by the child and its own keys,
+/// so an entry of the parent can overwrite a value of a child.
+pub(crate) fn child_prefix<Q: ?Sized>(parent_prefix: &[u8], key: &Q) -> Vec<u8>
+where
+    Q: BorshSerialize,
+{
+    let key = key.try_to_vec().unwrap_or_else(|_| env::abort());
+    [parent_prefix, &[CHILD_PREFIX_TAG], &env::sha256_array(&key)].concat()
+}
+
+impl<T, S> NestedCollection for Vector<T, S>
+where
+    S: ValueSerializer<T>,
+{
+    fn new_nested(prefix: Vec<u8>) -> Self {
+        Self::with_serializer(prefix)
+    }
+
+    fn clear_nested(&mut self) {
+        self.clear()
+    }
+}
+
+impl<T> NestedCollection for Deque<T>
+where
+    T: BorshSerialize,
+{
+    fn new_nested(prefix: Vec<u8>) -> Self {
+        Self::new(prefix)
+    }
+
+    fn clear_nested(&mut self) {
+        self.clear()
+    }
+}
+
+impl<T> NestedCollection for BinaryHeap<T>
+where
+    T: BorshSerialize,
+{
+    fn new_nested(prefix: Vec<u8>) -> Self {
+        Self::new(prefix)
+    }
+
+    fn clear_nested(&mut self) {
+        self.clear()
+    }
+}
+
+impl<T, S> NestedCollection for LazyOption<T, S>
+where
+    S: ValueSerializer<T>,
+{
+    fn new_nested(prefix: Vec<u8>) -> Self {
+        Self::with_serializer(prefix, None)
+    }
+
+    fn clear_nested(&mut self) {
+        self.set(None)
+    }
+}
+
+impl<T, H> NestedCollection for UnorderedSet<T, H>
+where
+    T: BorshSerialize + BorshDeserialize + Ord + Clone,
+    H: ToKey,
+{
+    fn new_nested(prefix: Vec<u8>) -> Self {
+        Self::with_hasher(prefix)
+    }
+
+    fn clear_nested(&mut self) {
+        self.clear()
+    }
+}
+
+impl<K, V, H, S> NestedCollection for UnorderedMap<K, V, H, S>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
+{
+    fn new_nested(prefix: Vec<u8>) -> Self {
+        Self::with_hasher(prefix)
+    }
+
+    fn clear_nested(&mut self) {
+        self.clear()
+    }
+}
+
+impl<K, V, H> NestedCollection for TreeMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    fn new_nested(prefix: Vec<u8>) -> Self {
+        Self::with_hasher(prefix)
+    }
+
+    fn clear_nested(&mut self) {
+        self.clear()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use borsh::BorshDeserialize;
+
+    use crate::store::{LookupMap, TreeMap, UnorderedMap, UnorderedSet, Vector};
+    use crate::test_utils::test_env::setup_free;
+
+    fn storage_is_empty() -> bool {
+        crate::mock::with_mocked_blockchain(|m| m.take_storage().is_empty())
+    }
+
+    #[test]
+    fn remove_and_clear_nested() {
+        setup_free();
+        let mut map: LookupMap<String, UnorderedSet<u32>> = LookupMap::new(b"m");
+        map.get_or_insert_child("alice".to_string()).extend([1, 2, 3]);
+        map.get_or_insert_child("bob".to_string()).insert(1);
+        map.flush();
+        assert!(map["alice"].contains(&2));
+        assert!(!map["bob"].contains(&2));
+
+        // Removing without clearing leaves the values of the set in storage.
+        drop(map.remove("bob"));
+        assert!(map.remove_and_clear("alice"));
+        map.flush();
+        assert!(!storage_is_empty());
+
+        let mut map: LookupMap<String, UnorderedSet<u32>> = LookupMap::new(b"m");
+        map.get_or_insert_child("alice".to_string()).extend([1, 2, 3]);
+        assert!(map.remove_and_clear("alice"));
+        drop(map);
+        assert!(storage_is_empty());
+    }
+
+    #[test]
+    fn child_entries_do_not_alias_keys() {
+        setup_free();
+        let mut map: LookupMap<String, Vector<u8>> = LookupMap::new(b"m");
+        map.get_or_insert_child("alice".to_string()).extend([1, 2, 3]);
+        let child_prefix = map.child_prefix("alice");
+        assert_eq!(child_prefix[..2], [b'm', 0xff]);
+        drop(map);
+
+        let storage = crate::mock::with_mocked_blockchain(|m| m.take_storage());
+        let child_keys: Vec<_> = storage.keys().filter(|k| k.starts_with(&child_prefix)).collect();
+        assert_eq!(child_keys.len(), 3);
+        for key in child_keys {
+            // A key of the map stored under the same bytes would deserialize from them.
+            assert!(String::try_from_slice(&key[1..]).is_err());
+        }
+    }
+
+    #[test]
+    fn deeply_nested() {
+        setup_free();
+        let child_prefixes;
+        {
+            let mut map: UnorderedMap<u8, TreeMap<u8, Vector<u8>>> = UnorderedMap::new(b"m");
+            map.get_or_insert_child(1).get_or_insert_child(2).extend([3, 4]);
+            map.get_or_insert_child(1).get_or_insert_child(3).push(5);
+            map.get_or_insert_child(2).get_or_insert_child(2).push(6);
+            map.flush();
+
+            assert_eq!(map[&1][&2].len(), 2);
+            assert_eq!(map[&1][&3][0], 5);
+            assert_eq!(map[&2][&2][0], 6);
+            assert_ne!(map[&1].child_prefix(&2), map[&2].child_prefix(&2));
+
+            child_prefixes = [map.child_prefix(&1), map.child_prefix(&2)];
+            for key in [1, 2] {
+                // Clearing is not recursive, the innermost collections are cleared first.
+                map.get_mut(&key).unwrap().values_mut().for_each(|v| v.clear());
+                assert!(map.remove_and_clear(&key));
+            }
+        }
+
+        // Only the tombstones of the outer map's free list are left in storage.
+        let storage = crate::mock::with_mocked_blockchain(|m| m.take_storage());
+        assert!(storage.keys().all(|k| child_prefixes.iter().all(|p| !k.starts_with(p))));
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+use crate::env;
+
+pub(crate) const ERR_ZERO_LIMIT: &str = "The limit of a page must be greater than zero";
+
+/// A page of the items of a collection, with the cursor to request the next page.
+///
+/// Pages are returned by the `paginate` methods of the index based collections, where the cursor
+/// is the index to start the next page from, and by [`TreeMap::range_after`], where the cursor is
+/// the last key of the page. The page implements [`Serialize`], so it can be returned from a view
+/// method.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::store::{Page, Vector};
+///
+/// let mut vec = Vector::new(b"v");
+/// vec.extend([10u8, 20, 30]);
+///
+/// let page = vec.paginate(0, 2);
+/// assert_eq!(page.items, [&10, &20]);
+/// assert_eq!(page.next_cursor, Some(2));
+///
+/// let page = vec.paginate(page.next_cursor.unwrap(), 2);
+/// assert_eq!(page, Page { items: vec![&30], next_cursor: None });
+///
+/// let json = near_sdk::serde_json::to_string(&page).unwrap();
+/// assert_eq!(json, r#"{"items":[30],"next_cursor":null}"#);
+/// ```
+///
+/// [`TreeMap::range_after`]: super::TreeMap::range_after
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "abi", derive(schemars::JsonSchema))]
+pub struct Page<T, C = u32> {
+    /// The items of the page.
+    pub items: Vec<T>,
+    /// The cursor to request the next page with, or `None` if the end of the collection was
+    /// reached.
+    pub next_cursor: Option<C>,
+}
+
+impl<T> Page<T> {
+    /// Collects the page of the index based collections: the items of the `limit` indices from
+    /// `from_index`, out of `len` indices, where `item` returns `None` for the indices without an
+    /// item, such as the removed slots of a free list. At most `limit` indices are read, so a page
+    /// can have less than `limit` items even if the end of the collection was not reached.
+    pub(crate) fn from_indices<F>(from_index: u32, limit: u32, len: u32, item: F) -> Self
+    where
+        F: FnMut(u32) -> Option<T>,
+    {
+        if limit == 0 {
+            env::panic_str(ERR_ZERO_LIMIT);
+        }
+        let end = core::cmp::min(from_index.saturating_add(limit), len);
+        let items = (from_index..end).filter_map(item).collect();
+        let next_cursor = if end < len { Some(end) } else { None };
+        Page { items, next_cursor }
+    }
+}
+
+impl<T, C> Page<T, C> {
+    /// Maps the items of the page with `f`, keeping the cursor. This can be used to clone the
+    /// borrowed items of a page or to convert them to a different type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::{Page, Vector};
+    ///
+    /// let mut vec = Vector::new(b"v");
+    /// vec.extend([1u8, 2, 3]);
+    ///
+    /// let page: Page<u64> = vec.paginate(1, 5).map(|v| u64::from(*v));
+    /// assert_eq!(page.items, [2, 3]);
+    /// ```
+    pub fn map<U, F>(self, f: F) -> Page<U, C>
+    where
+        F: FnMut(T) -> U,
+    {
+        Page { items: self.items.into_iter().map(f).collect(), next_cursor: self.next_cursor }
+    }
+}
@@ -0,0 +1,182 @@
+//! Value serialization formats for the store collections.
+//!
+//! By default, the collections store their values serialized with [`Borsh`]. [`Vector`],
+//! [`LookupMap`], [`UnorderedMap`] and [`LazyOption`] have a serializer type parameter which can be
+//! set to [`Json`], to store values which can be read without a Borsh schema, or to [`Raw`], to
+//! store pre-encoded `Vec<u8>` values as they are, without a length prefix.
+//!
+//! [`UnorderedMap`] stores each value followed by the index of its key, as a little-endian `u32`,
+//! so its stored values are only JSON, or the raw bytes, without their last 4 bytes.
+//!
+//! Custom formats can be used by implementing [`ValueSerializer`] and [`ValueDeserializer`].
+//!
+//! # Examples
+//!
+//! ```
+//! use near_sdk::store::key::Identity;
+//! use near_sdk::store::serializer::{Json, Raw};
+//! use near_sdk::store::{LookupMap, Vector};
+//!
+//! // Metadata which is already encoded as JSON is stored without being encoded again.
+//! let mut metadata: LookupMap<u64, Vec<u8>, Identity, Raw> = LookupMap::with_hasher(b"m");
+//! metadata.insert(1, br#"{"title":"token"}"#.to_vec());
+//!
+//! let mut names: Vector<String, Json> = Vector::with_serializer(b"n");
+//! names.push("alice".to_string());
+//! names.flush();
+//! assert_eq!(near_sdk::env::storage_read(&[b"n".as_slice(), &0u32.to_le_bytes()].concat()),
+//!            Some(br#""alice""#.to_vec()));
+//! ```
+//!
+//! [`Vector`]: super::Vector
+//! [`LookupMap`]: super::LookupMap
+//! [`UnorderedMap`]: super::UnorderedMap
+//! [`LazyOption`]: super::LazyOption
+
+use borsh::maybestd::io;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Serializes values of type `T` into the bytes written to storage.
+pub trait ValueSerializer<T: ?Sized> {
+    /// Appends the serialized bytes of `value` to `buffer`.
+    fn serialize_value(value: &T, buffer: &mut Vec<u8>) -> io::Result<()>;
+}
+
+/// Deserializes values of type `T` from the bytes read from storage.
+pub trait ValueDeserializer<T> {
+    /// Deserializes a value from all of the given `bytes`.
+    fn deserialize_value(bytes: &[u8]) -> io::Result<T>;
+}
+
+/// Serializes values with [Borsh](borsh). This is the default serializer of the collections.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Borsh {}
+
+impl<T> ValueSerializer<T> for Borsh
+where
+    T: BorshSerialize + ?Sized,
+{
+    fn serialize_value(value: &T, buffer: &mut Vec<u8>) -> io::Result<()> {
+        value.serialize(buffer)
+    }
+}
+
+impl<T> ValueDeserializer<T> for Borsh
+where
+    T: BorshDeserialize,
+{
+    fn deserialize_value(bytes: &[u8]) -> io::Result<T> {
+        T::try_from_slice(bytes)
+    }
+}
+
+/// Serializes values as JSON with [`serde_json`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Json {}
+
+impl<T> ValueSerializer<T> for Json
+where
+    T: Serialize + ?Sized,
+{
+    fn serialize_value(value: &T, buffer: &mut Vec<u8>) -> io::Result<()> {
+        serde_json::to_writer(buffer, value).map_err(io::Error::from)
+    }
+}
+
+impl<T> ValueDeserializer<T> for Json
+where
+    T: DeserializeOwned,
+{
+    fn deserialize_value(bytes: &[u8]) -> io::Result<T> {
+        serde_json::from_slice(bytes).map_err(io::Error::from)
+    }
+}
+
+/// Stores `Vec<u8>` values as they are, without the length prefix that [`Borsh`] adds.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Raw {}
+
+impl ValueSerializer<Vec<u8>> for Raw {
+    fn serialize_value(value: &Vec<u8>, buffer: &mut Vec<u8>) -> io::Result<()> {
+        buffer.extend_from_slice(value);
+        Ok(())
+    }
+}
+
+impl ValueDeserializer<Vec<u8>> for Raw {
+    fn deserialize_value(bytes: &[u8]) -> io::Result<Vec<u8>> {
+        Ok(bytes.to_vec())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::{Json, Raw};
+    use crate::env;
+    use crate::store::key::{Identity, Sha256};
+    use crate::store::{LazyOption, LookupMap, UnorderedMap, Vector};
+    use crate::test_utils::test_env::setup_free;
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    fn stored_values() -> Vec<Vec<u8>> {
+        crate::mock::with_mocked_blockchain(|m| m.take_storage()).into_values().collect()
+    }
+
+    #[test]
+    fn raw_values_have_no_length_prefix() {
+        setup_free();
+        let blob = b"\x89PNG".to_vec();
+        let mut map: LookupMap<u8, Vec<u8>, Identity, Raw> = LookupMap::with_hasher(b"m");
+        map.insert(1, blob.clone());
+        map.flush();
+        assert_eq!(env::storage_read(&[b'm', 1]), Some(blob.clone()));
+
+        let map: LookupMap<u8, Vec<u8>, Identity, Raw> =
+            BorshDeserialize::try_from_slice(&map.try_to_vec().unwrap()).unwrap();
+        assert_eq!(map.get(&1), Some(&blob));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        setup_free();
+        let mut vec: Vector<(String, u64), Json> = Vector::with_serializer(b"v");
+        vec.push(("alice".to_string(), 5));
+        let mut option: LazyOption<Vec<u32>, Json> = LazyOption::with_serializer(b"o", None);
+        option.set(Some(vec![1, 2]));
+        let (vec_bytes, option_bytes) = (vec.try_to_vec().unwrap(), option.try_to_vec().unwrap());
+        drop((vec, option));
+
+        let element_key = [b"v".as_slice(), &0u32.to_le_bytes()].concat();
+        assert_eq!(env::storage_read(&element_key), Some(br#"["alice",5]"#.to_vec()));
+        assert_eq!(env::storage_read(b"o"), Some(b"[1,2]".to_vec()));
+
+        let vec = Vector::<(String, u64), Json>::try_from_slice(&vec_bytes).unwrap();
+        assert_eq!(vec[0], ("alice".to_string(), 5));
+        let option = LazyOption::<Vec<u32>, Json>::try_from_slice(&option_bytes).unwrap();
+        assert_eq!(option.get(), &Some(vec![1, 2]));
+    }
+
+    #[test]
+    fn unordered_map_layout() {
+        setup_free();
+        let mut map: UnorderedMap<u8, String, Sha256, Json> = UnorderedMap::with_hasher(b"m");
+        map.insert(1, "a".to_string());
+        map.insert(2, "b".to_string());
+        map.remove(&1);
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&2, &"b".to_string())]);
+        drop(map);
+
+        // The value is followed by the index of the key, like the borsh serialized entries.
+        let values = stored_values();
+        assert!(values.contains(&[br#""b""#.as_slice(), &1u32.to_le_bytes()].concat()));
+
+        let mut map: UnorderedMap<u8, String> = UnorderedMap::new(b"b");
+        map.insert(1, "a".to_string());
+        drop(map);
+        let values = stored_values();
+        assert!(values.contains(&("a".to_string(), 0u32).try_to_vec().unwrap()));
+    }
+}
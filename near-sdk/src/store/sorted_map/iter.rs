@@ -0,0 +1,54 @@
+use std::marker::PhantomData;
+
+use super::SortedMap;
+use crate::env::{self, StorageIter};
+use crate::store::key::OrderedKey;
+use crate::store::serializer::{Borsh, ValueDeserializer, ValueSerializer};
+
+const ERR_KEY_DECODING: &str = "Cannot decode key";
+
+/// An iterator over a range of entries of a [`SortedMap`], in ascending order of the keys.
+///
+/// This `struct` is created by the `iter` and `range` methods on [`SortedMap`].
+#[derive(Debug)]
+pub struct Range<K, V, S = Borsh> {
+    iter: StorageIter,
+    prefix_len: usize,
+    /// Start of the range, when the storage iterator is created over the whole prefix and has
+    /// to skip the keys before it.
+    skip_before: Option<Vec<u8>>,
+    marker: PhantomData<(K, V, S)>,
+}
+
+impl<K, V, S> Range<K, V, S>
+where
+    K: OrderedKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
+{
+    pub(super) fn new(prefix: &[u8], start: Vec<u8>, end: Option<Vec<u8>>) -> Self {
+        let (iter, skip_before) = match end {
+            Some(end) => (env::storage_iter_range(&start, &end), None),
+            // There is no upper bound for the keys of the prefix, iterate over all of them.
+            None => (env::storage_iter_prefix(prefix), Some(start)),
+        };
+        Self { iter, prefix_len: prefix.len(), skip_before, marker: PhantomData }
+    }
+}
+
+impl<K, V, S> Iterator for Range<K, V, S>
+where
+    K: OrderedKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = match self.skip_before.take() {
+            Some(start) => self.iter.by_ref().find(|(key, _)| *key >= start)?,
+            None => self.iter.next()?,
+        };
+        let key = K::decode_ordered(&key[self.prefix_len..])
+            .unwrap_or_else(|| env::panic_str(ERR_KEY_DECODING));
+        Some((key, SortedMap::<K, V, S>::deserialize_element(&value)))
+    }
+}
@@ -0,0 +1,484 @@
+mod iter;
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+pub use self::iter::Range;
+use super::key::OrderedKey;
+use super::page::ERR_ZERO_LIMIT;
+use super::serializer::{Borsh, ValueDeserializer, ValueSerializer};
+use super::{Page, ERR_INCONSISTENT_STATE};
+use crate::{env, IntoStorageKey};
+
+const ERR_ELEMENT_DESERIALIZATION: &str = "Cannot deserialize element";
+const ERR_ELEMENT_SERIALIZATION: &str = "Cannot serialize element";
+
+fn expect_consistent_state<T>(val: Option<T>) -> T {
+    val.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+}
+
+/// A storage map that keeps its keys ordered by storing each entry under an order-preserving
+/// encoding of its key, and reads ranges of entries with the `storage_iter_range` host function.
+///
+/// Where [`TreeMap`] maintains an AVL-tree in separate storage entries, the entries of this map
+/// are already in key order in the storage trie, so ordered operations don't have to walk a tree:
+///
+/// | Operation                  | [`TreeMap`]                    | [`SortedMap`]                 |
+/// |----------------------------|--------------------------------|-------------------------------|
+/// | `get`/`contains_key`       | 1 read                         | 1 read                        |
+/// | `insert`/`remove`          | O(log(N)) node reads/writes    | 1 write                       |
+/// | `range` of K elements      | O(K + log(N)) node reads       | 1 iterator, K steps           |
+/// | Reverse iteration          | supported                      | not supported                 |
+/// | Key types                  | any [`BorshSerialize`]         | [`OrderedKey`] only           |
+/// | Storage per entry          | value, key and tree node       | encoded key and value         |
+///
+/// **The ordered operations of this map don't work on current nearcore protocol versions,
+/// including mainnet.** [`range`](Self::range), [`iter`](Self::iter),
+/// [`range_after`](Self::range_after) and [`clear`](Self::clear) use the `storage_iter_prefix`
+/// and `storage_iter_range` host functions, which are deprecated and disabled there, so these
+/// operations abort the contract. Only point queries and updates, which use the regular storage
+/// functions, work on those networks. The map is therefore only available with the `unstable`
+/// feature.
+///
+/// The other trade-offs of relying on storage iteration are:
+///
+/// - Changes are written to storage immediately instead of being cached, so that range queries
+///   always see them. Values are returned by value, and updating a value means inserting it again.
+/// - Range queries iterate over all storage keys starting with the prefix of the map, so the
+///   prefix must not be the prefix of any other collection, including collections nested in the
+///   values of this map.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::SortedMap;
+///
+/// let mut scores: SortedMap<u64, String> = SortedMap::new(b"s");
+/// scores.insert(30, "carol".to_string());
+/// scores.insert(10, "alice".to_string());
+/// scores.insert(20, "bob".to_string());
+///
+/// assert_eq!(scores.get(&20), Some("bob".to_string()));
+/// assert_eq!(scores.range(15..).map(|(k, _)| k).collect::<Vec<_>>(), [20, 30]);
+/// assert_eq!(scores.insert(20, "dave".to_string()), Some("bob".to_string()));
+/// assert_eq!(scores.len(), 3);
+/// ```
+///
+/// [`TreeMap`]: super::TreeMap
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct SortedMap<K, V, S = Borsh>
+where
+    K: OrderedKey,
+    S: ValueSerializer<V>,
+{
+    len: u32,
+    prefix: Box<[u8]>,
+    #[borsh_skip]
+    marker: PhantomData<(K, V, S)>,
+}
+
+impl<K, V> SortedMap<K, V, Borsh>
+where
+    K: OrderedKey,
+    V: BorshSerialize,
+{
+    /// Create a new [`SortedMap`] with the prefix provided.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    /// It must not be the prefix of the storage prefix of another collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::SortedMap;
+    ///
+    /// let mut map: SortedMap<u32, String> = SortedMap::new(b"m");
+    /// ```
+    pub fn new<P>(prefix: P) -> Self
+    where
+        P: IntoStorageKey,
+    {
+        Self::with_serializer(prefix)
+    }
+}
+
+impl<K, V, S> SortedMap<K, V, S>
+where
+    K: OrderedKey,
+    S: ValueSerializer<V>,
+{
+    /// Create a new [`SortedMap`] which serializes its values with the serializer `S`. See the
+    /// [`serializer`](crate::store::serializer) module for the available formats.
+    pub fn with_serializer<P>(prefix: P) -> Self
+    where
+        P: IntoStorageKey,
+    {
+        Self { len: 0, prefix: prefix.into_storage_key().into_boxed_slice(), marker: PhantomData }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns `true` if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn storage_key(&self, key: &K) -> Vec<u8> {
+        let mut buffer = self.prefix.to_vec();
+        key.encode_ordered(&mut buffer);
+        buffer
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        env::storage_has_key(&self.storage_key(key))
+    }
+
+    /// Removes all elements from the map. The storage keys of the map are collected before they
+    /// are removed, because storage iterators cannot be used while the storage is modified.
+    ///
+    /// This uses storage iteration, which aborts on current nearcore protocol versions, see
+    /// [`SortedMap`].
+    pub fn clear(&mut self) {
+        let keys: Vec<_> = env::storage_iter_prefix(&self.prefix).map(|(key, _)| key).collect();
+        for key in keys {
+            env::storage_remove(&key);
+        }
+        self.len = 0;
+    }
+}
+
+impl<K, V, S> SortedMap<K, V, S>
+where
+    K: OrderedKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
+{
+    fn deserialize_element(bytes: &[u8]) -> V {
+        S::deserialize_value(bytes).unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_DESERIALIZATION))
+    }
+
+    fn evicted_element() -> V {
+        Self::deserialize_element(&expect_consistent_state(env::storage_get_evicted()))
+    }
+
+    /// Returns the value corresponding to the key, read from storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::SortedMap;
+    ///
+    /// let mut map = SortedMap::new(b"m");
+    /// map.insert(1u8, "a".to_string());
+    /// assert_eq!(map.get(&1), Some("a".to_string()));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<V> {
+        env::storage_read(&self.storage_key(key)).map(|bytes| Self::deserialize_element(&bytes))
+    }
+
+    /// Inserts a key-value pair into the map and returns the previous value of the key, if any.
+    /// The value is written to storage immediately.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the map already contains `u32::MAX` elements.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut buffer = Vec::new();
+        S::serialize_value(&value, &mut buffer)
+            .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
+        if env::storage_write(&self.storage_key(&key), &buffer) {
+            Some(Self::evicted_element())
+        } else {
+            self.len = expect_consistent_state(self.len.checked_add(1));
+            None
+        }
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was in the map.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if env::storage_remove(&self.storage_key(key)) {
+            self.len = expect_consistent_state(self.len.checked_sub(1));
+            Some(Self::evicted_element())
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over the entries of the map, in ascending order of the keys.
+    pub fn iter(&self) -> Range<K, V, S> {
+        self.range(..)
+    }
+
+    /// Returns an iterator over the entries of the map with keys in `range`, in ascending order
+    /// of the keys. The entries are read with a single storage iterator, which aborts on current
+    /// nearcore protocol versions, see [`SortedMap`].
+    ///
+    /// The map must not be modified while the iterator is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::SortedMap;
+    /// use std::ops::Bound;
+    ///
+    /// let mut map = SortedMap::new(b"m");
+    /// for key in [-2i32, 5, -10, 0, 3] {
+    ///     map.insert(key, ());
+    /// }
+    ///
+    /// let keys = |range| map.range(range).map(|(k, _)| k).collect::<Vec<_>>();
+    /// assert_eq!(keys((Bound::Unbounded, Bound::Excluded(0))), [-10, -2]);
+    /// assert_eq!(keys((Bound::Excluded(-2), Bound::Included(5))), [0, 3, 5]);
+    /// ```
+    pub fn range<R>(&self, range: R) -> Range<K, V, S>
+    where
+        R: RangeBounds<K>,
+    {
+        let mut start = self.prefix.to_vec();
+        match range.start_bound() {
+            Bound::Included(key) => key.encode_ordered(&mut start),
+            Bound::Excluded(key) => {
+                key.encode_ordered(&mut start);
+                // The smallest byte string greater than the encoded key.
+                start.push(0);
+            }
+            Bound::Unbounded => {}
+        }
+        let end = match range.end_bound() {
+            Bound::Included(key) => {
+                let mut end = self.storage_key(key);
+                end.push(0);
+                Some(end)
+            }
+            Bound::Excluded(key) => Some(self.storage_key(key)),
+            Bound::Unbounded => prefix_end(&self.prefix),
+        };
+        Range::new(&self.prefix, start, end)
+    }
+
+    /// Returns a page of at most `limit` key-value pairs in ascending order of the keys, starting
+    /// after the `cursor` key, or from the first key if `cursor` is `None`. The cursor of the
+    /// next page is the last key of the page, if there are more entries after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::SortedMap;
+    ///
+    /// let mut map = SortedMap::new(b"m");
+    /// map.insert("b".to_string(), 2u8);
+    /// map.insert("a".to_string(), 1);
+    /// map.insert("c".to_string(), 3);
+    ///
+    /// let page = map.range_after(None, 2);
+    /// assert_eq!(page.items, [("a".to_string(), 1), ("b".to_string(), 2)]);
+    ///
+    /// let page = map.range_after(page.next_cursor.as_ref(), 2);
+    /// assert_eq!(page.items, [("c".to_string(), 3)]);
+    /// assert_eq!(page.next_cursor, None);
+    /// ```
+    pub fn range_after(&self, cursor: Option<&K>, limit: u32) -> Page<(K, V), K>
+    where
+        K: Clone,
+    {
+        if limit == 0 {
+            env::panic_str(ERR_ZERO_LIMIT);
+        }
+        let start = cursor.map_or(Bound::Unbounded, Bound::Excluded);
+        let mut range = self.range((start, Bound::Unbounded));
+        let items: Vec<_> = range.by_ref().take(limit as usize).collect();
+        let next_cursor = match (items.last(), range.next()) {
+            (Some((last, _)), Some(_)) => Some(last.clone()),
+            _ => None,
+        };
+        Page { items, next_cursor }
+    }
+}
+
+/// Returns the smallest byte string greater than all the byte strings starting with `prefix`, or
+/// `None` if there is no such string.
+fn prefix_end(prefix: &[u8]) -> Option<Vec<u8>> {
+    let last = prefix.iter().rposition(|b| *b != u8::MAX)?;
+    let mut end = prefix[..=last].to_vec();
+    end[last] += 1;
+    Some(end)
+}
+
+impl<K, V, S> fmt::Debug for SortedMap<K, V, S>
+where
+    K: OrderedKey,
+    S: ValueSerializer<V>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SortedMap").field("len", &self.len).field("prefix", &self.prefix).finish()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::{prefix_end, SortedMap};
+    use crate::store::key::OrderedKey;
+    use crate::store::TreeMap;
+    use crate::test_utils::VMContextBuilder;
+    use crate::{env, testing_env, Gas, GasMeter, RuntimeFeesConfig, VMConfig};
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeMap;
+    use std::ops::Bound;
+
+    #[test]
+    fn prefix_end_bounds() {
+        assert_eq!(prefix_end(b"ab"), Some(b"ac".to_vec()));
+        assert_eq!(prefix_end(&[1, 255, 255]), Some(vec![2]));
+        assert_eq!(prefix_end(&[255]), None);
+        assert_eq!(prefix_end(b""), None);
+    }
+
+    #[test]
+    fn ordered_key_encoding() {
+        fn check<K: OrderedKey + Clone + std::fmt::Debug>(mut keys: Vec<K>) {
+            keys.sort();
+            let encoded: Vec<Vec<u8>> = keys
+                .iter()
+                .map(|k| {
+                    let mut buffer = Vec::new();
+                    k.encode_ordered(&mut buffer);
+                    buffer
+                })
+                .collect();
+            assert!(encoded.windows(2).all(|w| w[0] < w[1]), "{:?}", keys);
+            for (key, bytes) in keys.iter().zip(&encoded) {
+                assert_eq!(K::decode_ordered(bytes).as_ref(), Some(key));
+            }
+        }
+        check(vec![0u8, 1, 127, 128, 255]);
+        check(vec![i64::MIN, -256, -1, 0, 1, 255, i64::MAX]);
+        check(vec![i128::MIN, -1, 0, i128::MAX]);
+        check(vec!["".to_string(), "a".to_string(), "ab".to_string(), "b".to_string()]);
+        check(vec![[0u8, 1], [1, 0], [255, 255]]);
+        assert_eq!(u32::decode_ordered(&[1, 2]), None);
+    }
+
+    #[test]
+    fn matches_btree_map() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(1);
+        let mut map = SortedMap::new(b"m");
+        let mut baseline = BTreeMap::new();
+        for _ in 0..300 {
+            let key = rng.gen_range(-50i16..50);
+            if rng.gen_bool(0.3) {
+                assert_eq!(map.remove(&key), baseline.remove(&key));
+            } else {
+                let value = rng.gen::<u32>();
+                assert_eq!(map.insert(key, value), baseline.insert(key, value));
+            }
+            assert_eq!(map.len() as usize, baseline.len());
+        }
+
+        assert!(map.iter().eq(baseline.clone().into_iter()));
+        let bounds =
+            [Bound::Unbounded, Bound::Included(-10), Bound::Excluded(0), Bound::Included(49)];
+        for start in bounds {
+            for end in bounds {
+                let expected: Vec<_> = match (start, end) {
+                    (
+                        Bound::Included(s) | Bound::Excluded(s),
+                        Bound::Included(e) | Bound::Excluded(e),
+                    ) if s >= e => continue,
+                    _ => baseline.range((start, end)).map(|(k, v)| (*k, *v)).collect(),
+                };
+                assert_eq!(map.range((start, end)).collect::<Vec<_>>(), expected);
+            }
+        }
+
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.iter().next(), None);
+    }
+
+    #[test]
+    fn unbounded_end_with_max_prefix() {
+        let mut map = SortedMap::new(vec![255u8]);
+        map.insert(3u8, ());
+        map.insert(1, ());
+        env::storage_write(&[254], b"before the map");
+        assert_eq!(map.range(2..).map(|(k, _)| k).collect::<Vec<_>>(), [3]);
+        assert_eq!(map.iter().map(|(k, _)| k).collect::<Vec<_>>(), [1, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "The limit of a page must be greater than zero")]
+    fn range_after_zero_limit() {
+        let mut map = SortedMap::new(b"m");
+        map.insert(1u8, ());
+        map.range_after(None, 0);
+    }
+
+    /// Measures the gas of the same operations on a `TreeMap` and a `SortedMap`. Storage
+    /// iteration is free in the default test config, so it is priced like storage reads here. The
+    /// ratios are hypothetical, since current nearcore versions don't enable storage iteration.
+    #[test]
+    fn gas_compared_to_tree_map() {
+        let mut config = VMConfig::test();
+        let costs = &mut config.ext_costs;
+        costs.storage_iter_create_range_base = costs.storage_read_base;
+        costs.storage_iter_create_prefix_base = costs.storage_read_base;
+        costs.storage_iter_create_from_byte = costs.storage_read_key_byte;
+        costs.storage_iter_create_to_byte = costs.storage_read_key_byte;
+        costs.storage_iter_create_prefix_byte = costs.storage_read_key_byte;
+        costs.storage_iter_next_base = costs.storage_read_base;
+        costs.storage_iter_next_key_byte = costs.storage_read_key_byte;
+        costs.storage_iter_next_value_byte = costs.storage_read_value_byte;
+        testing_env!(
+            VMContextBuilder::new().prepaid_gas(Gas(u64::MAX)).build(),
+            config,
+            RuntimeFeesConfig::test()
+        );
+
+        let mut keys: Vec<u64> = (0..500).collect();
+        keys.shuffle(&mut rand_xorshift::XorShiftRng::seed_from_u64(2));
+
+        let mut tree = TreeMap::new(b"t");
+        let mut sorted = SortedMap::new(b"s");
+        let tree_insert = GasMeter::new("tree_map insert");
+        for key in &keys {
+            tree.insert(*key, *key);
+        }
+        tree.flush();
+        let tree_insert = tree_insert.finish();
+        let sorted_insert = GasMeter::new("sorted_map insert");
+        for key in &keys {
+            sorted.insert(*key, *key);
+        }
+        let sorted_insert = sorted_insert.finish();
+
+        // A fresh instance of the tree, so that the nodes have to be read from storage.
+        let tree: TreeMap<u64, u64> = borsh::BorshDeserialize::try_from_slice(
+            &borsh::BorshSerialize::try_to_vec(&tree).unwrap(),
+        )
+        .unwrap();
+        let tree_range = GasMeter::new("tree_map range");
+        assert_eq!(tree.range(200..220).count(), 20);
+        let tree_range = tree_range.finish();
+        let sorted_range = GasMeter::new("sorted_map range");
+        assert_eq!(sorted.range(200..220).count(), 20);
+        let sorted_range = sorted_range.finish();
+
+        // The ratios documented on `SortedMap`.
+        let (sorted_insert, tree_insert) = (sorted_insert.0, tree_insert.0);
+        assert!(4 * sorted_insert > tree_insert && 5 * sorted_insert < 2 * tree_insert);
+        assert!(2 * sorted_range.0 < tree_range.0);
+    }
+}
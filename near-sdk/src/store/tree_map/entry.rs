@@ -139,7 +139,10 @@ where
     where
         V: Default,
     {
-        self.or_insert_with(Default::default)
+        match self {
+            Self::Occupied(entry) => entry.into_mut(),
+            Self::Vacant(entry) => entry.insert(Default::default()),
+        }
     }
 
     /// Provides in-place mutable access to an occupied entry before any
@@ -5,14 +5,18 @@ mod iter;
 use super::lookup_map as lm;
 use crate::store::free_list::{FreeList, FreeListIndex};
 use crate::store::key::{Sha256, ToKey};
-use crate::store::LookupMap;
-use crate::{env, IntoStorageKey};
+use crate::store::migration::Migration;
+use crate::store::nested::NestedCollection;
+use crate::store::page::ERR_ZERO_LIMIT;
+use crate::store::{Inconsistency, LookupMap, Page};
+use crate::{collections, env, IntoStorageKey};
 use borsh::{BorshDeserialize, BorshSerialize};
-pub use entry::Entry;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
 pub use iter::{Iter, IterMut, Keys, Range, RangeMut, Values, ValuesMut};
 use std::borrow::Borrow;
+use std::collections::BTreeMap;
 use std::fmt;
-use std::ops::RangeBounds;
+use std::ops::{Bound, RangeBounds};
 
 type NodeAndIndex<'a, K> = (FreeListIndex, &'a Node<K>);
 
@@ -20,14 +24,99 @@ fn expect<T>(val: Option<T>) -> T {
     val.unwrap_or_else(|| env::abort())
 }
 
-/// TreeMap based on AVL-tree
+/// A lazily loaded storage map that keeps its keys ordered, based on an AVL-tree. This structure
+/// is similar to [`UnorderedMap`](crate::store::UnorderedMap), except that iteration and
+/// [`range`] queries yield the entries in ascending order of the keys.
+///
+/// The values are stored in a [`LookupMap`] under the map's `prefix` using the map's [`ToKey`]
+/// implementation, which defaults to [`Sha256`]. To use a custom function, use [`with_hasher`].
+/// The nodes of the tree are stored separately, so only the nodes visited by an operation are
+/// loaded from storage.
 ///
 /// Runtime complexity (worst case):
 /// - `get`/`contains_key`:     O(1) - LookupMap lookup
 /// - `insert`/`remove`:        O(log(N))
-/// - `min`/`max`:              O(log(N))
-/// - `above`/`below`:          O(log(N))
+/// - first/last key:           O(log(N)) - through `keys().next()`/`keys().next_back()`
 /// - `range` of K elements:    O(Klog(N))
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::TreeMap;
+///
+/// // Initializes a map, the generic types can be inferred to `TreeMap<u64, String, Sha256>`
+/// // The `b"t"` parameter is a prefix for the storage keys of this data structure.
+/// let mut map = TreeMap::new(b"t");
+///
+/// map.insert(3u64, "c".to_string());
+/// map.insert(1, "a".to_string());
+/// map.insert(2, "b".to_string());
+///
+/// assert_eq!(map.get(&1), Some(&"a".to_string()));
+/// assert_eq!(map.keys().collect::<Vec<_>>(), [&1, &2, &3]);
+/// assert_eq!(map.range(2..).map(|(k, _)| *k).collect::<Vec<_>>(), [2, 3]);
+///
+/// for (_, value) in map.iter_mut() {
+///     value.push('!');
+/// }
+/// assert_eq!(map[&3], "c!");
+/// ```
+///
+/// # Migrating from `collections::TreeMap`
+///
+/// The storage layout of this map is not compatible with
+/// [`collections::TreeMap`](crate::collections::TreeMap), so the entries have to be copied over
+/// to a map with a new prefix. For large maps, the copy can be split across several calls with
+/// [`collections::TreeMap::iter_from`](crate::collections::TreeMap::iter_from).
+///
+/// ```
+/// use near_sdk::{collections, store};
+///
+/// let mut old: collections::TreeMap<u64, String> = collections::TreeMap::new(b"o");
+/// old.insert(&1, &"a".to_string());
+///
+/// let mut new = store::TreeMap::new(b"n");
+/// for (key, value) in old.iter() {
+///     new.insert(key, value);
+/// }
+/// old.clear();
+/// assert_eq!(new.get(&1), Some(&"a".to_string()));
+/// ```
+///
+/// To keep the prefix, [`from_legacy`] moves the entries into a map with the prefix of the legacy
+/// map instead, in batches which can also be split across several calls:
+///
+/// ```
+/// use near_sdk::{collections, store};
+///
+/// let mut old: collections::TreeMap<u64, String> = collections::TreeMap::new(b"o");
+/// old.insert(&1, &"a".to_string());
+///
+/// let mut migration = store::TreeMap::from_legacy(old);
+/// while !migration.migrate(100) {}
+/// let new = migration.finish();
+/// assert_eq!(new.get(&1), Some(&"a".to_string()));
+/// ```
+///
+/// The values are accessed by reference and modifications are written to storage when the map is
+/// [`flush`]ed or dropped. The methods of `collections::TreeMap` map to the following:
+///
+/// | `collections::TreeMap` | `store::TreeMap`                            |
+/// |------------------------|---------------------------------------------|
+/// | `get(&k)`              | `get(&k).cloned()` or `get_mut(&k)`         |
+/// | `insert(&k, &v)`       | `insert(k, v)`                              |
+/// | `min()`/`max()`        | `keys().next()`/`keys().next_back()`        |
+/// | `higher(&k)`           | `range((Excluded(&k), Unbounded)).next()`   |
+/// | `lower(&k)`            | `range(..&k).next_back()`                   |
+/// | `ceil_key(&k)`         | `range(&k..).next()`                        |
+/// | `floor_key(&k)`        | `range(..=&k).next_back()`                  |
+/// | `iter_from(k)`         | `range((Excluded(&k), Unbounded))`          |
+/// | `iter_rev()`           | `iter().rev()`                              |
+/// | `to_vec()`             | `iter().collect()`                          |
+///
+/// [`range`]: Self::range
+/// [`with_hasher`]: Self::with_hasher
+/// [`from_legacy`]: Self::from_legacy
+/// [`flush`]: Self::flush
 pub struct TreeMap<K, V, H = Sha256>
 where
     K: BorshSerialize + Ord,
@@ -156,6 +245,20 @@ where
     {
         Self::with_hasher(prefix)
     }
+
+    /// Starts converting `legacy` into a map with the same prefix and entries. The entries are
+    /// moved with the returned [`Migration`], see the [`migration`](crate::store::migration)
+    /// module.
+    pub fn from_legacy(
+        legacy: collections::TreeMap<K, V>,
+    ) -> Migration<collections::TreeMap<K, V>, Self>
+    where
+        K: BorshDeserialize + Clone,
+        V: BorshDeserialize,
+    {
+        let target = Self::new(legacy.prefix());
+        Migration::new(legacy, target)
+    }
 }
 
 impl<K, V, H> TreeMap<K, V, H>
@@ -164,6 +267,14 @@ where
     V: BorshSerialize,
     H: ToKey,
 {
+    /// Initialize a [`TreeMap`] with a custom hash function.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::{TreeMap, key::Keccak256};
+    ///
+    /// let map = TreeMap::<String, String, Keccak256>::with_hasher(b"m");
+    /// ```
     pub fn with_hasher<S>(prefix: S) -> Self
     where
         S: IntoStorageKey,
@@ -816,7 +927,7 @@ where
     V: BorshSerialize,
     H: ToKey,
 {
-    /// An iterator visiting all key-value pairs in arbitrary order.
+    /// An iterator visiting all key-value pairs in ascending order of the keys.
     /// The iterator element type is `(&'a K, &'a V)`.
     pub fn iter(&self) -> Iter<K, V, H>
     where
@@ -825,7 +936,7 @@ where
         Iter::new(self)
     }
 
-    /// An iterator visiting all key-value pairs in arbitrary order,
+    /// An iterator visiting all key-value pairs in ascending order of the keys,
     /// with exclusive references to the values.
     /// The iterator element type is `(&'a K, &'a mut V)`.
     pub fn iter_mut(&mut self) -> IterMut<K, V, H>
@@ -835,7 +946,7 @@ where
         IterMut::new(self)
     }
 
-    /// An iterator visiting all keys in arbitrary order.
+    /// An iterator visiting all keys in ascending order.
     /// The iterator element type is `&'a K`.
     pub fn keys(&self) -> Keys<K>
     where
@@ -844,7 +955,7 @@ where
         Keys::new(&self.tree)
     }
 
-    /// An iterator visiting all values in arbitrary order.
+    /// An iterator visiting all values in ascending order of the keys.
     /// The iterator element type is `&'a V`.
     pub fn values(&self) -> Values<K, V, H>
     where
@@ -853,7 +964,7 @@ where
         Values::new(self)
     }
 
-    /// A mutable iterator visiting all values in arbitrary order.
+    /// A mutable iterator visiting all values in ascending order of the keys.
     /// The iterator element type is `&'a mut V`.
     pub fn values_mut(&mut self) -> ValuesMut<K, V, H>
     where
@@ -900,6 +1011,54 @@ where
         Range::new(self, (range.start_bound(), range.end_bound()))
     }
 
+    /// Returns a page of at most `limit` key-value pairs in ascending order of the keys, starting
+    /// after the `cursor` key, or from the first key if `cursor` is `None`. The cursor of the
+    /// next page is the last key of the page, if there are more entries after it.
+    ///
+    /// The cursor key does not need to be in the map, so the pagination can continue even if the
+    /// last key of the previous page was removed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::TreeMap;
+    ///
+    /// let mut map = TreeMap::new(b"t");
+    /// map.insert(3, "c".to_string());
+    /// map.insert(1, "a".to_string());
+    /// map.insert(2, "b".to_string());
+    ///
+    /// let page = map.range_after(None, 2);
+    /// assert_eq!(page.items, [(&1, &"a".to_string()), (&2, &"b".to_string())]);
+    /// assert_eq!(page.next_cursor, Some(2));
+    ///
+    /// let page = map.range_after(page.next_cursor.as_ref(), 2);
+    /// assert_eq!(page.items, [(&3, &"c".to_string())]);
+    /// assert_eq!(page.next_cursor, None);
+    /// ```
+    pub fn range_after<Q>(&self, cursor: Option<&Q>, limit: u32) -> Page<(&K, &V), K>
+    where
+        K: BorshDeserialize + Borrow<Q> + Clone,
+        V: BorshDeserialize,
+        Q: ?Sized + Ord,
+    {
+        if limit == 0 {
+            env::panic_str(ERR_ZERO_LIMIT);
+        }
+        let start = cursor.map_or(Bound::Unbounded, Bound::Excluded);
+        let mut range = Range::new(self, (start, Bound::Unbounded));
+        let items: Vec<_> = range.by_ref().take(limit as usize).collect();
+        let next_cursor = match (items.last(), range.next()) {
+            (Some((last, _)), Some(_)) => Some((*last).clone()),
+            _ => None,
+        };
+        Page { items, next_cursor }
+    }
+
     /// Constructs a mutable double-ended iterator over a sub-range of elements in the map.
     /// The simplest way is to use the range syntax `min..max`, thus `range(min..max)` will
     /// yield elements from min (inclusive) to max (exclusive).
@@ -997,6 +1156,180 @@ where
     {
         Entry::new(self.values.entry(key), &mut self.tree)
     }
+
+    /// Returns the storage prefix of the collection nested under `key`, derived from the prefix
+    /// of the map and the key. See [`TreeMap::get_or_insert_child`].
+    pub fn child_prefix<Q: ?Sized>(&self, key: &Q) -> Vec<u8>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize,
+    {
+        self.values.child_prefix(key)
+    }
+
+    /// Returns a mutable reference to the collection nested under `key`, creating it with the
+    /// prefix from [`TreeMap::child_prefix`] if the entry is vacant.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::{TreeMap, Vector};
+    ///
+    /// let mut map: TreeMap<String, Vector<u8>> = TreeMap::new(b"m");
+    ///
+    /// map.get_or_insert_child("alice".to_string()).push(1);
+    /// map.get_or_insert_child("alice".to_string()).push(2);
+    ///
+    /// assert_eq!(map["alice"].len(), 2);
+    /// ```
+    pub fn get_or_insert_child(&mut self, key: K) -> &mut V
+    where
+        K: Clone + BorshDeserialize,
+        V: NestedCollection,
+    {
+        let prefix = self.child_prefix(&key);
+        self.entry(key).or_insert_with(|| V::new_nested(prefix))
+    }
+
+    /// Removes the collection nested under `key` from the map and clears all of its values from
+    /// storage. Returns `true` if the key was in the map.
+    ///
+    /// Removing the entry with [`TreeMap::remove`] only removes the metadata of the nested
+    /// collection, and leaves its values in storage.
+    pub fn remove_and_clear<Q: ?Sized>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q> + BorshDeserialize + Clone,
+        Q: BorshSerialize + ToOwned<Owned = K> + Ord,
+        V: NestedCollection,
+    {
+        match self.remove(key) {
+            Some(mut child) => {
+                child.clear_nested();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Checks the slots holding the nodes of the tree, that the nodes reachable from the root
+    /// form an ordered and balanced tree holding all of the nodes, and that the key of every node
+    /// has a value which can be deserialized, returning the inconsistencies found. The cached
+    /// changes are flushed first, and each node and value is then read again from storage.
+    ///
+    /// The values are stored under a hash of their key, so values whose key was lost cannot be
+    /// found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::TreeMap;
+    ///
+    /// let mut map = TreeMap::new(b"t");
+    /// map.insert(1u32, "a".to_string());
+    /// map.insert(2u32, "b".to_string());
+    /// assert!(map.validate().is_empty());
+    /// ```
+    pub fn validate(&mut self) -> Vec<Inconsistency>
+    where
+        K: BorshDeserialize,
+    {
+        self.values.reset_cache();
+        let mut nodes = BTreeMap::new();
+        let mut issues = self.tree.nodes.validate_with(|FreeListIndex(index), node| {
+            nodes.insert(index, node);
+        });
+
+        // Nodes reached from the root in pre-order, with the node linking to each of them.
+        let mut reached = Vec::new();
+        let mut parents = BTreeMap::new();
+        // Links to follow, with the nodes bounding the keys of the subtree from below and above.
+        let mut links = Vec::new();
+        if let Some(FreeListIndex(root)) = self.tree.root {
+            links.push((None, root, None, None));
+        }
+        while let Some((from, index, lower, upper)) = links.pop() {
+            let node = match nodes.get(&index) {
+                Some(node) if !parents.contains_key(&index) => node,
+                _ => {
+                    issues.push(Inconsistency::InvalidLink { from, to: index });
+                    continue;
+                }
+            };
+            parents.insert(index, from);
+            reached.push(index);
+            let key_of = |index: &u32| &nodes[index].key;
+            if lower.map_or(false, |l| key_of(&l) >= &node.key)
+                || upper.map_or(false, |u| key_of(&u) <= &node.key)
+            {
+                issues.push(Inconsistency::UnorderedNode { index });
+            }
+            if let Some(FreeListIndex(rgt)) = node.rgt {
+                links.push((Some(index), rgt, Some(index), upper));
+            }
+            if let Some(FreeListIndex(lft)) = node.lft {
+                links.push((Some(index), lft, lower, Some(index)));
+            }
+        }
+
+        // Children are reached after their parent, so their heights are known in reverse order.
+        let mut heights = BTreeMap::new();
+        for &index in reached.iter().rev() {
+            let node = &nodes[&index];
+            let height_of = |link: Option<FreeListIndex>| {
+                link.filter(|FreeListIndex(child)| parents.get(child) == Some(&Some(index)))
+                    .and_then(|FreeListIndex(child)| heights.get(&child).copied())
+                    .unwrap_or(0)
+            };
+            let (lft, rgt) = (height_of(node.lft), height_of(node.rgt));
+            let height = 1 + core::cmp::max(lft, rgt);
+            if node.ht != height || core::cmp::max(lft, rgt) - core::cmp::min(lft, rgt) > 1 {
+                issues.push(Inconsistency::InvalidHeight { index });
+            }
+            heights.insert(index, height);
+        }
+
+        for (&index, node) in &nodes {
+            if !parents.contains_key(&index) {
+                issues.push(Inconsistency::OrphanedNode { index });
+            }
+            match self.values.read_stored(&node.key) {
+                None => issues.push(Inconsistency::DanglingIndex { index }),
+                Some(Err(_)) => issues.push(Inconsistency::CorruptedEntry { index }),
+                Some(Ok(_)) => {}
+            }
+        }
+        issues
+    }
+
+    /// Repairs the map after [`validate`](Self::validate) found inconsistencies, which are
+    /// returned. Like [`validate`](Self::validate), it flushes the cached changes first, and
+    /// nothing else is written if there are no inconsistencies.
+    ///
+    /// The slots of the nodes are repaired first, and the tree is then rebuilt from the keys of
+    /// the remaining nodes which have a value. The values which cannot be deserialized are
+    /// removed.
+    pub fn repair(&mut self) -> Vec<Inconsistency>
+    where
+        K: BorshDeserialize + Clone,
+    {
+        let issues = self.validate();
+        if issues.is_empty() {
+            return issues;
+        }
+        self.tree.nodes.repair();
+        let mut keys: Vec<K> = self.tree.nodes.drain().map(|node| node.key).collect();
+        keys.sort();
+        keys.dedup();
+        self.tree.root = None;
+        for key in keys {
+            match self.values.read_stored(&key) {
+                Some(Ok(_)) => self.tree.internal_insert(key),
+                Some(Err(_)) => self.values.set(key, None),
+                None => {}
+            }
+        }
+        self.flush();
+        issues
+    }
 }
 
 impl<K, V, H> TreeMap<K, V, H>
@@ -1018,6 +1351,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::store::Inconsistency;
     use crate::test_utils::test_env::setup_free;
     use crate::test_utils::{next_trie_id, test_env};
 
@@ -1485,6 +1819,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn test_insert_8_remove_4_regression() {
         let insert = vec![882, 398, 161, 76];
         let remove = vec![242, 687, 860, 811];
@@ -2117,4 +2452,53 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn range_after_pagination() {
+        let mut map = TreeMap::new(b"t");
+        map.extend((0u32..25).map(|i| (i * 2, i)));
+
+        let mut collected = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = map.range_after(cursor.as_ref(), 10);
+            assert!(page.items.len() <= 10);
+            collected.extend(page.items.into_iter().map(|(k, v)| (*k, *v)));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert!(collected.iter().copied().eq(map.iter().map(|(k, v)| (*k, *v))));
+
+        // The cursor key does not have to be in the map.
+        let page = map.range_after(Some(&5), 2);
+        assert_eq!(page.items, [(&6, &3), (&8, &4)]);
+        assert_eq!(page.next_cursor, Some(8));
+        assert!(map.range_after(Some(&48), 2).items.is_empty());
+    }
+
+    #[test]
+    fn validate_and_repair() {
+        let mut map = TreeMap::new(b"t");
+        for key in 0u32..10 {
+            map.insert(key, key * 2);
+        }
+        assert!(map.validate().is_empty());
+
+        // Detach the left subtree of the root, and remove the value of the largest key.
+        let root = map.tree.root.unwrap();
+        let left = map.tree.nodes.get_mut(root).unwrap().lft.take().unwrap();
+        map.values.set(9, None);
+        let issues = map.validate();
+        assert!(issues.contains(&Inconsistency::InvalidHeight { index: root.0 }));
+        assert!(issues.contains(&Inconsistency::OrphanedNode { index: left.0 }));
+        assert!(issues.iter().any(|issue| matches!(issue, Inconsistency::DanglingIndex { .. })));
+
+        assert_eq!(map.repair(), issues);
+        assert!(map.validate().is_empty());
+        assert!(is_balanced(&map, map.tree.root.unwrap()));
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), (0..9).collect::<Vec<_>>());
+        assert_eq!(map.get(&4), Some(&8));
+    }
 }
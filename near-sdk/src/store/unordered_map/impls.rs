@@ -4,12 +4,13 @@ use borsh::{BorshDeserialize, BorshSerialize};
 
 use super::{ToKey, UnorderedMap, ERR_NOT_EXIST};
 use crate::env;
+use crate::store::serializer::{ValueDeserializer, ValueSerializer};
 
-impl<K, V, H> Extend<(K, V)> for UnorderedMap<K, V, H>
+impl<K, V, H, S> Extend<(K, V)> for UnorderedMap<K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
     fn extend<I>(&mut self, iter: I)
     where
@@ -21,11 +22,11 @@ where
     }
 }
 
-impl<K, V, H, Q: ?Sized> core::ops::Index<&Q> for UnorderedMap<K, V, H>
+impl<K, V, H, S, Q: ?Sized> core::ops::Index<&Q> for UnorderedMap<K, V, H, S>
 where
     K: BorshSerialize + Ord + Clone + Borrow<Q>,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 
     Q: BorshSerialize + ToOwned<Owned = K>,
 {
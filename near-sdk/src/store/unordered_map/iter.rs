@@ -2,31 +2,32 @@ use std::iter::FusedIterator;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 
-use super::{LookupMap, ToKey, UnorderedMap, ValueAndIndex, ERR_INCONSISTENT_STATE};
+use super::{IndexedValue, LookupMap, ToKey, UnorderedMap, ValueAndIndex, ERR_INCONSISTENT_STATE};
+use crate::store::serializer::{Borsh, ValueDeserializer, ValueSerializer};
 use crate::{env, store::free_list};
 
-impl<'a, K, V, H> IntoIterator for &'a UnorderedMap<K, V, H>
+impl<'a, K, V, H, S> IntoIterator for &'a UnorderedMap<K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
     type Item = (&'a K, &'a V);
-    type IntoIter = Iter<'a, K, V, H>;
+    type IntoIter = Iter<'a, K, V, H, S>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
 
-impl<'a, K, V, H> IntoIterator for &'a mut UnorderedMap<K, V, H>
+impl<'a, K, V, H, S> IntoIterator for &'a mut UnorderedMap<K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
     type Item = (&'a K, &'a mut V);
-    type IntoIter = IterMut<'a, K, V, H>;
+    type IntoIter = IterMut<'a, K, V, H, S>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter_mut()
@@ -36,34 +37,34 @@ where
 /// An iterator over elements of a [`UnorderedMap`].
 ///
 /// This `struct` is created by the `iter` method on [`UnorderedMap`].
-pub struct Iter<'a, K, V, H>
+pub struct Iter<'a, K, V, H, S = Borsh>
 where
     K: BorshSerialize + Ord + BorshDeserialize,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
     /// Values iterator which contains empty and filled cells.
     keys: free_list::Iter<'a, K>,
     /// Reference to underlying map to lookup values with `keys`.
-    values: &'a LookupMap<K, ValueAndIndex<V>, H>,
+    values: &'a LookupMap<K, ValueAndIndex<V>, H, IndexedValue<S>>,
 }
 
-impl<'a, K, V, H> Iter<'a, K, V, H>
+impl<'a, K, V, H, S> Iter<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
-    pub(super) fn new(map: &'a UnorderedMap<K, V, H>) -> Self {
+    pub(super) fn new(map: &'a UnorderedMap<K, V, H, S>) -> Self {
         Self { keys: map.keys.iter(), values: &map.values }
     }
 }
 
-impl<'a, K, V, H> Iterator for Iter<'a, K, V, H>
+impl<'a, K, V, H, S> Iterator for Iter<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
     type Item = (&'a K, &'a V);
 
@@ -87,26 +88,26 @@ where
     }
 }
 
-impl<'a, K, V, H> ExactSizeIterator for Iter<'a, K, V, H>
+impl<'a, K, V, H, S> ExactSizeIterator for Iter<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
 }
-impl<'a, K, V, H> FusedIterator for Iter<'a, K, V, H>
+impl<'a, K, V, H, S> FusedIterator for Iter<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
 }
 
-impl<'a, K, V, H> DoubleEndedIterator for Iter<'a, K, V, H>
+impl<'a, K, V, H, S> DoubleEndedIterator for Iter<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
         <Self as DoubleEndedIterator>::nth_back(self, 0)
@@ -123,31 +124,31 @@ where
 /// A mutable iterator over elements of a [`UnorderedMap`].
 ///
 /// This `struct` is created by the `iter_mut` method on [`UnorderedMap`].
-pub struct IterMut<'a, K, V, H>
+pub struct IterMut<'a, K, V, H, S = Borsh>
 where
     K: BorshSerialize + Ord + BorshDeserialize,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
     /// Values iterator which contains empty and filled cells.
     keys: free_list::Iter<'a, K>,
     /// Exclusive reference to underlying map to lookup values with `keys`.
-    values: &'a mut LookupMap<K, ValueAndIndex<V>, H>,
+    values: &'a mut LookupMap<K, ValueAndIndex<V>, H, IndexedValue<S>>,
 }
 
-impl<'a, K, V, H> IterMut<'a, K, V, H>
+impl<'a, K, V, H, S> IterMut<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
-    pub(super) fn new(map: &'a mut UnorderedMap<K, V, H>) -> Self {
+    pub(super) fn new(map: &'a mut UnorderedMap<K, V, H, S>) -> Self {
         Self { keys: map.keys.iter(), values: &mut map.values }
     }
     fn get_entry_mut<'b>(&'b mut self, key: &'a K) -> (&'a K, &'a mut V)
     where
         K: Clone,
-        V: BorshDeserialize,
+        S: ValueDeserializer<V>,
     {
         let entry =
             self.values.get_mut(key).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
@@ -162,11 +163,11 @@ where
     }
 }
 
-impl<'a, K, V, H> Iterator for IterMut<'a, K, V, H>
+impl<'a, K, V, H, S> Iterator for IterMut<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
     type Item = (&'a K, &'a mut V);
 
@@ -188,26 +189,26 @@ where
     }
 }
 
-impl<'a, K, V, H> ExactSizeIterator for IterMut<'a, K, V, H>
+impl<'a, K, V, H, S> ExactSizeIterator for IterMut<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
 }
-impl<'a, K, V, H> FusedIterator for IterMut<'a, K, V, H>
+impl<'a, K, V, H, S> FusedIterator for IterMut<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
 }
 
-impl<'a, K, V, H> DoubleEndedIterator for IterMut<'a, K, V, H>
+impl<'a, K, V, H, S> DoubleEndedIterator for IterMut<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
         <Self as DoubleEndedIterator>::nth_back(self, 0)
@@ -233,10 +234,10 @@ impl<'a, K> Keys<'a, K>
 where
     K: BorshSerialize + BorshDeserialize,
 {
-    pub(super) fn new<V, H>(map: &'a UnorderedMap<K, V, H>) -> Self
+    pub(super) fn new<V, H, S>(map: &'a UnorderedMap<K, V, H, S>) -> Self
     where
         K: Ord,
-        V: BorshSerialize,
+        S: ValueSerializer<V>,
         H: ToKey,
     {
         Self { inner: map.keys.iter() }
@@ -277,31 +278,31 @@ where
 /// An iterator over the values of a [`UnorderedMap`].
 ///
 /// This `struct` is created by the `values` method on [`UnorderedMap`].
-pub struct Values<'a, K, V, H>
+pub struct Values<'a, K, V, H, S = Borsh>
 where
     K: BorshSerialize + Ord + BorshDeserialize,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
-    inner: Iter<'a, K, V, H>,
+    inner: Iter<'a, K, V, H, S>,
 }
 
-impl<'a, K, V, H> Values<'a, K, V, H>
+impl<'a, K, V, H, S> Values<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
-    pub(super) fn new(map: &'a UnorderedMap<K, V, H>) -> Self {
+    pub(super) fn new(map: &'a UnorderedMap<K, V, H, S>) -> Self {
         Self { inner: map.iter() }
     }
 }
 
-impl<'a, K, V, H> Iterator for Values<'a, K, V, H>
+impl<'a, K, V, H, S> Iterator for Values<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
     type Item = &'a V;
 
@@ -322,26 +323,26 @@ where
     }
 }
 
-impl<'a, K, V, H> ExactSizeIterator for Values<'a, K, V, H>
+impl<'a, K, V, H, S> ExactSizeIterator for Values<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
 }
-impl<'a, K, V, H> FusedIterator for Values<'a, K, V, H>
+impl<'a, K, V, H, S> FusedIterator for Values<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
 }
 
-impl<'a, K, V, H> DoubleEndedIterator for Values<'a, K, V, H>
+impl<'a, K, V, H, S> DoubleEndedIterator for Values<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
         <Self as DoubleEndedIterator>::nth_back(self, 0)
@@ -355,31 +356,31 @@ where
 /// A mutable iterator over values of a [`UnorderedMap`].
 ///
 /// This `struct` is created by the `values_mut` method on [`UnorderedMap`].
-pub struct ValuesMut<'a, K, V, H>
+pub struct ValuesMut<'a, K, V, H, S = Borsh>
 where
     K: BorshSerialize + Ord + BorshDeserialize,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
-    inner: IterMut<'a, K, V, H>,
+    inner: IterMut<'a, K, V, H, S>,
 }
 
-impl<'a, K, V, H> ValuesMut<'a, K, V, H>
+impl<'a, K, V, H, S> ValuesMut<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
-    pub(super) fn new(map: &'a mut UnorderedMap<K, V, H>) -> Self {
+    pub(super) fn new(map: &'a mut UnorderedMap<K, V, H, S>) -> Self {
         Self { inner: map.iter_mut() }
     }
 }
 
-impl<'a, K, V, H> Iterator for ValuesMut<'a, K, V, H>
+impl<'a, K, V, H, S> Iterator for ValuesMut<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
     type Item = &'a mut V;
 
@@ -400,26 +401,26 @@ where
     }
 }
 
-impl<'a, K, V, H> ExactSizeIterator for ValuesMut<'a, K, V, H>
+impl<'a, K, V, H, S> ExactSizeIterator for ValuesMut<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
 }
-impl<'a, K, V, H> FusedIterator for ValuesMut<'a, K, V, H>
+impl<'a, K, V, H, S> FusedIterator for ValuesMut<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
 }
 
-impl<'a, K, V, H> DoubleEndedIterator for ValuesMut<'a, K, V, H>
+impl<'a, K, V, H, S> DoubleEndedIterator for ValuesMut<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
         <Self as DoubleEndedIterator>::nth_back(self, 0)
@@ -430,25 +431,25 @@ where
     }
 }
 
-/// A draining iterator for [`UnorderedMap<K, V, H>`].
+/// A draining iterator for [`UnorderedMap<K, V, H, S>`].
 #[derive(Debug)]
-pub struct Drain<'a, K, V, H>
+pub struct Drain<'a, K, V, H, S = Borsh>
 where
     K: BorshSerialize + BorshDeserialize + Ord,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
     keys: free_list::Drain<'a, K>,
-    values: &'a mut LookupMap<K, ValueAndIndex<V>, H>,
+    values: &'a mut LookupMap<K, ValueAndIndex<V>, H, IndexedValue<S>>,
 }
 
-impl<'a, K, V, H> Drain<'a, K, V, H>
+impl<'a, K, V, H, S> Drain<'a, K, V, H, S>
 where
     K: BorshSerialize + BorshDeserialize + Ord,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
-    pub(crate) fn new(list: &'a mut UnorderedMap<K, V, H>) -> Self {
+    pub(crate) fn new(list: &'a mut UnorderedMap<K, V, H, S>) -> Self {
         Self { keys: list.keys.drain(), values: &mut list.values }
     }
 
@@ -459,7 +460,7 @@ where
     fn remove_value(&mut self, key: K) -> (K, V)
     where
         K: Clone,
-        V: BorshDeserialize,
+        S: ValueDeserializer<V>,
     {
         let value = self
             .values
@@ -471,11 +472,11 @@ where
     }
 }
 
-impl<'a, K, V, H> Iterator for Drain<'a, K, V, H>
+impl<'a, K, V, H, S> Iterator for Drain<'a, K, V, H, S>
 where
     K: BorshSerialize + BorshDeserialize + Ord + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
     type Item = (K, V);
 
@@ -494,27 +495,27 @@ where
     }
 }
 
-impl<'a, K, V, H> ExactSizeIterator for Drain<'a, K, V, H>
+impl<'a, K, V, H, S> ExactSizeIterator for Drain<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
 }
 
-impl<'a, K, V, H> FusedIterator for Drain<'a, K, V, H>
+impl<'a, K, V, H, S> FusedIterator for Drain<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
 }
 
-impl<'a, K, V, H> DoubleEndedIterator for Drain<'a, K, V, H>
+impl<'a, K, V, H, S> DoubleEndedIterator for Drain<'a, K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + Clone,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
         let key = self.keys.next_back()?;
@@ -3,18 +3,23 @@ mod impls;
 mod iter;
 
 use std::borrow::Borrow;
+use std::marker::PhantomData;
 use std::{fmt, mem};
 
+use borsh::maybestd::io;
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use crate::store::key::{Sha256, ToKey};
-use crate::{env, IntoStorageKey};
+use crate::store::serializer::{Borsh, ValueDeserializer, ValueSerializer};
+use crate::{collections, env, IntoStorageKey};
 
 pub use entry::{Entry, OccupiedEntry, VacantEntry};
 
 pub use self::iter::{Drain, Iter, IterMut, Keys, Values, ValuesMut};
 use super::free_list::FreeListIndex;
-use super::{FreeList, LookupMap, ERR_INCONSISTENT_STATE, ERR_NOT_EXIST};
+use super::migration::Migration;
+use super::nested::NestedCollection;
+use super::{FreeList, Inconsistency, LookupMap, Page, ERR_INCONSISTENT_STATE, ERR_NOT_EXIST};
 
 /// A lazily loaded storage map that stores its content directly on the storage trie.
 /// This structure is similar to [`near_sdk::store::LookupMap`](crate::store::LookupMap), except
@@ -74,30 +79,92 @@ use super::{FreeList, LookupMap, ERR_INCONSISTENT_STATE, ERR_NOT_EXIST};
 /// *stat += random_stat_buff();
 /// ```
 ///
+/// # Migrating from `collections::UnorderedMap`
+///
+/// The storage layout of this map is not compatible with
+/// [`collections::UnorderedMap`](crate::collections::UnorderedMap). [`from_legacy`] moves the
+/// entries into a map with the prefix of the legacy map, in batches which can be split across
+/// several calls, see the [`migration`](crate::store::migration) module:
+///
+/// ```
+/// use near_sdk::{collections, store};
+///
+/// let mut old: collections::UnorderedMap<String, u8> = collections::UnorderedMap::new(b"m");
+/// old.insert(&"a".to_string(), &1);
+///
+/// let mut migration = store::UnorderedMap::from_legacy(old);
+/// while !migration.migrate(100) {}
+/// let new = migration.finish();
+/// assert_eq!(new["a"], 1);
+/// ```
+///
+/// The values are accessed by reference and modifications are written to storage when the map is
+/// [`flush`]ed or dropped. The methods of `collections::UnorderedMap` map to the following:
+///
+/// | `collections::UnorderedMap` | `store::UnorderedMap`               |
+/// |-----------------------------|-------------------------------------|
+/// | `get(&k)`                   | `get(&k).cloned()` or `get_mut(&k)` |
+/// | `insert(&k, &v)`            | `insert(k, v)`                      |
+/// | `keys_as_vector()`          | `keys()`                            |
+/// | `values_as_vector()`        | `values()`                          |
+/// | `to_vec()`                  | `iter().collect()`                  |
+///
 /// [`with_hasher`]: Self::with_hasher
-pub struct UnorderedMap<K, V, H = Sha256>
+/// [`from_legacy`]: Self::from_legacy
+/// [`flush`]: Self::flush
+pub struct UnorderedMap<K, V, H = Sha256, S = Borsh>
 where
     K: BorshSerialize + Ord,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
     keys: FreeList<K>,
-    values: LookupMap<K, ValueAndIndex<V>, H>,
+    values: LookupMap<K, ValueAndIndex<V>, H, IndexedValue<S>>,
 }
 
-#[derive(BorshSerialize, BorshDeserialize)]
 struct ValueAndIndex<V> {
     value: V,
     key_index: FreeListIndex,
 }
 
+/// Serializes the [`ValueAndIndex`] entries of the map as the value serialized with `S`, followed
+/// by the index of the key. With [`Borsh`], this is the same layout as deriving borsh would give.
+struct IndexedValue<S>(PhantomData<S>);
+
+impl<V, S> ValueSerializer<ValueAndIndex<V>> for IndexedValue<S>
+where
+    S: ValueSerializer<V>,
+{
+    fn serialize_value(entry: &ValueAndIndex<V>, buffer: &mut Vec<u8>) -> io::Result<()> {
+        S::serialize_value(&entry.value, buffer)?;
+        BorshSerialize::serialize(&entry.key_index, buffer)
+    }
+}
+
+impl<V, S> ValueDeserializer<ValueAndIndex<V>> for IndexedValue<S>
+where
+    S: ValueDeserializer<V>,
+{
+    fn deserialize_value(bytes: &[u8]) -> io::Result<ValueAndIndex<V>> {
+        let index_start = bytes
+            .len()
+            .checked_sub(mem::size_of::<u32>())
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        let (value, key_index) = bytes.split_at(index_start);
+        Ok(ValueAndIndex {
+            value: S::deserialize_value(value)?,
+            key_index: FreeListIndex::try_from_slice(key_index)?,
+        })
+    }
+}
+
 //? Manual implementations needed only because borsh derive is leaking field types
 // https://github.com/near/borsh-rs/issues/41
-impl<K, V, H> BorshSerialize for UnorderedMap<K, V, H>
+impl<K, V, H, S> BorshSerialize for UnorderedMap<K, V, H, S>
 where
     K: BorshSerialize + Ord,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
     fn serialize<W: borsh::maybestd::io::Write>(
         &self,
@@ -109,11 +176,11 @@ where
     }
 }
 
-impl<K, V, H> BorshDeserialize for UnorderedMap<K, V, H>
+impl<K, V, H, S> BorshDeserialize for UnorderedMap<K, V, H, S>
 where
     K: BorshSerialize + Ord,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
     fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
         Ok(Self {
@@ -123,22 +190,22 @@ where
     }
 }
 
-impl<K, V, H> Drop for UnorderedMap<K, V, H>
+impl<K, V, H, S> Drop for UnorderedMap<K, V, H, S>
 where
     K: BorshSerialize + Ord,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
     fn drop(&mut self) {
         self.flush()
     }
 }
 
-impl<K, V, H> fmt::Debug for UnorderedMap<K, V, H>
+impl<K, V, H, S> fmt::Debug for UnorderedMap<K, V, H, S>
 where
     K: BorshSerialize + Ord + BorshDeserialize + fmt::Debug,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("UnorderedMap")
@@ -148,7 +215,7 @@ where
     }
 }
 
-impl<K, V> UnorderedMap<K, V, Sha256>
+impl<K, V> UnorderedMap<K, V, Sha256, Borsh>
 where
     K: BorshSerialize + Ord,
     V: BorshSerialize,
@@ -172,13 +239,41 @@ where
     {
         Self::with_hasher(prefix)
     }
+
+    /// Starts converting `legacy` into a map with the same prefix and entries. The entries are
+    /// moved with the returned [`Migration`], see the [`migration`](crate::store::migration)
+    /// module.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::{collections, store};
+    ///
+    /// let mut legacy: collections::UnorderedMap<String, u8> = collections::UnorderedMap::new(b"m");
+    /// legacy.insert(&"a".to_string(), &1);
+    ///
+    /// let mut migration = store::UnorderedMap::from_legacy(legacy);
+    /// assert!(migration.migrate(10));
+    /// let map = migration.finish();
+    /// assert_eq!(map["a"], 1);
+    /// ```
+    pub fn from_legacy(
+        legacy: collections::UnorderedMap<K, V>,
+    ) -> Migration<collections::UnorderedMap<K, V>, Self>
+    where
+        K: BorshDeserialize + Clone,
+        V: BorshDeserialize,
+    {
+        let target = Self::new(legacy.prefix());
+        Migration::new(legacy, target)
+    }
 }
 
-impl<K, V, H> UnorderedMap<K, V, H>
+impl<K, V, H, S> UnorderedMap<K, V, H, S>
 where
     K: BorshSerialize + Ord,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
     /// Initialize a [`UnorderedMap`] with a custom hash function.
     ///
@@ -188,9 +283,17 @@ where
     ///
     /// let map = UnorderedMap::<String, String, Keccak256>::with_hasher(b"m");
     /// ```
-    pub fn with_hasher<S>(prefix: S) -> Self
+    ///
+    /// The serializer of the values can be set in the same way, see the
+    /// [`serializer`](crate::store::serializer) module:
+    /// ```
+    /// use near_sdk::store::{UnorderedMap, key::Sha256, serializer::Json};
+    ///
+    /// let map = UnorderedMap::<String, String, Sha256, Json>::with_hasher(b"m");
+    /// ```
+    pub fn with_hasher<P>(prefix: P) -> Self
     where
-        S: IntoStorageKey,
+        P: IntoStorageKey,
     {
         let mut vec_key = prefix.into_storage_key();
         let map_key = [vec_key.as_slice(), b"m"].concat();
@@ -247,7 +350,7 @@ where
     pub fn clear(&mut self)
     where
         K: BorshDeserialize + Clone,
-        V: BorshDeserialize,
+        S: ValueDeserializer<V>,
     {
         for k in self.keys.drain() {
             // Set instead of remove to avoid loading the value from storage.
@@ -272,13 +375,55 @@ where
     ///     println!("key: {} val: {}", key, val);
     /// }
     /// ```
-    pub fn iter(&self) -> Iter<K, V, H>
+    pub fn iter(&self) -> Iter<K, V, H, S>
     where
         K: BorshDeserialize,
     {
         Iter::new(self)
     }
 
+    /// Returns a page of at most `limit` key-value pairs, in the order of
+    /// [`UnorderedMap::iter`], with the cursor to request the next page with. The first page is
+    /// requested with a cursor of `0`. Only the entries of the page are loaded from storage, so
+    /// this is cheaper than skipping entries of [`UnorderedMap::iter`].
+    ///
+    /// The cursor is an index in the underlying storage, and the slots of removed keys count
+    /// towards the `limit`, to bound the cost of a page. A page can then have less than `limit`
+    /// entries, or none, even if the end of the map was not reached.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::UnorderedMap;
+    ///
+    /// let mut map = UnorderedMap::new(b"m");
+    /// map.insert("a".to_string(), 1u8);
+    /// map.insert("b".to_string(), 2u8);
+    /// map.insert("c".to_string(), 3u8);
+    ///
+    /// let page = map.paginate(0, 2);
+    /// assert_eq!(page.items, [(&"a".to_string(), &1), (&"b".to_string(), &2)]);
+    ///
+    /// let page = map.paginate(page.next_cursor.unwrap(), 2);
+    /// assert_eq!(page.items, [(&"c".to_string(), &3)]);
+    /// assert_eq!(page.next_cursor, None);
+    /// ```
+    pub fn paginate(&self, from_index: u32, limit: u32) -> Page<(&K, &V)>
+    where
+        K: BorshDeserialize + Clone,
+        S: ValueDeserializer<V>,
+    {
+        self.keys.paginate(from_index, limit).map(|key| {
+            let entry =
+                self.values.get(key).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+            (key, &entry.value)
+        })
+    }
+
     /// An iterator visiting all key-value pairs in arbitrary order,
     /// with exclusive references to the values.
     /// The iterator element type is `(&'a K, &'a mut V)`.
@@ -302,7 +447,7 @@ where
     ///     println!("key: {} val: {}", key, val);
     /// }
     /// ```
-    pub fn iter_mut(&mut self) -> IterMut<K, V, H>
+    pub fn iter_mut(&mut self) -> IterMut<K, V, H, S>
     where
         K: BorshDeserialize,
     {
@@ -350,7 +495,7 @@ where
     ///     println!("{}", val);
     /// }
     /// ```
-    pub fn values(&self) -> Values<K, V, H>
+    pub fn values(&self) -> Values<K, V, H, S>
     where
         K: BorshDeserialize,
     {
@@ -378,7 +523,7 @@ where
     ///     println!("{}", val);
     /// }
     /// ```
-    pub fn values_mut(&mut self) -> ValuesMut<K, V, H>
+    pub fn values_mut(&mut self) -> ValuesMut<K, V, H, S>
     where
         K: BorshDeserialize,
     {
@@ -405,7 +550,7 @@ where
     ///
     /// assert!(a.is_empty());
     /// ```
-    pub fn drain(&mut self) -> Drain<K, V, H>
+    pub fn drain(&mut self) -> Drain<K, V, H, S>
     where
         K: BorshDeserialize,
     {
@@ -413,11 +558,11 @@ where
     }
 }
 
-impl<K, V, H> UnorderedMap<K, V, H>
+impl<K, V, H, S> UnorderedMap<K, V, H, S>
 where
     K: BorshSerialize + Ord,
-    V: BorshSerialize + BorshDeserialize,
     H: ToKey,
+    S: ValueSerializer<V> + ValueDeserializer<V>,
 {
     /// Returns a reference to the value corresponding to the key.
     ///
@@ -613,13 +758,156 @@ where
     {
         Entry::new(self.values.entry(key), &mut self.keys)
     }
+
+    /// Returns the storage prefix of the collection nested under `key`, derived from the prefix
+    /// of the map and the key. See [`UnorderedMap::get_or_insert_child`].
+    pub fn child_prefix<Q: ?Sized>(&self, key: &Q) -> Vec<u8>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize,
+    {
+        self.values.child_prefix(key)
+    }
+
+    /// Returns a mutable reference to the collection nested under `key`, creating it with the
+    /// prefix from [`UnorderedMap::child_prefix`] if the entry is vacant.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::{UnorderedMap, Vector};
+    ///
+    /// let mut map: UnorderedMap<String, Vector<u8>> = UnorderedMap::new(b"m");
+    ///
+    /// map.get_or_insert_child("alice".to_string()).push(1);
+    /// map.get_or_insert_child("alice".to_string()).push(2);
+    ///
+    /// assert_eq!(map["alice"].len(), 2);
+    /// ```
+    pub fn get_or_insert_child(&mut self, key: K) -> &mut V
+    where
+        K: Clone + BorshDeserialize,
+        V: NestedCollection,
+    {
+        let prefix = self.child_prefix(&key);
+        self.entry(key).or_insert_with(|| V::new_nested(prefix))
+    }
+
+    /// Removes the collection nested under `key` from the map and clears all of its values from
+    /// storage. Returns `true` if the key was in the map.
+    ///
+    /// Removing the entry with [`UnorderedMap::remove`] only removes the metadata of the nested
+    /// collection, and leaves its values in storage.
+    pub fn remove_and_clear<Q: ?Sized>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q> + BorshDeserialize,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+        V: NestedCollection,
+    {
+        match self.remove(key) {
+            Some(mut child) => {
+                child.clear_nested();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Checks that every key of the map has an entry which can be deserialized and refers back
+    /// to the key, along with the slots holding the keys, returning the inconsistencies found.
+    /// The cached changes are flushed first, and each key and entry is then read again from
+    /// storage.
+    ///
+    /// The values are stored under a hash of their key, so entries whose key was lost cannot be
+    /// found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::UnorderedMap;
+    ///
+    /// let mut map = UnorderedMap::new(b"m");
+    /// map.insert("a".to_string(), 1u8);
+    /// assert!(map.validate().is_empty());
+    /// ```
+    pub fn validate(&mut self) -> Vec<Inconsistency>
+    where
+        K: BorshDeserialize,
+    {
+        self.values.reset_cache();
+        let mut keys = Vec::new();
+        let mut issues = self.keys.validate_with(|index, key| keys.push((index, key)));
+        for (FreeListIndex(index), key) in keys {
+            match self.values.read_stored(&key) {
+                None => issues.push(Inconsistency::DanglingIndex { index }),
+                Some(Err(_)) => issues.push(Inconsistency::CorruptedEntry { index }),
+                Some(Ok(entry)) if entry.key_index.0 != index => {
+                    issues.push(Inconsistency::IndexMismatch { index, recorded: entry.key_index.0 })
+                }
+                Some(Ok(_)) => {}
+            }
+        }
+        issues
+    }
+
+    /// Repairs the map after [`validate`](Self::validate) found inconsistencies, which are
+    /// returned. Like [`validate`](Self::validate), it flushes the cached changes first, and
+    /// nothing else is written if there are no inconsistencies.
+    ///
+    /// The slots of the keys are repaired first. Keys without an entry are then removed, along
+    /// with the entries which cannot be deserialized. When a key is held by several slots, the
+    /// slot its entry refers to is kept, and entries referring to a slot of another key are
+    /// updated to refer to their key.
+    pub fn repair(&mut self) -> Vec<Inconsistency>
+    where
+        K: BorshDeserialize + Clone,
+    {
+        let issues = self.validate();
+        if issues.is_empty() {
+            return issues;
+        }
+        self.keys.repair();
+        let mut keys = Vec::new();
+        self.keys.validate_with(|index, key| keys.push((index, key)));
+        for (index, key) in keys {
+            match self.values.read_stored(&key) {
+                None => {
+                    self.keys.remove(index);
+                }
+                Some(Err(_)) => {
+                    self.keys.remove(index);
+                    self.values.set(key, None);
+                }
+                Some(Ok(_)) => {
+                    // Read through the cache, to see the entries updated for previous slots.
+                    let recorded = self
+                        .values
+                        .get(&key)
+                        .unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+                        .key_index;
+                    if recorded == index {
+                        continue;
+                    }
+                    if self.keys.get(recorded) == Some(&key) {
+                        self.keys.remove(index);
+                    } else {
+                        self.values
+                            .get_mut(&key)
+                            .unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+                            .key_index = index;
+                    }
+                }
+            }
+        }
+        self.flush();
+        issues
+    }
 }
 
-impl<K, V, H> UnorderedMap<K, V, H>
+impl<K, V, H, S> UnorderedMap<K, V, H, S>
 where
     K: BorshSerialize + Ord,
-    V: BorshSerialize,
     H: ToKey,
+    S: ValueSerializer<V>,
 {
     /// Flushes the intermediate values of the map before this is called when the structure is
     /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
@@ -634,6 +922,9 @@ where
 #[cfg(test)]
 mod tests {
     use super::UnorderedMap;
+    use crate::env;
+    use crate::store::key::{Sha256, ToKey};
+    use crate::store::Inconsistency;
     use crate::test_utils::test_env::setup_free;
     use arbitrary::{Arbitrary, Unstructured};
     use borsh::{BorshDeserialize, BorshSerialize};
@@ -756,4 +1047,64 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn paginate_with_removed_entries() {
+        let mut map = UnorderedMap::new(b"m");
+        map.extend((0u32..30).map(|i| (i, i)));
+        for i in (0..30).filter(|i| i % 3 == 0) {
+            map.remove(&i);
+        }
+
+        let mut collected = Vec::new();
+        let mut cursor = Some(0);
+        while let Some(from_index) = cursor {
+            let page = map.paginate(from_index, 7);
+            assert!(page.items.len() <= 7);
+            collected.extend(page.items.into_iter().map(|(k, v)| (*k, *v)));
+            cursor = page.next_cursor;
+        }
+        assert!(collected.iter().copied().eq(map.iter().map(|(k, v)| (*k, *v))));
+
+        // Removed slots count towards the limit.
+        for i in 0..20 {
+            map.remove(&i);
+        }
+        let page = map.paginate(0, 7);
+        assert!(page.items.is_empty());
+        assert_eq!(page.next_cursor, Some(7));
+    }
+
+    #[test]
+    #[should_panic(expected = "The limit of a page must be greater than zero")]
+    fn paginate_zero_limit() {
+        let mut map = UnorderedMap::new(b"m");
+        map.insert(1u8, 1u8);
+        map.paginate(0, 0);
+    }
+
+    #[test]
+    fn validate_and_repair() {
+        let mut map = UnorderedMap::new(b"m");
+        for (key, value) in [("a", 1u8), ("b", 2), ("c", 3)] {
+            map.insert(key.to_string(), value);
+        }
+        assert!(map.validate().is_empty());
+
+        map.values.set("a".to_string(), None);
+        map.keys.insert("b".to_string());
+        map.flush();
+        env::storage_write(&Sha256::to_key(b"mm", "c", &mut Vec::new()), &[]);
+        let issues = [
+            Inconsistency::DanglingIndex { index: 0 },
+            Inconsistency::CorruptedEntry { index: 2 },
+            Inconsistency::IndexMismatch { index: 3, recorded: 1 },
+        ];
+        assert_eq!(map.validate(), issues);
+
+        assert_eq!(map.repair(), issues);
+        assert!(map.validate().is_empty());
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&"b".to_string(), &2)]);
+        assert!(!map.contains_key("c"));
+    }
 }
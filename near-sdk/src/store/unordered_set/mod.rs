@@ -1,7 +1,7 @@
 mod impls;
 mod iter;
 
-use super::{FreeList, LookupMap, ERR_INCONSISTENT_STATE};
+use super::{FreeList, Inconsistency, LookupMap, Page, ERR_INCONSISTENT_STATE};
 use crate::store::free_list::FreeListIndex;
 use crate::store::key::{Sha256, ToKey};
 use crate::store::unordered_set::iter::{
@@ -78,6 +78,24 @@ use std::fmt;
 /// );
 /// ```
 ///
+/// # Migrating from `collections::UnorderedSet`
+///
+/// The storage layout of this set is not compatible with
+/// [`collections::UnorderedSet`](crate::collections::UnorderedSet), so the elements have to be
+/// copied over to a set with a new prefix:
+///
+/// ```
+/// use near_sdk::{collections, store};
+///
+/// let mut old: collections::UnorderedSet<String> = collections::UnorderedSet::new(b"o");
+/// old.insert(&"a".to_string());
+///
+/// let mut new = store::UnorderedSet::new(b"n");
+/// new.extend(old.iter());
+/// old.clear();
+/// assert!(new.contains("a"));
+/// ```
+///
 /// [`with_hasher`]: Self::with_hasher
 /// [`LookupSet`]: crate::store::LookupSet
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -421,6 +439,42 @@ where
         Iter::new(self)
     }
 
+    /// Returns a page of at most `limit` elements, in the order of [`UnorderedSet::iter`], with
+    /// the cursor to request the next page with. The first page is requested with a cursor of
+    /// `0`. Only the elements of the page are loaded from storage, so this is cheaper than
+    /// skipping elements of [`UnorderedSet::iter`].
+    ///
+    /// The cursor is an index in the underlying storage, and the slots of removed elements count
+    /// towards the `limit`, to bound the cost of a page. A page can then have less than `limit`
+    /// elements, or none, even if the end of the set was not reached.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::UnorderedSet;
+    ///
+    /// let mut set = UnorderedSet::new(b"s");
+    /// set.extend([1, 2, 3, 4]);
+    /// set.remove(&2);
+    ///
+    /// let page = set.paginate(0, 2);
+    /// assert_eq!(page.items, [&1]);
+    ///
+    /// let page = set.paginate(page.next_cursor.unwrap(), 2);
+    /// assert_eq!(page.items, [&3, &4]);
+    /// assert_eq!(page.next_cursor, None);
+    /// ```
+    pub fn paginate(&self, from_index: u32, limit: u32) -> Page<&T>
+    where
+        T: BorshDeserialize,
+    {
+        self.elements.paginate(from_index, limit)
+    }
+
     /// Clears the set, returning all elements in an iterator.
     ///
     /// # Examples
@@ -498,6 +552,78 @@ where
         }
     }
 
+    /// Checks that every element of the set has an index entry which can be deserialized and
+    /// refers back to the element, along with the slots holding the elements, returning the
+    /// inconsistencies found. The cached changes are flushed first, and each element and entry
+    /// is then read again from storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::UnorderedSet;
+    ///
+    /// let mut set = UnorderedSet::new(b"s");
+    /// set.insert("a".to_string());
+    /// assert!(set.validate().is_empty());
+    /// ```
+    pub fn validate(&mut self) -> Vec<Inconsistency>
+    where
+        T: BorshDeserialize,
+    {
+        self.index.reset_cache();
+        let mut elements = Vec::new();
+        let mut issues =
+            self.elements.validate_with(|index, element| elements.push((index, element)));
+        for (FreeListIndex(index), element) in elements {
+            match self.index.read_stored(&element) {
+                None => issues.push(Inconsistency::DanglingIndex { index }),
+                Some(Err(_)) => issues.push(Inconsistency::CorruptedEntry { index }),
+                Some(Ok(FreeListIndex(recorded))) if recorded != index => {
+                    issues.push(Inconsistency::IndexMismatch { index, recorded })
+                }
+                Some(Ok(_)) => {}
+            }
+        }
+        issues
+    }
+
+    /// Repairs the set after [`validate`](Self::validate) found inconsistencies, which are
+    /// returned. Like [`validate`](Self::validate), it flushes the cached changes first, and
+    /// nothing else is written if there are no inconsistencies.
+    ///
+    /// The slots of the elements are repaired first. The index entries of the elements which are
+    /// missing or cannot be deserialized are then rewritten, and when an element is held by
+    /// several slots, the slot its entry refers to is kept.
+    pub fn repair(&mut self) -> Vec<Inconsistency>
+    where
+        T: BorshDeserialize + Clone,
+    {
+        let issues = self.validate();
+        if issues.is_empty() {
+            return issues;
+        }
+        self.elements.repair();
+        let mut elements = Vec::new();
+        self.elements.validate_with(|index, element| elements.push((index, element)));
+        for (_, element) in &elements {
+            if let Some(Err(_)) = self.index.read_stored(element) {
+                self.index.set(element.clone(), None);
+            }
+        }
+        for (index, element) in elements {
+            // Read through the cache, to see the entries updated for previous slots.
+            match self.index.get(&element).copied() {
+                Some(recorded) if recorded == index => {}
+                Some(recorded) if self.elements.get(recorded) == Some(&element) => {
+                    self.elements.remove(index);
+                }
+                _ => self.index.set(element, Some(index)),
+            }
+        }
+        self.flush();
+        issues
+    }
+
     /// Flushes the intermediate values of the map before this is called when the structure is
     /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
     /// in memory.
@@ -510,6 +636,7 @@ where
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
+    use crate::store::Inconsistency;
     use crate::store::UnorderedSet;
     use crate::test_utils::test_env::setup_free;
     use arbitrary::{Arbitrary, Unstructured};
@@ -564,6 +691,7 @@ mod tests {
 
             for _ in s.drain() {}
 
+            #[allow(clippy::never_loop)]
             for _ in &s {
                 panic!("s should be empty!");
             }
@@ -844,4 +972,26 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn validate_and_repair() {
+        let mut set = UnorderedSet::new(b"s");
+        set.extend([1u8, 2]);
+        assert!(set.validate().is_empty());
+
+        set.index.set(1, None);
+        set.elements.insert(2);
+        let issues = [
+            Inconsistency::DanglingIndex { index: 0 },
+            Inconsistency::IndexMismatch { index: 2, recorded: 1 },
+        ];
+        assert_eq!(set.validate(), issues);
+
+        // The elements are the surviving data of a set, so only the duplicate is removed.
+        assert_eq!(set.repair(), issues);
+        assert!(set.validate().is_empty());
+        assert_eq!(set.iter().collect::<Vec<_>>(), [&1, &2]);
+        assert!(set.remove(&1));
+        assert!(set.validate().is_empty());
+    }
 }
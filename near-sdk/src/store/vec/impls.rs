@@ -1,45 +1,44 @@
-use borsh::{BorshDeserialize, BorshSerialize};
-
 use super::iter::{Iter, IterMut};
 use super::{Vector, ERR_INDEX_OUT_OF_BOUNDS};
 use crate::env;
+use crate::store::serializer::{ValueDeserializer, ValueSerializer};
 
-impl<T> Drop for Vector<T>
+impl<T, S> Drop for Vector<T, S>
 where
-    T: BorshSerialize,
+    S: ValueSerializer<T>,
 {
     fn drop(&mut self) {
         self.flush()
     }
 }
 
-impl<'a, T> IntoIterator for &'a Vector<T>
+impl<'a, T, S> IntoIterator for &'a Vector<T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     type Item = &'a T;
-    type IntoIter = Iter<'a, T>;
+    type IntoIter = Iter<'a, T, S>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
 
-impl<'a, T> IntoIterator for &'a mut Vector<T>
+impl<'a, T, S> IntoIterator for &'a mut Vector<T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     type Item = &'a mut T;
-    type IntoIter = IterMut<'a, T>;
+    type IntoIter = IterMut<'a, T, S>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter_mut()
     }
 }
 
-impl<T> Extend<T> for Vector<T>
+impl<T, S> Extend<T> for Vector<T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     fn extend<I>(&mut self, iter: I)
     where
@@ -51,9 +50,9 @@ where
     }
 }
 
-impl<T> core::ops::Index<u32> for Vector<T>
+impl<T, S> core::ops::Index<u32> for Vector<T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     type Output = T;
 
@@ -62,9 +61,9 @@ where
     }
 }
 
-impl<T> core::ops::IndexMut<u32> for Vector<T>
+impl<T, S> core::ops::IndexMut<u32> for Vector<T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     fn index_mut(&mut self, index: u32) -> &mut Self::Output {
         self.get_mut(index).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS))
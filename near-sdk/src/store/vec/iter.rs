@@ -1,26 +1,26 @@
-use borsh::{BorshDeserialize, BorshSerialize};
 use core::{iter::FusedIterator, ops::Range};
 
 use super::{Vector, ERR_INDEX_OUT_OF_BOUNDS};
 use crate::env;
+use crate::store::serializer::{Borsh, ValueDeserializer, ValueSerializer};
 
 /// An iterator over references to each element in the stored vector.
 #[derive(Debug)]
-pub struct Iter<'a, T>
+pub struct Iter<'a, T, S = Borsh>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     /// Underlying vector to iterate through
-    vec: &'a Vector<T>,
+    vec: &'a Vector<T, S>,
     /// Range of indices to iterate.
     range: Range<u32>,
 }
 
-impl<'a, T> Iter<'a, T>
+impl<'a, T, S> Iter<'a, T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
-    pub(super) fn new(vec: &'a Vector<T>) -> Self {
+    pub(super) fn new(vec: &'a Vector<T, S>) -> Self {
         Self { vec, range: Range { start: 0, end: vec.len() } }
     }
 
@@ -30,9 +30,9 @@ where
     }
 }
 
-impl<'a, T> Iterator for Iter<'a, T>
+impl<'a, T, S> Iterator for Iter<'a, T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     type Item = &'a T;
 
@@ -55,12 +55,15 @@ where
     }
 }
 
-impl<'a, T> ExactSizeIterator for Iter<'a, T> where T: BorshSerialize + BorshDeserialize {}
-impl<'a, T> FusedIterator for Iter<'a, T> where T: BorshSerialize + BorshDeserialize {}
+impl<'a, T, S> ExactSizeIterator for Iter<'a, T, S> where
+    S: ValueSerializer<T> + ValueDeserializer<T>
+{
+}
+impl<'a, T, S> FusedIterator for Iter<'a, T, S> where S: ValueSerializer<T> + ValueDeserializer<T> {}
 
-impl<'a, T> DoubleEndedIterator for Iter<'a, T>
+impl<'a, T, S> DoubleEndedIterator for Iter<'a, T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
         <Self as DoubleEndedIterator>::nth_back(self, 0)
@@ -74,22 +77,22 @@ where
 
 /// An iterator over exclusive references to each element of a stored vector.
 #[derive(Debug)]
-pub struct IterMut<'a, T>
+pub struct IterMut<'a, T, S = Borsh>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     /// Mutable reference to vector used to iterate through.
-    vec: &'a mut Vector<T>,
+    vec: &'a mut Vector<T, S>,
     /// Range of indices to iterate.
     range: Range<u32>,
 }
 
-impl<'a, T> IterMut<'a, T>
+impl<'a, T, S> IterMut<'a, T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     /// Creates a new iterator for the given storage vector.
-    pub(crate) fn new(vec: &'a mut Vector<T>) -> Self {
+    pub(crate) fn new(vec: &'a mut Vector<T, S>) -> Self {
         let end = vec.len();
         Self { vec, range: Range { start: 0, end } }
     }
@@ -100,9 +103,9 @@ where
     }
 }
 
-impl<'a, T> IterMut<'a, T>
+impl<'a, T, S> IterMut<'a, T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     fn get_mut<'b>(&'b mut self, at: u32) -> Option<&'a mut T> {
         self.vec.get_mut(at).map(|value| {
@@ -115,9 +118,9 @@ where
     }
 }
 
-impl<'a, T> Iterator for IterMut<'a, T>
+impl<'a, T, S> Iterator for IterMut<'a, T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     type Item = &'a mut T;
 
@@ -140,12 +143,16 @@ where
     }
 }
 
-impl<'a, T> ExactSizeIterator for IterMut<'a, T> where T: BorshSerialize + BorshDeserialize {}
-impl<'a, T> FusedIterator for IterMut<'a, T> where T: BorshSerialize + BorshDeserialize {}
+impl<'a, T, S> ExactSizeIterator for IterMut<'a, T, S> where
+    S: ValueSerializer<T> + ValueDeserializer<T>
+{
+}
+impl<'a, T, S> FusedIterator for IterMut<'a, T, S> where S: ValueSerializer<T> + ValueDeserializer<T>
+{}
 
-impl<'a, T> DoubleEndedIterator for IterMut<'a, T>
+impl<'a, T, S> DoubleEndedIterator for IterMut<'a, T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
         <Self as DoubleEndedIterator>::nth_back(self, 0)
@@ -157,26 +164,26 @@ where
     }
 }
 
-/// A draining iterator for [`Vector<T>`].
+/// A draining iterator for [`Vector<T, S>`].
 #[derive(Debug)]
-pub struct Drain<'a, T>
+pub struct Drain<'a, T, S = Borsh>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     /// Mutable reference to vector used to iterate through.
-    vec: &'a mut Vector<T>,
+    vec: &'a mut Vector<T, S>,
     /// Range of indices to iterate.
     range: Range<u32>,
     /// Range of elements to delete.
     delete_range: Range<u32>,
 }
 
-impl<'a, T> Drain<'a, T>
+impl<'a, T, S> Drain<'a, T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     /// Creates a new iterator for the given storage vector.
-    pub(crate) fn new(vec: &'a mut Vector<T>, range: Range<u32>) -> Self {
+    pub(crate) fn new(vec: &'a mut Vector<T, S>, range: Range<u32>) -> Self {
         Self { vec, delete_range: range.clone(), range }
     }
 
@@ -194,9 +201,9 @@ where
     }
 }
 
-impl<'a, T> Drop for Drain<'a, T>
+impl<'a, T, S> Drop for Drain<'a, T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     fn drop(&mut self) {
         let delete_indices = (self.delete_range.start..self.range.start)
@@ -218,9 +225,9 @@ where
     }
 }
 
-impl<'a, T> Iterator for Drain<'a, T>
+impl<'a, T, S> Iterator for Drain<'a, T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     type Item = T;
 
@@ -252,12 +259,15 @@ where
     }
 }
 
-impl<'a, T> ExactSizeIterator for Drain<'a, T> where T: BorshSerialize + BorshDeserialize {}
-impl<'a, T> FusedIterator for Drain<'a, T> where T: BorshSerialize + BorshDeserialize {}
+impl<'a, T, S> ExactSizeIterator for Drain<'a, T, S> where
+    S: ValueSerializer<T> + ValueDeserializer<T>
+{
+}
+impl<'a, T, S> FusedIterator for Drain<'a, T, S> where S: ValueSerializer<T> + ValueDeserializer<T> {}
 
-impl<'a, T> DoubleEndedIterator for Drain<'a, T>
+impl<'a, T, S> DoubleEndedIterator for Drain<'a, T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
         let delete_idx = self.range.next_back()?;
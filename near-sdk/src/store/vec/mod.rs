@@ -63,8 +63,10 @@ use std::{
 use borsh::{BorshDeserialize, BorshSerialize};
 
 pub use self::iter::{Drain, Iter, IterMut};
-use super::ERR_INCONSISTENT_STATE;
-use crate::{env, IntoStorageKey};
+use super::migration::Migration;
+use super::serializer::{Borsh, ValueDeserializer, ValueSerializer};
+use super::{Inconsistency, Page, ERR_INCONSISTENT_STATE};
+use crate::{collections, env, IntoStorageKey};
 
 use super::IndexMap;
 
@@ -111,19 +113,45 @@ fn expect_consistent_state<T>(val: Option<T>) -> T {
 /// vec.extend([1, 2, 3].iter().copied());
 /// assert!(Iterator::eq(vec.into_iter(), [7, 1, 2, 3].iter()));
 /// ```
-pub struct Vector<T>
+///
+/// # Migrating from `collections::Vector`
+///
+/// The storage layout of this vector is not compatible with
+/// [`collections::Vector`](crate::collections::Vector), which indexes the elements with `u64`
+/// instead of `u32`. [`from_legacy`] rewrites the elements under the prefix of the legacy vector,
+/// in batches which can be split across several calls:
+///
+/// ```
+/// use near_sdk::{collections, store};
+///
+/// let mut old: collections::Vector<String> = collections::Vector::new(b"v");
+/// old.push(&"a".to_string());
+///
+/// let mut migration = store::Vector::from_legacy(old);
+/// while !migration.migrate(100) {}
+/// let new = migration.finish();
+/// assert_eq!(new[0], "a");
+/// ```
+///
+/// The elements are accessed by reference and modifications are written to storage when the
+/// vector is [`flush`]ed or dropped, so `get(i)` becomes `get(i).cloned()` and `replace(i, &v)`
+/// becomes `replace(i, v)`.
+///
+/// [`from_legacy`]: Self::from_legacy
+/// [`flush`]: Self::flush
+pub struct Vector<T, S = Borsh>
 where
-    T: BorshSerialize,
+    S: ValueSerializer<T>,
 {
     pub(crate) len: u32,
-    pub(crate) values: IndexMap<T>,
+    pub(crate) values: IndexMap<T, S>,
 }
 
 //? Manual implementations needed only because borsh derive is leaking field types
 // https://github.com/near/borsh-rs/issues/41
-impl<T> BorshSerialize for Vector<T>
+impl<T, S> BorshSerialize for Vector<T, S>
 where
-    T: BorshSerialize,
+    S: ValueSerializer<T>,
 {
     fn serialize<W: borsh::maybestd::io::Write>(
         &self,
@@ -135,9 +163,9 @@ where
     }
 }
 
-impl<T> BorshDeserialize for Vector<T>
+impl<T, S> BorshDeserialize for Vector<T, S>
 where
-    T: BorshSerialize,
+    S: ValueSerializer<T>,
 {
     fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
         Ok(Self {
@@ -157,10 +185,85 @@ fn collections_vec_not_backwards_compatible() {
     assert!(Vector::<u8>::try_from_slice(&v1.try_to_vec().unwrap()).is_err());
 }
 
-impl<T> Vector<T>
+impl<T> Vector<T, Borsh>
 where
     T: BorshSerialize,
 {
+    /// Create new vector with zero elements. Prefixes storage accesss with the prefix provided.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut vec: Vector<u8> = Vector::new(b"a");
+    /// ```
+    pub fn new<P>(prefix: P) -> Self
+    where
+        P: IntoStorageKey,
+    {
+        Self::with_serializer(prefix)
+    }
+
+    /// Starts converting `legacy` into a vector with the same prefix and elements. The elements
+    /// are moved with the returned [`Migration`], see the [`migration`](crate::store::migration)
+    /// module.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `legacy` has more than [`u32::MAX`] elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::{collections, store};
+    ///
+    /// let mut legacy: collections::Vector<u8> = collections::Vector::new(b"v");
+    /// legacy.extend([1, 2, 3]);
+    ///
+    /// let mut migration = store::Vector::from_legacy(legacy);
+    /// assert!(!migration.migrate(2));
+    /// assert!(migration.migrate(2));
+    /// let vec = migration.finish();
+    /// assert!(Iterator::eq(vec.iter(), [1, 2, 3].iter()));
+    /// ```
+    pub fn from_legacy(legacy: collections::Vector<T>) -> Migration<collections::Vector<T>, Self>
+    where
+        T: BorshDeserialize,
+    {
+        let len =
+            u32::try_from(legacy.len()).unwrap_or_else(|_| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS));
+        let target = Self { len, values: IndexMap::new(legacy.prefix()) };
+        Migration::new(legacy, target)
+    }
+}
+
+impl<T, S> Vector<T, S>
+where
+    S: ValueSerializer<T>,
+{
+    /// Create new vector with zero elements, which serializes its elements with the serializer
+    /// `S`. See the [`serializer`](crate::store::serializer) module for the available formats.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::serializer::Json;
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut vec: Vector<String, Json> = Vector::with_serializer(b"a");
+    /// vec.push("stored as JSON".to_string());
+    /// ```
+    pub fn with_serializer<P>(prefix: P) -> Self
+    where
+        P: IntoStorageKey,
+    {
+        Self { len: 0, values: IndexMap::with_serializer(prefix) }
+    }
+
     /// Returns the number of elements in the vector, also referred to as its size.
     /// This function returns a `u32` rather than the [`Vec`] equivalent of `usize` to have
     /// consistency between targets.
@@ -196,25 +299,6 @@ where
         self.len == 0
     }
 
-    /// Create new vector with zero elements. Prefixes storage accesss with the prefix provided.
-    ///
-    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
-    /// storing and looking up values in storage to ensure no collisions with other collections.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use near_sdk::store::Vector;
-    ///
-    /// let mut vec: Vector<u8> = Vector::new(b"a");
-    /// ```
-    pub fn new<S>(prefix: S) -> Self
-    where
-        S: IntoStorageKey,
-    {
-        Self { len: 0, values: IndexMap::new(prefix) }
-    }
-
     /// Removes all elements from the collection. This will remove all storage values for the
     /// length of the [`Vector`].
     ///
@@ -300,9 +384,9 @@ where
     }
 }
 
-impl<T> Vector<T>
+impl<T, S> Vector<T, S>
 where
-    T: BorshSerialize + BorshDeserialize,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
 {
     /// Returns the element by index or `None` if it is not present.
     ///
@@ -450,7 +534,7 @@ where
     /// assert_eq!(iterator.next(), Some(&4));
     /// assert_eq!(iterator.next(), None);
     /// ```
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<T, S> {
         Iter::new(self)
     }
 
@@ -470,10 +554,39 @@ where
     /// }
     /// assert_eq!(vec.iter().copied().collect::<Vec<_>>(), &[3u32, 4, 6]);
     /// ```
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<T, S> {
         IterMut::new(self)
     }
 
+    /// Returns a page of at most `limit` elements starting at `from_index`, with the index to
+    /// start the next page from. Only the elements of the page are loaded from storage.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut vec = Vector::new(b"v");
+    /// vec.extend([1, 2, 3, 4, 5]);
+    ///
+    /// let page = vec.paginate(1, 3);
+    /// assert_eq!(page.items, [&2, &3, &4]);
+    /// assert_eq!(page.next_cursor, Some(4));
+    ///
+    /// let page = vec.paginate(4, 3);
+    /// assert_eq!(page.items, [&5]);
+    /// assert_eq!(page.next_cursor, None);
+    /// ```
+    pub fn paginate(&self, from_index: u32, limit: u32) -> Page<&T> {
+        Page::from_indices(from_index, limit, self.len(), |i| {
+            Some(expect_consistent_state(self.values.get(i)))
+        })
+    }
+
     /// Creates a draining iterator that removes the specified range in the vector
     /// and yields the removed items.
     ///
@@ -501,7 +614,7 @@ where
     /// vec.drain(..);
     /// assert!(vec.is_empty());
     /// ```
-    pub fn drain<R>(&mut self, range: R) -> Drain<T>
+    pub fn drain<R>(&mut self, range: R) -> Drain<T, S>
     where
         R: RangeBounds<u32>,
     {
@@ -524,11 +637,100 @@ where
         // This will also cap the max length at the length of the vector.
         Drain::new(self, Range { start, end: core::cmp::min(end, self.len()) })
     }
+
+    /// Checks that every element within the length of the vector is stored and can be
+    /// deserialized, and that no elements are stored after them, returning the inconsistencies
+    /// found. The cached changes are flushed first, and each element is then read again from
+    /// storage.
+    ///
+    /// The elements stored after the end are read from the index following the last element
+    /// until one is not stored, so elements stored after such a gap are not found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::{Inconsistency, Vector};
+    ///
+    /// let mut vec = Vector::new(b"v");
+    /// vec.extend([1u8, 2, 3]);
+    /// assert!(vec.validate().is_empty());
+    ///
+    /// near_sdk::env::storage_remove(&[b'v', 1, 0, 0, 0]);
+    /// assert_eq!(vec.validate(), [Inconsistency::MissingSlot { index: 1 }]);
+    /// ```
+    pub fn validate(&mut self) -> Vec<Inconsistency> {
+        self.values.reset_cache();
+        let mut issues = Vec::new();
+        for index in 0..self.len {
+            match self.values.read_stored(index) {
+                None => issues.push(Inconsistency::MissingSlot { index }),
+                Some(Err(_)) => issues.push(Inconsistency::CorruptedSlot { index }),
+                Some(Ok(_)) => {}
+            }
+        }
+        let mut index = self.len;
+        while index < u32::MAX && self.values.is_stored(index) {
+            issues.push(Inconsistency::OrphanedSlot { index });
+            index += 1;
+        }
+        issues
+    }
+
+    /// Repairs the vector after [`validate`](Self::validate) found inconsistencies, which are
+    /// returned. Like [`validate`](Self::validate), it flushes the cached changes first, and
+    /// nothing else is written if there are no inconsistencies.
+    ///
+    /// The elements which are missing or cannot be deserialized are removed, shifting the
+    /// following elements to the left, and the elements found past the end of the vector are
+    /// removed from storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::{Inconsistency, Vector};
+    ///
+    /// let mut vec = Vector::new(b"v");
+    /// vec.extend([1u8, 2, 3]);
+    /// vec.flush();
+    /// near_sdk::env::storage_write(&[b'v', 1, 0, 0, 0], &[]);
+    ///
+    /// assert_eq!(vec.repair(), [Inconsistency::CorruptedSlot { index: 1 }]);
+    /// assert_eq!(vec.iter().collect::<Vec<_>>(), [&1, &3]);
+    /// assert!(vec.validate().is_empty());
+    /// ```
+    pub fn repair(&mut self) -> Vec<Inconsistency> {
+        let issues = self.validate();
+        if issues.is_empty() {
+            return issues;
+        }
+        let mut len = 0;
+        for index in 0..self.len {
+            match self.values.read_stored(index) {
+                Some(Ok(_)) => {
+                    if len != index {
+                        let value = self.values.remove(index);
+                        self.values.set(len, value);
+                    }
+                    len += 1;
+                }
+                Some(Err(_)) | None => self.values.set(index, None),
+            }
+        }
+        for issue in &issues {
+            if let Inconsistency::OrphanedSlot { index } = *issue {
+                self.values.set(index, None);
+            }
+        }
+        self.len = len;
+        self.flush();
+        issues
+    }
 }
 
-impl<T> fmt::Debug for Vector<T>
+impl<T, S> fmt::Debug for Vector<T, S>
 where
-    T: BorshSerialize + BorshDeserialize + fmt::Debug,
+    S: ValueSerializer<T> + ValueDeserializer<T>,
+    T: fmt::Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if cfg!(feature = "expensive-debug") {
@@ -887,4 +1089,31 @@ mod tests {
         let vec = Vector::<String>::deserialize(&mut serialized.as_slice()).unwrap();
         assert_eq!(vec[0], "Some data");
     }
+
+    #[test]
+    fn validate_and_repair() {
+        use crate::env;
+        use crate::store::Inconsistency;
+
+        let key = |index: u32| [b"v".as_slice(), &index.to_le_bytes()].concat();
+        let mut vec = Vector::new(b"v");
+        vec.extend(0u8..5);
+        assert!(vec.validate().is_empty());
+
+        env::storage_remove(&key(1));
+        env::storage_write(&key(3), &[]);
+        env::storage_write(&key(5), &[5]);
+        let issues = [
+            Inconsistency::MissingSlot { index: 1 },
+            Inconsistency::CorruptedSlot { index: 3 },
+            Inconsistency::OrphanedSlot { index: 5 },
+        ];
+        assert_eq!(vec.validate(), issues);
+
+        assert_eq!(vec.repair(), issues);
+        assert_eq!(vec.iter().collect::<Vec<_>>(), [&0, &2, &4]);
+        assert!((3..=5).all(|index| !env::storage_has_key(&key(index))));
+        assert!(vec.validate().is_empty());
+        assert!(vec.repair().is_empty());
+    }
 }
@@ -93,11 +93,101 @@ pub fn get_logs() -> Vec<String> {
     crate::mock::with_mocked_blockchain(|b| b.logs())
 }
 
+/// Returns the messages of the logs emitted at the given level through [`log_debug!`],
+/// [`log_info!`] or [`log_warn!`], without the level prefix. Only available in unit tests.
+///
+/// [`log_debug!`]: crate::log_debug
+/// [`log_info!`]: crate::log_info
+/// [`log_warn!`]: crate::log_warn
+pub fn get_logs_by_level(level: crate::LogLevel) -> Vec<String> {
+    get_logs()
+        .iter()
+        .filter_map(|log| match crate::LogLevel::split_log(log) {
+            Some((log_level, message)) if log_level == level => Some(message.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the name and used gas of each [`GasMeter`] dropped so far, in the order they were
+/// dropped. Only available in unit tests.
+///
+/// [`GasMeter`]: crate::GasMeter
+pub fn get_gas_meters() -> Vec<(String, crate::Gas)> {
+    crate::mock::with_mocked_blockchain(|b| b.gas_meters())
+}
+
 /// Accessing receipts created by the contract. Only available in unit tests.
 pub fn get_created_receipts() -> Vec<Receipt> {
     crate::mock::with_mocked_blockchain(|b| b.created_receipts())
 }
 
+/// Checks that a [`ToKey`] implementation satisfies the requirements of the trait for the given
+/// sample `keys`: the same prefix and key always give the same storage key, and distinct
+/// prefixes or serialized keys give distinct storage keys.
+///
+/// # Panics
+///
+/// Panics with a description of the first requirement that is not satisfied.
+///
+/// [`ToKey`]: crate::store::key::ToKey
+pub fn check_to_key<H, Q>(keys: &[Q])
+where
+    H: crate::store::key::ToKey,
+    Q: borsh::BorshSerialize,
+{
+    let prefixes: [&[u8]; 3] = [b"", b"p", b"pp"];
+    let mut seen = std::collections::HashMap::new();
+    for prefix in prefixes {
+        for key in keys {
+            let serialized = key.try_to_vec().expect("key should serialize");
+            let storage_key = H::to_key(prefix, key, &mut Vec::new()).as_ref().to_vec();
+            let again = H::to_key(prefix, key, &mut Vec::with_capacity(64)).as_ref().to_vec();
+            assert_eq!(
+                storage_key, again,
+                "to_key is not deterministic for prefix {:?} and key {:?}",
+                prefix, serialized
+            );
+
+            let combined = (prefix, serialized);
+            if let Some(other) = seen.insert(storage_key.clone(), combined.clone()) {
+                assert_eq!(
+                    other, combined,
+                    "to_key gives the same storage key {:?} for distinct prefixes or keys",
+                    storage_key
+                );
+            }
+        }
+    }
+}
+
+/// Checks that a [`ToKey`] implementation preserves the order of the given sample `keys`, so
+/// that iterating over the raw storage of a map gives its entries in key order.
+///
+/// # Panics
+///
+/// Panics if the storage keys of two keys are not in the same order as the keys.
+///
+/// [`ToKey`]: crate::store::key::ToKey
+pub fn check_to_key_order<H, K>(keys: &[K])
+where
+    H: crate::store::key::ToKey,
+    K: borsh::BorshSerialize + Ord + core::fmt::Debug,
+{
+    let mut sorted: Vec<&K> = keys.iter().collect();
+    sorted.sort();
+    for pair in sorted.windows(2) {
+        let a = H::to_key(b"p", pair[0], &mut Vec::new());
+        let b = H::to_key(b"p", pair[1], &mut Vec::new());
+        assert!(
+            a.as_ref() <= b.as_ref(),
+            "to_key does not preserve the order of {:?} and {:?}",
+            pair[0],
+            pair[1]
+        );
+    }
+}
+
 /// Objects stored on the trie directly should have identifiers. If identifier is not provided
 /// explicitly than `Default` trait would use this index to generate an id.
 #[cfg(test)]
@@ -0,0 +1,144 @@
+//! Types for the `alt_bn128` elliptic curve host functions, see [`env::alt_bn128_g1_multiexp`],
+//! [`env::alt_bn128_g1_sum`] and [`env::alt_bn128_pairing_check`].
+//!
+//! All field elements are encoded as 32 byte little-endian integers, which is the encoding
+//! expected by the runtime. Note that this differs from the big-endian encoding used by the
+//! Ethereum precompiles.
+//!
+//! [`env::alt_bn128_g1_multiexp`]: crate::env::alt_bn128_g1_multiexp
+//! [`env::alt_bn128_g1_sum`]: crate::env::alt_bn128_g1_sum
+//! [`env::alt_bn128_pairing_check`]: crate::env::alt_bn128_pairing_check
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Size in bytes of an encoded field element or scalar.
+pub const SCALAR_SIZE: usize = 32;
+/// Size in bytes of an encoded [`G1Point`].
+pub const G1_POINT_SIZE: usize = SCALAR_SIZE * 2;
+/// Size in bytes of an encoded [`G2Point`].
+pub const G2_POINT_SIZE: usize = G1_POINT_SIZE * 2;
+
+/// An element of the scalar field of the curve, encoded as a 32 byte little-endian integer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, BorshSerialize, BorshDeserialize)]
+pub struct Scalar(pub [u8; SCALAR_SIZE]);
+
+impl Scalar {
+    /// Returns the little-endian encoding of the scalar.
+    pub const fn to_bytes(self) -> [u8; SCALAR_SIZE] {
+        self.0
+    }
+}
+
+impl From<u64> for Scalar {
+    fn from(value: u64) -> Self {
+        Self::from(value as u128)
+    }
+}
+
+impl From<u128> for Scalar {
+    fn from(value: u128) -> Self {
+        let mut bytes = [0; SCALAR_SIZE];
+        bytes[..16].copy_from_slice(&value.to_le_bytes());
+        Self(bytes)
+    }
+}
+
+/// A point on the G1 curve, encoded as its affine `x` and `y` coordinates. The point at infinity
+/// is encoded with both coordinates set to zero, see [`G1Point::ZERO`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, BorshSerialize, BorshDeserialize)]
+pub struct G1Point {
+    pub x: [u8; SCALAR_SIZE],
+    pub y: [u8; SCALAR_SIZE],
+}
+
+impl G1Point {
+    /// The point at infinity, which is the identity of the group.
+    pub const ZERO: Self = Self { x: [0; SCALAR_SIZE], y: [0; SCALAR_SIZE] };
+
+    /// Decodes a point from the `x || y` encoding used by the runtime.
+    pub fn from_bytes(bytes: &[u8; G1_POINT_SIZE]) -> Self {
+        let mut point = Self::ZERO;
+        point.x.copy_from_slice(&bytes[..SCALAR_SIZE]);
+        point.y.copy_from_slice(&bytes[SCALAR_SIZE..]);
+        point
+    }
+
+    /// Encodes the point as `x || y`, which is the encoding used by the runtime.
+    pub fn to_bytes(&self) -> [u8; G1_POINT_SIZE] {
+        let mut bytes = [0; G1_POINT_SIZE];
+        bytes[..SCALAR_SIZE].copy_from_slice(&self.x);
+        bytes[SCALAR_SIZE..].copy_from_slice(&self.y);
+        bytes
+    }
+}
+
+impl Default for G1Point {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+/// A point on the G2 curve, encoded as its affine `x` and `y` coordinates. Each coordinate is an
+/// element of the quadratic extension field, encoded as its real part followed by its imaginary
+/// part. The point at infinity is encoded with all bytes set to zero, see [`G2Point::ZERO`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, BorshSerialize, BorshDeserialize)]
+pub struct G2Point {
+    pub x: [u8; G1_POINT_SIZE],
+    pub y: [u8; G1_POINT_SIZE],
+}
+
+impl G2Point {
+    /// The point at infinity, which is the identity of the group.
+    pub const ZERO: Self = Self { x: [0; G1_POINT_SIZE], y: [0; G1_POINT_SIZE] };
+
+    /// Decodes a point from the `x || y` encoding used by the runtime.
+    pub fn from_bytes(bytes: &[u8; G2_POINT_SIZE]) -> Self {
+        let mut point = Self::ZERO;
+        point.x.copy_from_slice(&bytes[..G1_POINT_SIZE]);
+        point.y.copy_from_slice(&bytes[G1_POINT_SIZE..]);
+        point
+    }
+
+    /// Encodes the point as `x || y`, which is the encoding used by the runtime.
+    pub fn to_bytes(&self) -> [u8; G2_POINT_SIZE] {
+        let mut bytes = [0; G2_POINT_SIZE];
+        bytes[..G1_POINT_SIZE].copy_from_slice(&self.x);
+        bytes[G1_POINT_SIZE..].copy_from_slice(&self.y);
+        bytes
+    }
+}
+
+impl Default for G2Point {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalar_from_int() {
+        let scalar = Scalar::from(0x0102u64);
+        assert_eq!(scalar.0[..2], [2, 1]);
+        assert!(scalar.0[2..].iter().all(|b| *b == 0));
+        assert_eq!(Scalar::from(u128::MAX).0[..16], [u8::MAX; 16]);
+    }
+
+    #[test]
+    fn point_encoding_roundtrip() {
+        let mut bytes = [0; G1_POINT_SIZE];
+        bytes.iter_mut().enumerate().for_each(|(i, b)| *b = i as u8);
+        let point = G1Point::from_bytes(&bytes);
+        assert_eq!(point.x[0], 0);
+        assert_eq!(point.y[0], SCALAR_SIZE as u8);
+        assert_eq!(point.to_bytes(), bytes);
+
+        let mut bytes = [0; G2_POINT_SIZE];
+        bytes.iter_mut().enumerate().for_each(|(i, b)| *b = i as u8);
+        let point = G2Point::from_bytes(&bytes);
+        assert_eq!(point.y[0], G1_POINT_SIZE as u8);
+        assert_eq!(point.to_bytes(), bytes);
+    }
+}
@@ -20,8 +20,38 @@
 ///     }
 /// }
 /// ```
+///
+/// Errors can also panic with a machine-parseable payload of the form
+/// `{"code":<code>,"msg":<message>}` through [`FunctionError::panic_with_code`]:
+///
+/// ```
+/// use near_sdk::FunctionError;
+///
+/// enum Error {
+///     NotFound,
+/// }
+///
+/// impl FunctionError for Error {
+///     fn panic(&self) -> ! {
+///         match self {
+///             Error::NotFound => self.panic_with_code(&404, "not found"),
+///         }
+///     }
+/// }
+/// ```
 pub trait FunctionError {
     fn panic(&self) -> !;
+
+    /// Panics with the structured `{"code":<code>,"msg":<message>}` payload, see
+    /// [`env::panic_with_code`].
+    ///
+    /// [`env::panic_with_code`]: crate::env::panic_with_code
+    fn panic_with_code<C: serde::Serialize + ?Sized>(&self, code: &C, message: &str) -> !
+    where
+        Self: Sized,
+    {
+        crate::env::panic_with_code(code, message)
+    }
 }
 
 impl<T> FunctionError for T
@@ -17,6 +17,8 @@ mod error;
 pub use self::error::Abort;
 pub use self::error::FunctionError;
 
+pub mod alt_bn128;
+
 /// Raw type for duration in nanoseconds
 pub type Duration = u64;
 
@@ -36,7 +38,7 @@ pub type CryptoHash = [u8; 32];
 /// using up all remaining available gas.
 ///
 /// [`promise_batch_action_function_call_weight`]: `crate::env::promise_batch_action_function_call_weight`
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(transparent)]
 pub struct GasWeight(pub u64);
 
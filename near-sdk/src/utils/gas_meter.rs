@@ -0,0 +1,98 @@
+use crate::{env, Gas};
+
+/// Guard that measures the gas used by the contract over a named scope, from the creation of the
+/// meter until it is dropped or [`finish`]ed.
+///
+/// In unit tests, every meter reports the gas it measured when it goes out of scope. The reports
+/// can be retrieved with [`test_utils::get_gas_meters`]. On chain, meters don't record anything.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{env, GasMeter};
+///
+/// # fn main() {
+/// {
+///     let _meter = GasMeter::new("store_value");
+///     env::storage_write(b"key", b"value");
+/// }
+///
+/// let reports = near_sdk::test_utils::get_gas_meters();
+/// assert_eq!(reports[0].0, "store_value");
+/// assert!(reports[0].1 > near_sdk::Gas(0));
+/// # }
+/// ```
+///
+/// [`finish`]: GasMeter::finish
+/// [`test_utils::get_gas_meters`]: crate::test_utils::get_gas_meters
+#[derive(Debug)]
+pub struct GasMeter {
+    name: &'static str,
+    start: Gas,
+}
+
+impl GasMeter {
+    /// Starts measuring gas for the scope with the given `name`.
+    pub fn new(name: &'static str) -> Self {
+        Self { name, start: env::used_gas() }
+    }
+
+    /// Returns the name of the measured scope.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the gas used since the meter was created.
+    pub fn used_gas(&self) -> Gas {
+        env::used_gas() - self.start
+    }
+
+    /// Stops measuring and returns the gas used since the meter was created.
+    pub fn finish(self) -> Gas {
+        let used_gas = self.used_gas();
+        self.report(used_gas);
+        // The gas was already reported, so the meter must not report it again on drop.
+        core::mem::forget(self);
+        used_gas
+    }
+
+    #[allow(unused_variables)]
+    fn report(&self, used_gas: Gas) {
+        #[cfg(all(not(target_arch = "wasm32"), feature = "unit-testing"))]
+        crate::mock::with_mocked_blockchain(|b| b.record_gas_meter(self.name, used_gas));
+    }
+}
+
+impl Drop for GasMeter {
+    fn drop(&mut self) {
+        #[cfg(all(not(target_arch = "wasm32"), feature = "unit-testing"))]
+        self.report(self.used_gas());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GasMeter;
+    use crate::test_utils::{get_gas_meters, VMContextBuilder};
+    use crate::{env, testing_env, Gas};
+
+    #[test]
+    fn nested_meters() {
+        testing_env!(VMContextBuilder::new().build());
+
+        let outer = GasMeter::new("outer");
+        {
+            let _inner = GasMeter::new("inner");
+            env::storage_write(b"key", b"value");
+        }
+        env::storage_write(b"other", b"value");
+        let outer_gas = outer.finish();
+
+        let reports = get_gas_meters();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].0, "inner");
+        assert_eq!(reports[1], ("outer".to_string(), outer_gas));
+        assert!(reports[0].1 > Gas(0));
+        assert!(reports[1].1 > reports[0].1);
+    }
+}
@@ -0,0 +1,283 @@
+use serde::ser::{self, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+/// Converts the value of a structured log field to JSON. `u128` and `i128` values are logged as
+/// strings, like [`U128`](crate::json_types::U128) and [`I128`](crate::json_types::I128), since
+/// JSON numbers can't represent them. This also applies to the ones nested in other values.
+#[doc(hidden)]
+pub fn log_field_value<T: Serialize + ?Sized>(key: &str, value: &T) -> Value {
+    value.serialize(FieldSerializer).unwrap_or_else(|err| {
+        crate::env::panic_str(&format!("Failed to serialize the log field `{}`: {}", key, err))
+    })
+}
+
+/// Serializes a value to [`Value`] like `serde_json`, except for the 128-bit integers.
+struct FieldSerializer;
+
+use serde_json::value::Serializer as Json;
+
+macro_rules! delegate {
+    ($($method:ident($($arg:ident: $ty:ty),*) -> $ret:ty;)*) => {
+        $(
+            fn $method(self, $($arg: $ty),*) -> Result<$ret, Self::Error> {
+                Json.$method($($arg),*)
+            }
+        )*
+    };
+}
+
+impl Serializer for FieldSerializer {
+    type Ok = Value;
+    type Error = serde_json::Error;
+    type SerializeSeq = SerializeVec;
+    type SerializeTuple = SerializeVec;
+    type SerializeTupleStruct = SerializeVec;
+    type SerializeTupleVariant = SerializeVariant<SerializeVec>;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeVariant<SerializeMap>;
+
+    fn serialize_u128(self, v: u128) -> Result<Value, Self::Error> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, Self::Error> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Self::Error> {
+        Ok(variant_value(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeVec, Self::Error> {
+        Ok(SerializeVec(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeVec, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeVec, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeVec>, Self::Error> {
+        Ok(SerializeVariant { variant, inner: self.serialize_seq(Some(len))? })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeMap, Self::Error> {
+        Ok(SerializeMap { map: Map::new(), next_key: None })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeMap, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeMap>, Self::Error> {
+        Ok(SerializeVariant { variant, inner: self.serialize_map(Some(len))? })
+    }
+
+    delegate! {
+        serialize_bool(v: bool) -> Value;
+        serialize_i8(v: i8) -> Value;
+        serialize_i16(v: i16) -> Value;
+        serialize_i32(v: i32) -> Value;
+        serialize_i64(v: i64) -> Value;
+        serialize_u8(v: u8) -> Value;
+        serialize_u16(v: u16) -> Value;
+        serialize_u32(v: u32) -> Value;
+        serialize_u64(v: u64) -> Value;
+        serialize_f32(v: f32) -> Value;
+        serialize_f64(v: f64) -> Value;
+        serialize_char(v: char) -> Value;
+        serialize_str(v: &str) -> Value;
+        serialize_bytes(v: &[u8]) -> Value;
+        serialize_none() -> Value;
+        serialize_unit() -> Value;
+        serialize_unit_struct(name: &'static str) -> Value;
+        serialize_unit_variant(name: &'static str, variant_index: u32, variant: &'static str) -> Value;
+    }
+}
+
+/// Externally tagged enum variant, as `serde_json` represents them.
+fn variant_value(variant: &'static str, value: Value) -> Value {
+    let mut map = Map::new();
+    map.insert(variant.to_string(), value);
+    Value::Object(map)
+}
+
+/// Elements of a sequence or a tuple.
+struct SerializeVec(Vec<Value>);
+
+impl SerializeVec {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), serde_json::Error> {
+        self.0.push(value.serialize(FieldSerializer)?);
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for SerializeVec {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Value::Array(self.0))
+    }
+}
+
+impl ser::SerializeTuple for SerializeVec {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Value::Array(self.0))
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeVec {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Value::Array(self.0))
+    }
+}
+
+/// Entries of a map or the fields of a struct.
+struct SerializeMap {
+    map: Map<String, Value>,
+    next_key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        // JSON keys are strings, so numbers and booleans are stringified like `serde_json` does.
+        self.next_key = Some(match key.serialize(FieldSerializer)? {
+            Value::String(key) => key,
+            Value::Number(key) => key.to_string(),
+            Value::Bool(key) => key.to_string(),
+            _ => return Err(ser::Error::custom("key must be a string")),
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| ser::Error::custom("serialize_value called before serialize_key"))?;
+        self.map.insert(key, value.serialize(FieldSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Value::Object(self.map))
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.map.insert(key.to_string(), value.serialize(FieldSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Value::Object(self.map))
+    }
+}
+
+/// Tuple or struct variant of an enum.
+struct SerializeVariant<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeVec> {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.inner.push(value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(variant_value(self.variant, Value::Array(self.inner.0)))
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<SerializeMap> {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(variant_value(self.variant, Value::Object(self.inner.map)))
+    }
+}
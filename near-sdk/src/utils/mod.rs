@@ -6,6 +6,13 @@ mod stable_map;
 pub(crate) use self::stable_map::StableMap;
 mod cache_entry;
 pub(crate) use cache_entry::{CacheEntry, EntryState};
+mod gas_meter;
+pub use self::gas_meter::GasMeter;
+mod storage_tracker;
+pub use self::storage_tracker::StorageTracker;
+mod log_field;
+#[doc(hidden)]
+pub use self::log_field::log_field_value;
 
 use crate::{env, PromiseResult};
 
@@ -42,6 +49,132 @@ macro_rules! log {
     };
 }
 
+/// Severity of a log emitted through [`log_debug!`], [`log_info!`] or [`log_warn!`].
+///
+/// Leveled logs are prefixed with the name of their level, for example `INFO: message`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum LogLevel {
+    Debug = 0,
+    Info = 1,
+    Warn = 2,
+}
+
+impl LogLevel {
+    /// Returns the name of the level, as used in the log prefix.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+        }
+    }
+
+    /// Splits a leveled log into its level and message. Returns `None` if the log was not emitted
+    /// with a level.
+    pub fn split_log(log: &str) -> Option<(LogLevel, &str)> {
+        let (prefix, message) = log.split_once(": ")?;
+        let level = match prefix {
+            "DEBUG" => LogLevel::Debug,
+            "INFO" => LogLevel::Info,
+            "WARN" => LogLevel::Warn,
+            _ => return None,
+        };
+        Some((level, message))
+    }
+}
+
+/// Minimum level of the logs emitted through [`log_debug!`], [`log_info!`] and [`log_warn!`].
+/// Logs with a lower level are removed at compile time, so they don't add to the contract size.
+///
+/// This is [`LogLevel::Debug`] by default, and can be raised with the `log-level-info` and
+/// `log-level-warn` features.
+pub const MIN_LOG_LEVEL: LogLevel = if cfg!(feature = "log-level-warn") {
+    LogLevel::Warn
+} else if cfg!(feature = "log-level-info") {
+    LogLevel::Info
+} else {
+    LogLevel::Debug
+};
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_with_level {
+    ($level:expr, $message:expr; $($key:ident = $value:expr),+ $(,)?) => {
+        if $level as u8 >= $crate::MIN_LOG_LEVEL as u8 {
+            let mut fields = $crate::serde_json::Map::new();
+            $(
+                fields.insert(
+                    ::std::string::String::from(stringify!($key)),
+                    $crate::utils::log_field_value(stringify!($key), &$value),
+                );
+            )+
+            let json = $crate::serde_json::json!({ "message": $message, "fields": fields });
+            $crate::env::log_str(&format!("{}: {}", $level.as_str(), json))
+        }
+    };
+    ($level:expr, $($arg:tt)*) => {
+        if $level as u8 >= $crate::MIN_LOG_LEVEL as u8 {
+            $crate::env::log_str(&format!("{}: {}", $level.as_str(), format_args!($($arg)*)))
+        }
+    };
+}
+
+/// Helper macro to log a message at [`LogLevel::Debug`], see [`log_info!`] for the accepted forms.
+///
+/// Debug logs are removed at compile time if either the `log-level-info` or `log-level-warn`
+/// feature of the SDK is enabled.
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        $crate::__log_with_level!($crate::LogLevel::Debug, $($arg)*)
+    };
+}
+
+/// Helper macro to log a message at [`LogLevel::Info`].
+///
+/// The message can either be formatted like with [`log!`], or be followed by `;` and a list of
+/// `key = value` fields, where values implement [`serde::Serialize`]. The structured form logs
+/// a JSON object of the shape `{"fields":{..},"message":".."}`. Fields of type `u128` or `i128`,
+/// such as a [`Balance`](crate::Balance), are logged as strings like
+/// [`U128`](crate::json_types::U128). Within other values, they have to be wrapped in `U128`,
+/// since the contract panics on those which don't fit in a JSON number.
+///
+/// Info logs are removed at compile time if the `log-level-warn` feature of the SDK is enabled.
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::log_info;
+///
+/// # fn main() {
+/// log_info!("Hello {}", "world");
+/// log_info!("transfer"; amount = 10, receiver = "bob.near");
+///
+/// assert_eq!(
+///     near_sdk::test_utils::get_logs(),
+///     [
+///         "INFO: Hello world",
+///         r#"INFO: {"fields":{"amount":10,"receiver":"bob.near"},"message":"transfer"}"#,
+///     ]
+/// );
+/// # }
+/// ```
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        $crate::__log_with_level!($crate::LogLevel::Info, $($arg)*)
+    };
+}
+
+/// Helper macro to log a message at [`LogLevel::Warn`], see [`log_info!`] for the accepted forms.
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        $crate::__log_with_level!($crate::LogLevel::Warn, $($arg)*)
+    };
+}
+
 /// Helper macro to create assertions that will panic through the runtime host functions.
 ///
 /// This macro can be used similarly to [`assert!`] but will reduce code size by not including
@@ -58,6 +191,29 @@ macro_rules! log {
 /// require!("test" != "other", "Some custom error message if false");
 /// # }
 /// ```
+///
+/// An error code can be passed before the message to panic with a machine-parseable payload of
+/// the form `{"code":<code>,"msg":<message>}`, see [`env::panic_with_code`]. The code can be any
+/// serializable value, such as an integer or a unit enum variant.
+///
+/// ```no_run
+/// use near_sdk::require;
+/// use near_sdk::serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// #[serde(crate = "near_sdk::serde")]
+/// enum ErrorCode {
+///     NotOwner,
+/// }
+///
+/// # fn main() {
+/// let is_owner = true;
+/// require!(is_owner, 403, "Only the owner can call this method");
+/// require!(is_owner, ErrorCode::NotOwner, "Only the owner can call this method");
+/// # }
+/// ```
+///
+/// [`env::panic_with_code`]: crate::env::panic_with_code
 #[macro_export]
 macro_rules! require {
     ($cond:expr $(,)?) => {
@@ -76,6 +232,12 @@ macro_rules! require {
             $crate::env::panic_str(&$message)
         }
     };
+    ($cond:expr, $code:expr, $message:expr $(,)?) => {
+        // The payload must be parseable, so the debug `assert!` message format is not used here.
+        if !$cond {
+            $crate::env::panic_with_code(&$code, &$message)
+        }
+    };
 }
 
 /// Assert that predecessor_account_id == current_account_id, meaning contract called itself.
@@ -122,7 +284,8 @@ macro_rules! setup_alloc {
 
 #[cfg(test)]
 mod tests {
-    use crate::test_utils::get_logs;
+    use super::LogLevel;
+    use crate::test_utils::{get_logs, get_logs_by_level};
 
     #[test]
     fn test_log_simple() {
@@ -137,4 +300,67 @@ mod tests {
 
         assert_eq!(get_logs(), vec!["hello user_name (25)".to_string()]);
     }
+
+    #[test]
+    #[cfg(not(any(feature = "log-level-info", feature = "log-level-warn")))]
+    fn test_log_levels() {
+        log_debug!("debug {}", 1);
+        log_info!("info");
+        log_warn!("warn {x}", x = 3);
+        log!("no level");
+
+        assert_eq!(
+            get_logs(),
+            vec![
+                "DEBUG: debug 1".to_string(),
+                "INFO: info".to_string(),
+                "WARN: warn 3".to_string(),
+                "no level".to_string()
+            ]
+        );
+        assert_eq!(get_logs_by_level(LogLevel::Warn), vec!["warn 3".to_string()]);
+    }
+
+    #[test]
+    fn test_log_structured() {
+        let account = "alice.near";
+        log_warn!("low balance"; account = account, balance = 5u128, tags = ["a", "b"]);
+
+        assert_eq!(
+            get_logs_by_level(LogLevel::Warn),
+            vec![r#"{"fields":{"account":"alice.near","balance":"5","tags":["a","b"]},"message":"low balance"}"#
+                .to_string()]
+        );
+    }
+
+    #[test]
+    fn test_log_structured_u128() {
+        log_info!("deposit"; amount = 10u128.pow(24), debt = -(10i128.pow(24)), fee = Some(1u128));
+
+        assert_eq!(
+            get_logs_by_level(LogLevel::Info),
+            vec![r#"{"fields":{"amount":"1000000000000000000000000","debt":"-1000000000000000000000000","fee":"1"},"message":"deposit"}"#
+                .to_string()]
+        );
+    }
+
+    #[test]
+    fn test_log_structured_nested_u128() {
+        let mut by_account = std::collections::BTreeMap::new();
+        by_account.insert("alice.near", (1u128, -2i128));
+        log_info!("deposits"; amounts = [10u128.pow(24)], by_account = by_account);
+
+        assert_eq!(
+            get_logs_by_level(LogLevel::Info),
+            vec![r#"{"fields":{"amounts":["1000000000000000000000000"],"by_account":{"alice.near":["1","-2"]}},"message":"deposits"}"#
+                .to_string()]
+        );
+    }
+
+    #[test]
+    fn test_split_log() {
+        assert_eq!(LogLevel::split_log("INFO: a: b"), Some((LogLevel::Info, "a: b")));
+        assert_eq!(LogLevel::split_log("EVENT_JSON:{}"), None);
+        assert_eq!(LogLevel::split_log("other: message"), None);
+    }
 }
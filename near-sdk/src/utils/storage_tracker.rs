@@ -0,0 +1,189 @@
+use crate::{env, Balance, StorageUsage};
+
+/// Accumulates the storage bytes added and released over one or more tracked scopes, based on the
+/// difference of [`env::storage_usage`] between the start and the end of each scope.
+///
+/// Storage collections only write their changes when they are flushed or dropped, so the changes
+/// of a collection are only attributed to the tracker if the collection is flushed before the
+/// scope ends. To attribute storage per collection or per account, use one tracker for each and
+/// [`consume`] them into a total when needed.
+///
+/// Flushing a map doesn't flush the collections nested in its values, such as the ones created
+/// with [`get_or_insert_child`], which write their own changes when they are flushed or dropped.
+/// These have to be flushed in the scope as well.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::store::LookupMap;
+/// use near_sdk::StorageTracker;
+///
+/// # fn main() {
+/// let mut balances: LookupMap<String, u128> = LookupMap::new(b"b");
+///
+/// let mut tracker = StorageTracker::default();
+/// tracker.track(|| {
+///     balances.insert("alice".to_string(), 10);
+///     balances.flush();
+/// });
+/// assert!(tracker.bytes_added() > 0);
+///
+/// // The exact cost of the entry can be refunded when it is removed.
+/// let mut released = StorageTracker::default();
+/// released.track(|| {
+///     balances.remove("alice");
+///     balances.flush();
+/// });
+/// assert_eq!(released.bytes_released(), tracker.bytes_added());
+/// assert_eq!(released.released_cost(), tracker.added_cost());
+/// # }
+/// ```
+///
+/// [`consume`]: StorageTracker::consume
+/// [`get_or_insert_child`]: crate::store::LookupMap::get_or_insert_child
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StorageTracker {
+    bytes_added: StorageUsage,
+    bytes_released: StorageUsage,
+    initial_storage_usage: Option<StorageUsage>,
+}
+
+impl StorageTracker {
+    /// Starts tracking the storage usage.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tracker was already started.
+    pub fn start(&mut self) {
+        if self.initial_storage_usage.replace(env::storage_usage()).is_some() {
+            env::panic_str("StorageTracker is already started");
+        }
+    }
+
+    /// Stops tracking the storage usage, and adds the difference of storage usage since the tracker
+    /// was started to the bytes added or released.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tracker was not started.
+    pub fn stop(&mut self) {
+        let initial = self
+            .initial_storage_usage
+            .take()
+            .unwrap_or_else(|| env::panic_str("StorageTracker was not started"));
+        let current = env::storage_usage();
+        if current >= initial {
+            self.bytes_added += current - initial;
+        } else {
+            self.bytes_released += initial - current;
+        }
+    }
+
+    /// Tracks the storage usage changes of `f`, which should flush any collection it modifies,
+    /// including nested collections.
+    pub fn track<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        self.start();
+        let result = f();
+        self.stop();
+        result
+    }
+
+    /// Returns `true` if the tracker is currently tracking a scope.
+    pub fn is_started(&self) -> bool {
+        self.initial_storage_usage.is_some()
+    }
+
+    /// Returns the number of bytes added to storage over the tracked scopes.
+    pub fn bytes_added(&self) -> StorageUsage {
+        self.bytes_added
+    }
+
+    /// Returns the number of bytes released from storage over the tracked scopes.
+    pub fn bytes_released(&self) -> StorageUsage {
+        self.bytes_released
+    }
+
+    /// Returns the cost of the bytes added to storage, at the current storage byte cost.
+    pub fn added_cost(&self) -> Balance {
+        Balance::from(self.bytes_added) * env::storage_byte_cost()
+    }
+
+    /// Returns the cost of the bytes released from storage, at the current storage byte cost.
+    pub fn released_cost(&self) -> Balance {
+        Balance::from(self.bytes_released) * env::storage_byte_cost()
+    }
+
+    /// Returns `true` if no bytes were added or released over the tracked scopes.
+    pub fn is_empty(&self) -> bool {
+        self.bytes_added == 0 && self.bytes_released == 0
+    }
+
+    /// Adds the bytes tracked by `other` to this tracker, and resets the counts of `other`.
+    pub fn consume(&mut self, other: &mut StorageTracker) {
+        self.bytes_added += core::mem::take(&mut other.bytes_added);
+        self.bytes_released += core::mem::take(&mut other.bytes_released);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StorageTracker;
+    use crate::store::{LookupMap, Vector};
+    use crate::test_utils::test_env::setup_free;
+    use crate::{env, AccountId};
+
+    #[test]
+    fn per_account_attribution() {
+        setup_free();
+        let mut owners: LookupMap<AccountId, Vector<u64>> = LookupMap::new(b"o");
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let carol: AccountId = "carol.near".parse().unwrap();
+
+        let mut alice_tracker = StorageTracker::default();
+        let mut carol_tracker = StorageTracker::default();
+        alice_tracker.track(|| {
+            let tokens = owners.get_or_insert_child(alice.clone());
+            tokens.extend([1, 2, 3]);
+            tokens.flush();
+            owners.flush();
+        });
+        carol_tracker.track(|| {
+            let tokens = owners.get_or_insert_child(carol.clone());
+            tokens.push(1);
+            tokens.flush();
+            owners.flush();
+        });
+
+        let mut element_tracker = StorageTracker::default();
+        element_tracker.track(|| {
+            let tokens = owners.get_mut(&carol).unwrap();
+            tokens.push(2);
+            tokens.flush();
+        });
+        // The account IDs have the same length, so the entries only differ by their elements.
+        let element_bytes = element_tracker.bytes_added();
+        assert!(element_bytes > 0);
+        assert_eq!(alice_tracker.bytes_added(), carol_tracker.bytes_added() + 2 * element_bytes);
+
+        let mut total = StorageTracker::default();
+        total.consume(&mut alice_tracker);
+        total.consume(&mut carol_tracker);
+        total.consume(&mut element_tracker);
+        assert!(alice_tracker.is_empty() && carol_tracker.is_empty());
+
+        let usage = env::storage_usage();
+        total.track(|| {
+            owners.remove_and_clear(&alice);
+            owners.remove_and_clear(&carol);
+            owners.flush();
+        });
+        assert_eq!(total.bytes_released(), usage - env::storage_usage());
+        assert_eq!(total.bytes_released(), total.bytes_added());
+    }
+
+    #[test]
+    #[should_panic(expected = "StorageTracker was not started")]
+    fn stop_without_start() {
+        StorageTracker::default().stop();
+    }
+}
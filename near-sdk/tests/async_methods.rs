@@ -0,0 +1,79 @@
+//! Callbacks generated for the `.await` points of async methods.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::test_utils::VMContextBuilder;
+use near_sdk::{
+    ext_contract, near_bindgen, testing_env, AccountId, PromiseResult, RuntimeFeesConfig, VMConfig,
+};
+
+#[ext_contract(ext_token)]
+pub trait Token {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: u128);
+}
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Contract {
+    transferred: u128,
+    attempts: u32,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub async fn pay(&mut self, token: AccountId, receiver_id: AccountId, amount: u128) {
+        ext_token::ext(token).ft_transfer(receiver_id, amount).await;
+        self.transferred += amount;
+    }
+
+    pub async fn try_pay(&mut self, token: AccountId, receiver_id: AccountId, amount: u128) {
+        let _: Result<(), near_sdk::PromiseError> =
+            ext_token::ext(token).ft_transfer(receiver_id, amount).await;
+        self.attempts += 1;
+    }
+}
+
+fn callback_context(result: PromiseResult) {
+    testing_env!(
+        VMContextBuilder::new().build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![result],
+    );
+}
+
+#[test]
+fn await_chains_callback() {
+    testing_env!(VMContextBuilder::new().build());
+    let mut contract = Contract::default();
+    drop(contract.pay("token.near".parse().unwrap(), "bob.near".parse().unwrap(), 10));
+    drop(contract.try_pay("token.near".parse().unwrap(), "bob.near".parse().unwrap(), 10));
+
+    let receipts = near_sdk::test_utils::get_created_receipts();
+    assert_eq!(receipts.len(), 4);
+    assert_eq!(receipts[0].receiver_id, "token.near".parse().unwrap());
+    assert_eq!(receipts[1].receiver_id, near_sdk::env::current_account_id());
+}
+
+#[test]
+fn unbound_await_succeeded() {
+    callback_context(PromiseResult::Successful(vec![]));
+    let mut contract = Contract::default();
+    contract.__pay_resume_1(10);
+    assert_eq!(contract.transferred, 10);
+}
+
+#[test]
+#[should_panic(expected = "The promise awaited by `pay` at `.await` number 1 failed")]
+fn unbound_await_failed() {
+    callback_context(PromiseResult::Failed);
+    Contract::default().__pay_resume_1(10);
+}
+
+#[test]
+fn discarded_result_failed() {
+    callback_context(PromiseResult::Failed);
+    let mut contract = Contract::default();
+    contract.__try_pay_resume_1();
+    assert_eq!(contract.attempts, 1);
+}